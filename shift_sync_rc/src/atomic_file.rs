@@ -0,0 +1,45 @@
+//! 一時ファイルに書いてから置き換える書き込み
+//!
+//! 書いている途中で止まっても、元のファイルが半端な中身で残らないようにする。
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// `path` を書くときの一時ファイル（同じディレクトリの `.<ファイル名>.tmp`）
+///
+/// 拡張子を付け替えると `a.tmp` 自身や、`a.ics` と `a.toml` で同じ名前になるので、ファイル名全体から作る。
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(path.file_name().unwrap_or(path.as_os_str()));
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// `contents` を `path` に書く（ディレクトリがなければ作る）
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = temp_path(path);
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_path_keeps_whole_file_name() {
+        assert_eq!(
+            temp_path(Path::new("/home/a/.shift_sync/config.toml")),
+            Path::new("/home/a/.shift_sync/.config.toml.tmp")
+        );
+        assert_eq!(temp_path(Path::new("a.tmp")), Path::new(".a.tmp.tmp"));
+        assert_ne!(
+            temp_path(Path::new("a.ics")),
+            temp_path(Path::new("a.toml"))
+        );
+    }
+}
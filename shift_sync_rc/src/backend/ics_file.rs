@@ -0,0 +1,411 @@
+//! ローカルの .ics ファイル（カレンダーアプリから購読する用）
+//!
+//! ファイル全体を読み、シフトの VEVENT だけを UID で追加・更新・削除して書き戻す。
+//! シフトと関係ない VEVENT・VTIMEZONE・カレンダーのプロパティ（X-WR-CALNAME など）は読んだときの行のまま残す。
+//! 書き出しは同期の最後（`finish`）に1回だけ、一時ファイルに書いてから置き換える。
+//!
+//! `TZID` 付きで書くときは、シフトが使うゾーンの VTIMEZONE も置く。
+//! このツールが置いた VTIMEZONE（`X-SHIFT-SYNC` 付き）はシフトの日付に合わせて作り直し、
+//! 他のアプリが置いた同じゾーンの VTIMEZONE があればそちらをそのまま使う。
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+
+use crate::atomic_file;
+use crate::backend::{BackendError, CalendarBackend, Capabilities, RemoteEvent};
+use crate::ics::{
+    IcsOptions, MARKER_PROPERTY, event_hash, generate_ics, generate_vevent, generate_vtimezone,
+    read_events, shift_event_uid,
+};
+use crate::shift::Shift;
+use crate::sync::SyncWindow;
+
+#[derive(Debug)]
+pub enum IcsFileError {
+    /// ファイルが読めない・書けない
+    Io {
+        path: PathBuf,
+        action: &'static str,
+        source: io::Error,
+    },
+    /// iCalendar として読めない
+    Invalid { path: PathBuf, message: String },
+}
+
+impl fmt::Display for IcsFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IcsFileError::Io {
+                path,
+                action,
+                source,
+            } => write!(f, "{} の{action}に失敗: {source}", path.display()),
+            IcsFileError::Invalid { path, message } => {
+                write!(
+                    f,
+                    "{} が iCalendar として読めない: {message}",
+                    path.display()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for IcsFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IcsFileError::Io { source, .. } => Some(source),
+            IcsFileError::Invalid { .. } => None,
+        }
+    }
+}
+
+impl BackendError for IcsFileError {
+    fn is_conflict(&self) -> bool {
+        false
+    }
+}
+
+/// VCALENDAR の中のコンポーネント1つ（VEVENT、VTIMEZONE など）
+#[derive(Debug, Clone)]
+struct Component {
+    /// ファイルの行（折り返したまま、改行なし）
+    lines: Vec<String>,
+    /// シフトの VEVENT なら UID と開始日
+    shift: Option<(String, Option<NaiveDate>)>,
+    /// シフトの VEVENT の DTSTART の TZID
+    zone: Option<String>,
+}
+
+impl Component {
+    fn new(lines: Vec<String>) -> Self {
+        let is_event = lines
+            .first()
+            .is_some_and(|l| l.eq_ignore_ascii_case("BEGIN:VEVENT"));
+        let text = lines.join("\r\n");
+        let shift = if is_event {
+            shift_event_uid(&text)
+        } else {
+            None
+        };
+        let zone = shift.as_ref().and_then(|_| {
+            let event = read_events(&text).into_iter().next()?;
+            Some(event.get("DTSTART")?.param("TZID")?.to_string())
+        });
+        Component { lines, shift, zone }
+    }
+
+    fn event(shift: &Shift, options: &IcsOptions) -> Self {
+        let ics = generate_vevent(shift, Utc::now(), options);
+        Component::new(ics.lines().map(str::to_string).collect())
+    }
+
+    fn uid(&self) -> Option<&str> {
+        self.shift.as_ref().map(|(uid, _)| uid.as_str())
+    }
+
+    /// VTIMEZONE なら TZID
+    fn tzid(&self) -> Option<&str> {
+        if !self.lines.first()?.eq_ignore_ascii_case("BEGIN:VTIMEZONE") {
+            return None;
+        }
+        self.lines
+            .iter()
+            .find_map(|l| l.strip_prefix("TZID:").map(str::trim))
+    }
+
+    /// このツールが置いた VTIMEZONE か
+    fn is_generated_timezone(&self) -> bool {
+        let marker = format!("{MARKER_PROPERTY}:");
+        self.tzid().is_some() && self.lines.iter().any(|l| l.starts_with(&marker))
+    }
+}
+
+/// 同期先としての .ics ファイル
+///
+/// ファイルには ETag も編集の記録もないので、内容が変わったシフトは上書きする。
+pub struct IcsFileBackend {
+    path: PathBuf,
+    /// VCALENDAR 直下のプロパティの行
+    properties: Vec<String>,
+    components: Vec<Component>,
+    options: IcsOptions,
+    changed: bool,
+}
+
+impl IcsFileBackend {
+    /// `path` の .ics を読み込む（なければ空のカレンダーから始める）
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, IcsFileError> {
+        let path = path.into();
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(source) => {
+                return Err(IcsFileError::Io {
+                    path,
+                    action: "読み込み",
+                    source,
+                });
+            }
+        };
+        let (properties, components) =
+            parse_calendar(&text).map_err(|message| IcsFileError::Invalid {
+                path: path.clone(),
+                message,
+            })?;
+        Ok(IcsFileBackend {
+            path,
+            properties,
+            components,
+            options: IcsOptions::default(),
+            changed: false,
+        })
+    }
+
+    /// 書き込むイベントの書き出し方（既定は `IcsOptions::default()`）
+    pub fn with_options(mut self, options: IcsOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 書き出す iCalendar
+    pub fn to_ics(&self) -> String {
+        let mut ics = String::from("BEGIN:VCALENDAR\r\n");
+        let lines = self
+            .properties
+            .iter()
+            .chain(self.components.iter().flat_map(|c| &c.lines));
+        for line in lines {
+            ics.push_str(line);
+            ics.push_str("\r\n");
+        }
+        ics.push_str("END:VCALENDAR\r\n");
+        ics
+    }
+
+    /// シフトが使うゾーンの VTIMEZONE を、シフトの日付の範囲に合わせて置き直す
+    fn refresh_timezones(&mut self) {
+        // TZID -> シフトの最初と最後の日
+        let mut ranges: Vec<(String, NaiveDate, NaiveDate)> = Vec::new();
+        for c in &self.components {
+            let (Some(zone), Some((_, Some(date)))) = (&c.zone, &c.shift) else {
+                continue;
+            };
+            match ranges.iter_mut().find(|(z, _, _)| z == zone) {
+                Some((_, first, last)) => {
+                    (*first, *last) = ((*first).min(*date), (*last).max(*date))
+                }
+                None => ranges.push((zone.clone(), *date, *date)),
+            }
+        }
+
+        self.components.retain(|c| {
+            !c.is_generated_timezone() || ranges.iter().any(|(z, _, _)| c.tzid() == Some(z))
+        });
+        for (zone, first, last) in ranges {
+            let Ok(tz) = zone.parse::<Tz>() else {
+                continue;
+            };
+            let existing = self.components.iter().position(|c| c.tzid() == Some(&zone));
+            if existing.is_some_and(|i| !self.components[i].is_generated_timezone()) {
+                continue;
+            }
+            // 日をまたぐシフトと、ゾーンによる日付のずれの分だけ広げる
+            let midnight =
+                |date: NaiveDate| -> DateTime<Utc> { date.and_time(NaiveTime::MIN).and_utc() };
+            let from = midnight(first.checked_sub_days(Days::new(1)).unwrap_or(first));
+            let to = midnight(last.checked_add_days(Days::new(3)).unwrap_or(last));
+            let mut lines: Vec<String> = generate_vtimezone(tz, from, to)
+                .lines()
+                .map(str::to_string)
+                .collect();
+            lines.insert(2, format!("{MARKER_PROPERTY}:{zone}"));
+            let timezone = Component::new(lines);
+            match existing {
+                Some(i) => self.components[i] = timezone,
+                None => {
+                    let first_event = self
+                        .components
+                        .iter()
+                        .position(|c| c.tzid().is_none())
+                        .unwrap_or(self.components.len());
+                    self.components.insert(first_event, timezone);
+                }
+            }
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        atomic_file::write(&self.path, self.to_ics())
+    }
+}
+
+impl CalendarBackend for IcsFileBackend {
+    type Error = IcsFileError;
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    fn content_hash(&self, shift: &Shift) -> String {
+        event_hash(&generate_vevent(shift, Utc::now(), &self.options))
+    }
+
+    fn list_events(&mut self, range: SyncWindow) -> Result<Vec<RemoteEvent>, IcsFileError> {
+        Ok(self
+            .components
+            .iter()
+            .filter_map(|c| {
+                let (uid, date) = c.shift.clone()?;
+                if date.is_some_and(|d| !range.contains(d)) {
+                    return None;
+                }
+                Some(RemoteEvent {
+                    id: uid.clone(),
+                    uid,
+                    etag: None,
+                    date,
+                    hash: event_hash(&c.lines.join("\r\n")),
+                    written_hash: None,
+                })
+            })
+            .collect())
+    }
+
+    fn create_event(&mut self, shift: &Shift) -> Result<(), IcsFileError> {
+        self.components.push(Component::event(shift, &self.options));
+        self.changed = true;
+        Ok(())
+    }
+
+    fn update_event(&mut self, current: &RemoteEvent, shift: &Shift) -> Result<(), IcsFileError> {
+        let event = Component::event(shift, &self.options);
+        match self
+            .components
+            .iter_mut()
+            .find(|c| c.uid() == Some(current.uid.as_str()))
+        {
+            Some(component) => *component = event,
+            None => self.components.push(event),
+        }
+        self.changed = true;
+        Ok(())
+    }
+
+    fn delete_event(&mut self, current: &RemoteEvent) -> Result<(), IcsFileError> {
+        self.components
+            .retain(|c| c.uid() != Some(current.uid.as_str()));
+        self.changed = true;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), IcsFileError> {
+        if !self.changed {
+            return Ok(());
+        }
+        self.refresh_timezones();
+        self.save().map_err(|source| IcsFileError::Io {
+            path: self.path.clone(),
+            action: "書き込み",
+            source,
+        })?;
+        self.changed = false;
+        Ok(())
+    }
+}
+
+/// VCALENDAR を直下のプロパティの行とコンポーネントに分ける
+///
+/// 空のファイルは空のカレンダーとして扱う。VCALENDAR が複数あれば1つにまとめる。
+fn parse_calendar(text: &str) -> Result<(Vec<String>, Vec<Component>), String> {
+    let mut properties = Vec::new();
+    let mut components = Vec::new();
+    // 読んでいる途中のコンポーネントの行と、BEGIN の入れ子の深さ
+    let mut current: Option<(Vec<String>, usize)> = None;
+    let mut in_calendar = false;
+    let mut found = false;
+
+    for line in text.lines().filter(|l| !l.is_empty()) {
+        // 折り返しの続きの行は BEGIN / END ではない
+        let head = if line.starts_with([' ', '\t']) {
+            String::new()
+        } else {
+            line.to_ascii_uppercase()
+        };
+        if let Some((lines, depth)) = current.as_mut() {
+            lines.push(line.to_string());
+            if head.starts_with("BEGIN:") {
+                *depth += 1;
+            } else if head.starts_with("END:") {
+                *depth -= 1;
+                if *depth == 0
+                    && let Some((lines, _)) = current.take()
+                {
+                    components.push(Component::new(lines));
+                }
+            }
+            continue;
+        }
+        match head.as_str() {
+            "BEGIN:VCALENDAR" => (in_calendar, found) = (true, true),
+            "END:VCALENDAR" => in_calendar = false,
+            _ if !in_calendar => return Err(format!("VCALENDAR の外に {line:?} がある")),
+            _ if head.starts_with("BEGIN:") => current = Some((vec![line.to_string()], 1)),
+            _ => properties.push(line.to_string()),
+        }
+    }
+    if let Some((lines, _)) = current {
+        return Err(format!("{} に END がない", lines[0]));
+    }
+    if in_calendar {
+        return Err("END:VCALENDAR がない".to_string());
+    }
+    if !found {
+        // 空のファイル
+        return parse_calendar(&generate_ics(&[]));
+    }
+    Ok((properties, components))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_calendar_into_components() {
+        let text = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nX-WR-CALNAME:バイ\r\n ト\r\n\
+BEGIN:VTIMEZONE\r\nTZID:Asia/Tokyo\r\nBEGIN:STANDARD\r\nTZOFFSETTO:+0900\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\n\
+BEGIN:VEVENT\r\nUID:dentist@example.com\r\nDTSTART:20260115T090000\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:shift-20260115-1000-1900-99055630\r\nDTSTART:20260115T100000\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let (properties, components) = parse_calendar(text).unwrap();
+        assert_eq!(properties, ["VERSION:2.0", "X-WR-CALNAME:バイ", " ト"]);
+        assert_eq!(components.len(), 3);
+        assert_eq!(components[0].lines.len(), 6);
+        assert_eq!(components[0].uid(), None);
+        assert_eq!(components[1].uid(), None);
+        assert_eq!(
+            components[2].uid(),
+            Some("shift-20260115-1000-1900-99055630")
+        );
+    }
+
+    #[test]
+    fn empty_file_is_an_empty_calendar() {
+        let (properties, components) = parse_calendar("").unwrap();
+        assert!(properties.iter().any(|p| p.starts_with("PRODID:")));
+        assert!(components.is_empty());
+
+        assert!(parse_calendar("BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:x\n").is_err());
+        assert!(parse_calendar("<html></html>").is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! メモリ上のカレンダー（テスト用の書き込み先）
+//!
+//! 書き込まれたシフトをそのまま持ち、スマホでの編集や書き込みの失敗を真似できる。
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use sha1::{Digest, Sha1};
+
+use crate::backend::{BackendError, CalendarBackend, Capabilities, RemoteEvent};
+use crate::shift::Shift;
+use crate::sync::{SyncAction, SyncWindow};
+
+/// メモリ上のイベント
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryEvent {
+    pub shift: Shift,
+    /// 書き換えのたびに増える番号（ETag の代わり）
+    pub version: u64,
+    /// このツールが最後に書き込んだ内容のハッシュ（他で作られたイベントは `None`）
+    pub written_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryError {
+    /// 一覧を取った後に書き換えられていた
+    Conflict { uid: String },
+    /// `fail_on` で失敗させた
+    Rejected { uid: String, action: SyncAction },
+}
+
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryError::Conflict { uid } => write!(f, "{uid} は他で変更されています"),
+            MemoryError::Rejected { uid, action } => write!(f, "{uid} の{action}は拒否されました"),
+        }
+    }
+}
+
+impl std::error::Error for MemoryError {}
+
+impl BackendError for MemoryError {
+    fn is_conflict(&self) -> bool {
+        matches!(self, MemoryError::Conflict { .. })
+    }
+}
+
+/// メモリ上のカレンダー
+#[derive(Debug, Clone)]
+pub struct MemoryBackend {
+    capabilities: Capabilities,
+    /// UID -> イベント
+    events: BTreeMap<String, MemoryEvent>,
+    next_version: u64,
+    /// 書き込みを拒否する UID
+    failing: HashSet<String>,
+    /// 一覧の後に他で書き換えられる（古い版を一覧に出す）UID
+    racing: HashSet<String>,
+    /// 行った書き込み（操作と UID）
+    log: Vec<(SyncAction, String)>,
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        MemoryBackend::new()
+    }
+}
+
+impl MemoryBackend {
+    /// ETag も編集の検出もある書き込み先
+    pub fn new() -> Self {
+        MemoryBackend::with_capabilities(Capabilities {
+            conditional_writes: true,
+            detects_edits: true,
+            incremental_listing: false,
+        })
+    }
+
+    pub fn with_capabilities(capabilities: Capabilities) -> Self {
+        MemoryBackend {
+            capabilities,
+            events: BTreeMap::new(),
+            next_version: 0,
+            failing: HashSet::new(),
+            racing: HashSet::new(),
+            log: Vec::new(),
+        }
+    }
+
+    /// 他のアプリで作られたイベントとして直接置く
+    pub fn insert(&mut self, shift: Shift) {
+        let version = self.bump();
+        self.events.insert(
+            shift.uid(),
+            MemoryEvent {
+                shift,
+                version,
+                written_hash: None,
+            },
+        );
+    }
+
+    /// イベントを直接書き換える（スマホで編集した想定）
+    pub fn edit(&mut self, uid: &str, edit: impl FnOnce(&mut Shift)) {
+        let version = self.bump();
+        let event = self.events.get_mut(uid).expect("event");
+        edit(&mut event.shift);
+        event.version = version;
+    }
+
+    /// `uid` への書き込みを失敗させる
+    pub fn fail_on(&mut self, uid: &str) {
+        self.failing.insert(uid.to_string());
+    }
+
+    /// `uid` を一覧の直後に他で書き換えられたことにする（条件付きの書き込みが競合する）
+    pub fn race_on(&mut self, uid: &str) {
+        self.racing.insert(uid.to_string());
+    }
+
+    pub fn get(&self, uid: &str) -> Option<&MemoryEvent> {
+        self.events.get(uid)
+    }
+
+    /// 置かれているシフト（UID 順）
+    pub fn shifts(&self) -> Vec<&Shift> {
+        self.events.values().map(|e| &e.shift).collect()
+    }
+
+    /// 行った書き込み（操作と UID）
+    pub fn writes(&self) -> &[(SyncAction, String)] {
+        &self.log
+    }
+
+    pub fn clear_writes(&mut self) {
+        self.log.clear();
+    }
+
+    fn bump(&mut self) -> u64 {
+        self.next_version += 1;
+        self.next_version
+    }
+
+    /// 書き込みの前の確認（拒否・ETag の照合）
+    fn check(&self, uid: &str, action: SyncAction, etag: Option<&str>) -> Result<(), MemoryError> {
+        if self.failing.contains(uid) {
+            return Err(MemoryError::Rejected {
+                uid: uid.to_string(),
+                action,
+            });
+        }
+        let current = self.events.get(uid).map(|e| e.version.to_string());
+        if self.capabilities.conditional_writes
+            && let Some(etag) = etag
+            && current.as_deref() != Some(etag)
+        {
+            return Err(MemoryError::Conflict {
+                uid: uid.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, action: SyncAction, shift: &Shift) {
+        let version = self.bump();
+        let hash = self.content_hash(shift);
+        self.events.insert(
+            shift.uid(),
+            MemoryEvent {
+                shift: shift.clone(),
+                version,
+                written_hash: Some(hash),
+            },
+        );
+        self.log.push((action, shift.uid()));
+    }
+}
+
+impl CalendarBackend for MemoryBackend {
+    type Error = MemoryError;
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    fn content_hash(&self, shift: &Shift) -> String {
+        let fields = [
+            shift.title.clone(),
+            shift.location.clone(),
+            shift.memo.clone(),
+            shift.start().to_rfc3339(),
+            shift.end().to_rfc3339(),
+        ];
+        let mut hasher = Sha1::new();
+        for field in fields {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    fn list_events(&mut self, range: SyncWindow) -> Result<Vec<RemoteEvent>, MemoryError> {
+        Ok(self
+            .events
+            .iter()
+            .filter(|(_, e)| {
+                e.shift.date() < range.end && range.start <= e.shift.end().date_naive()
+            })
+            .map(|(uid, e)| {
+                let version = if self.racing.contains(uid) {
+                    e.version - 1
+                } else {
+                    e.version
+                };
+                RemoteEvent {
+                    uid: uid.clone(),
+                    id: uid.clone(),
+                    etag: Some(version.to_string()),
+                    date: Some(e.shift.date()),
+                    hash: self.content_hash(&e.shift),
+                    written_hash: e
+                        .written_hash
+                        .clone()
+                        .filter(|_| self.capabilities.detects_edits),
+                }
+            })
+            .collect())
+    }
+
+    fn create_event(&mut self, shift: &Shift) -> Result<(), MemoryError> {
+        let uid = shift.uid();
+        self.check(&uid, SyncAction::Create, None)?;
+        if self.events.contains_key(&uid) && self.capabilities.conditional_writes {
+            return Err(MemoryError::Conflict { uid });
+        }
+        self.write(SyncAction::Create, shift);
+        Ok(())
+    }
+
+    fn update_event(&mut self, current: &RemoteEvent, shift: &Shift) -> Result<(), MemoryError> {
+        self.check(&current.uid, SyncAction::Update, current.etag.as_deref())?;
+        self.write(SyncAction::Update, shift);
+        Ok(())
+    }
+
+    fn delete_event(&mut self, current: &RemoteEvent) -> Result<(), MemoryError> {
+        self.check(&current.uid, SyncAction::Delete, current.etag.as_deref())?;
+        self.events.remove(&current.uid);
+        self.log.push((SyncAction::Delete, current.uid.clone()));
+        Ok(())
+    }
+}
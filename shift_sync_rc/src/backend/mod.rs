@@ -0,0 +1,80 @@
+//! 同期先のカレンダー
+//!
+//! Go版は iCloud の CalDAV、Swift版は EventKit か Google に決め打ちで書き込んでいる。
+//! こちらは書き込み先を `CalendarBackend` にまとめ、同期の手順（`sync::sync_shifts`）はどれにも同じものを使う。
+
+pub mod ics_file;
+pub mod memory;
+
+use chrono::NaiveDate;
+
+use crate::shift::Shift;
+use crate::sync::SyncWindow;
+
+/// 書き込み先ができること
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// ETag などで、一覧を取った後に他で書き換えられたイベントへの書き込みを止められる
+    pub conditional_writes: bool,
+    /// 書き込んだ後に他で編集されたイベントを見分けられる（`RemoteEvent::written_hash` が入る）
+    pub detects_edits: bool,
+    /// 前回からの差分だけで一覧を取れる（CalDAV の sync-token など）
+    pub incremental_listing: bool,
+}
+
+/// 書き込み先にあるシフトのイベント
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEvent {
+    /// シフトの UID（`shift-...`）
+    pub uid: String,
+    /// 書き込み先でのイベントの場所（CalDAV のリソース URL、Google のイベントID など）
+    pub id: String,
+    pub etag: Option<String>,
+    /// シフトの開始日（削除してよい範囲かどうかの判定に使う）
+    pub date: Option<NaiveDate>,
+    /// 今の内容のハッシュ（`CalendarBackend::content_hash` と比べられる値）
+    pub hash: String,
+    /// このツールが最後に書き込んだ内容のハッシュ（分からなければ `None`）
+    pub written_hash: Option<String>,
+}
+
+impl RemoteEvent {
+    /// 書き込んだ後に他で編集されているか
+    pub fn is_edited(&self) -> bool {
+        self.written_hash.as_ref().is_some_and(|w| *w != self.hash)
+    }
+}
+
+/// 書き込み先のエラー
+pub trait BackendError: std::error::Error {
+    /// 他で変更されていて書き込めなかった（412 など）
+    fn is_conflict(&self) -> bool;
+}
+
+/// シフトを書き込むカレンダー
+///
+/// 一覧・追加・更新・削除だけを受け持ち、どれを追加・更新・削除するかは `sync::sync_shifts` が決める。
+pub trait CalendarBackend {
+    type Error: BackendError;
+
+    fn capabilities(&self) -> Capabilities;
+
+    /// `shift` を書き込んだときの内容のハッシュ（`RemoteEvent::hash` と同じ方法で計算する）
+    fn content_hash(&self, shift: &Shift) -> String;
+
+    /// `range` にかかるシフトのイベント（シフトと関係ない予定は含めない）
+    fn list_events(&mut self, range: SyncWindow) -> Result<Vec<RemoteEvent>, Self::Error>;
+
+    fn create_event(&mut self, shift: &Shift) -> Result<(), Self::Error>;
+
+    /// `current` を `shift` の内容で置き換える（できれば `current.etag` を条件にする）
+    fn update_event(&mut self, current: &RemoteEvent, shift: &Shift) -> Result<(), Self::Error>;
+
+    /// `current` を削除する（できれば `current.etag` を条件にする）
+    fn delete_event(&mut self, current: &RemoteEvent) -> Result<(), Self::Error>;
+
+    /// 同期の最後に呼ばれる。書き込みをまとめて反映する書き込み先（ファイルなど）はここで書き出す
+    fn finish(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,815 @@
+//! CalDAV (RFC 4791) クライアント
+//!
+//! iCloud に限らず、任意の CalDAV サーバーで principal → calendar-home-set → カレンダー一覧の順に探索する。
+
+pub mod state;
+pub mod sync;
+pub mod xml;
+
+use std::fmt;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use ureq::Agent;
+use ureq::http::Request;
+use url::Url;
+
+use self::xml::{APPLE_ICAL, CALDAV, DAV, Multistatus, parse_multistatus};
+use crate::ics::{generate_timezone_ics, shift_event_uid};
+
+/// iCloud の CalDAV 入口
+pub const ICLOUD_URL: &str = "https://caldav.icloud.com/";
+
+const MAX_REDIRECTS: usize = 5;
+
+/// CalDAV サーバーとのやりとりで起きたエラー
+#[derive(Debug)]
+pub enum CalDavError {
+    /// URL として読めない
+    InvalidUrl(String),
+    /// 接続できない・応答が読めないなど
+    Http { url: String, source: ureq::Error },
+    /// リダイレクトが続きすぎた
+    TooManyRedirects(String),
+    /// 想定外のステータスが返ってきた
+    Status {
+        method: String,
+        url: String,
+        status: u16,
+        body: String,
+    },
+    /// 応答の XML が読めない
+    Xml { url: String, message: String },
+    /// 必要なプロパティが応答になかった
+    MissingProperty { url: String, property: &'static str },
+    /// sync-token が無効になっている（期限切れなど）
+    InvalidSyncToken { url: String },
+    /// If-Match / If-None-Match の条件が合わなかった（412、他で変更された）
+    PreconditionFailed { method: String, url: String },
+}
+
+impl fmt::Display for CalDavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalDavError::InvalidUrl(url) => write!(f, "URL が変やで: {url}"),
+            CalDavError::Http { url, source } => write!(f, "{url}: {source}"),
+            CalDavError::TooManyRedirects(url) => write!(f, "{url}: リダイレクトが多すぎます"),
+            CalDavError::Status {
+                method,
+                url,
+                status,
+                body,
+            } => write!(f, "{method} {url} status={status} body={body}"),
+            CalDavError::Xml { url, message } => write!(f, "{url} の応答が読めない: {message}"),
+            CalDavError::MissingProperty { url, property } => {
+                write!(f, "{url} から {property} が取れんかった…")
+            }
+            CalDavError::InvalidSyncToken { url } => {
+                write!(f, "{url}: sync-token が無効です")
+            }
+            CalDavError::PreconditionFailed { method, url } => {
+                write!(f, "{method} {url}: 他で変更されています (412)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CalDavError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CalDavError::Http { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl CalDavError {
+    /// 認証エラー（ID やアプリ用パスワードの間違い）かどうか
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(
+            self,
+            CalDavError::Status {
+                status: 401 | 403,
+                ..
+            }
+        )
+    }
+
+    /// 412（ETag が合わない・すでに存在する）かどうか
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, CalDavError::PreconditionFailed { .. })
+    }
+}
+
+/// カレンダーコレクション
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calendar {
+    pub url: String,
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    /// Apple の `calendar-color`（"#FF2968FF" など）
+    pub color: Option<String>,
+    /// `supported-calendar-component-set` の中身（"VEVENT" など）。空なら制限なし
+    pub components: Vec<String>,
+}
+
+impl Calendar {
+    /// 表示名（未設定なら Go版と同じ "(no name)"）
+    pub fn name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or("(no name)")
+    }
+
+    /// VEVENT を置けるかどうか
+    pub fn supports_events(&self) -> bool {
+        self.components.is_empty() || self.components.iter().any(|c| c == "VEVENT")
+    }
+}
+
+/// 探索結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discovery {
+    pub principal_url: String,
+    pub home_url: String,
+    pub calendars: Vec<Calendar>,
+}
+
+/// MKCALENDAR で作るカレンダーの設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewCalendar {
+    pub display_name: String,
+    pub description: Option<String>,
+    /// Apple の `calendar-color`（`normalize_color` で揃えたもの）
+    pub color: Option<String>,
+    /// `calendar-timezone` に入れるタイムゾーン
+    pub timezone: Option<Tz>,
+}
+
+impl NewCalendar {
+    pub fn new(display_name: &str) -> Self {
+        NewCalendar {
+            display_name: display_name.to_string(),
+            description: None,
+            color: None,
+            timezone: None,
+        }
+    }
+}
+
+/// カレンダー内のリソース（中身はまだ見ていない）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceRef {
+    pub url: String,
+    pub etag: Option<String>,
+}
+
+/// このツールが書いたシフトのイベント
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventResource {
+    pub url: String,
+    /// シフトの UID（`shift-...`）
+    pub uid: String,
+    pub etag: Option<String>,
+    /// シフトの開始日（削除してよい範囲かどうかの判定に使う）
+    pub date: Option<NaiveDate>,
+}
+
+impl EventResource {
+    /// カレンダーデータからこのツールのイベントかどうかを見分ける
+    ///
+    /// `X-SHIFT-SYNC` があればその値を、なければ `shift-` で始まる UID をシフトの UID とする。
+    /// リソース名は見ないので、他のクライアントが名前を変えたイベントも見つかる。
+    pub fn identify(resource: ResourceRef, ics: &str) -> Option<Self> {
+        let (uid, date) = shift_event_uid(ics)?;
+        Some(EventResource {
+            url: resource.url,
+            uid,
+            etag: resource.etag,
+            date,
+        })
+    }
+}
+
+/// 前回の sync-token からの差分（RFC 6578）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncDelta {
+    /// 追加・変更されたリソース
+    pub changed: Vec<ResourceRef>,
+    /// 削除されたリソースの URL
+    pub removed: Vec<String>,
+    pub sync_token: String,
+}
+
+/// GET したイベント
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventBody {
+    pub ics: String,
+    pub etag: Option<String>,
+}
+
+/// 書き込みの前提条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precondition<'a> {
+    /// 条件なしで上書き
+    None,
+    /// まだ存在しないときだけ作る（`If-None-Match: *`）
+    Absent,
+    /// ETag が一致するときだけ書き換える（`If-Match`）
+    Matches(&'a str),
+}
+
+impl Precondition<'_> {
+    fn header(&self) -> Option<(&'static str, &str)> {
+        match self {
+            Precondition::None => None,
+            Precondition::Absent => Some(("If-None-Match", "*")),
+            Precondition::Matches(etag) => Some(("If-Match", etag)),
+        }
+    }
+}
+
+/// HTTP 応答（リダイレクト後の URL 付き）
+pub(crate) struct Reply {
+    pub url: Url,
+    pub status: u16,
+    pub etag: Option<String>,
+    pub body: String,
+}
+
+/// Basic 認証で CalDAV サーバーと話すクライアント
+pub struct CalDavClient {
+    agent: Agent,
+    authorization: String,
+}
+
+impl CalDavClient {
+    pub fn new(username: &str, password: &str) -> Self {
+        let agent = Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(30)))
+            .http_status_as_error(false)
+            .allow_non_standard_methods(true)
+            .max_redirects(0)
+            .build()
+            .into();
+        CalDavClient {
+            agent,
+            authorization: format!("Basic {}", BASE64.encode(format!("{username}:{password}"))),
+        }
+    }
+
+    /// リクエストを送る
+    ///
+    /// PROPFIND などもメソッドと本文を保ったままリダイレクトを追う。
+    /// 認証ヘッダは同じホストへのリダイレクトにだけ付け直す。
+    pub(crate) fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> Result<Reply, CalDavError> {
+        let mut url = Url::parse(url).map_err(|_| CalDavError::InvalidUrl(url.to_string()))?;
+        let origin = url.origin();
+
+        for _ in 0..=MAX_REDIRECTS {
+            let mut builder = Request::builder().method(method).uri(url.as_str());
+            if url.origin() == origin {
+                builder = builder.header("Authorization", &self.authorization);
+            }
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            let request = builder
+                .body(body.to_string())
+                .map_err(|_| CalDavError::InvalidUrl(url.to_string()))?;
+
+            let mut resp = self
+                .agent
+                .run(request)
+                .map_err(|source| CalDavError::Http {
+                    url: url.to_string(),
+                    source,
+                })?;
+            let status = resp.status().as_u16();
+
+            let location = resp.headers().get("Location").and_then(|v| v.to_str().ok());
+            if let (301 | 302 | 307 | 308, Some(location)) = (status, location) {
+                url = url
+                    .join(location)
+                    .map_err(|_| CalDavError::InvalidUrl(location.to_string()))?;
+                continue;
+            }
+
+            let etag = resp
+                .headers()
+                .get("ETag")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            let body = resp
+                .body_mut()
+                .read_to_string()
+                .map_err(|source| CalDavError::Http {
+                    url: url.to_string(),
+                    source,
+                })?;
+            return Ok(Reply {
+                url,
+                status,
+                etag,
+                body,
+            });
+        }
+        Err(CalDavError::TooManyRedirects(url.to_string()))
+    }
+
+    /// PROPFIND を送って multistatus を読む
+    /// Go版: propfind
+    pub(crate) fn propfind(
+        &self,
+        url: &str,
+        depth: &str,
+        body: &str,
+    ) -> Result<(Url, Multistatus), CalDavError> {
+        self.dav_request("PROPFIND", url, depth, body)
+    }
+
+    /// 応答が multistatus になるリクエスト（PROPFIND / REPORT）を送る
+    pub(crate) fn dav_request(
+        &self,
+        method: &str,
+        url: &str,
+        depth: &str,
+        body: &str,
+    ) -> Result<(Url, Multistatus), CalDavError> {
+        let reply = self.send(
+            method,
+            url,
+            &[
+                ("Depth", depth),
+                ("Content-Type", "application/xml; charset=utf-8"),
+            ],
+            body,
+        )?;
+        if !(200..300).contains(&reply.status) {
+            return Err(CalDavError::Status {
+                method: method.to_string(),
+                url: reply.url.to_string(),
+                status: reply.status,
+                body: reply.body,
+            });
+        }
+        let multistatus = parse_multistatus(&reply.body).map_err(|message| CalDavError::Xml {
+            url: reply.url.to_string(),
+            message,
+        })?;
+        Ok((reply.url, multistatus))
+    }
+
+    /// ログインユーザーの principal URL
+    /// Go版: getCurrentUserPrincipal
+    pub fn current_user_principal(&self, url: &str) -> Result<String, CalDavError> {
+        let body = r#"<?xml version="1.0" encoding="utf-8" ?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:current-user-principal/>
+  </d:prop>
+</d:propfind>
+"#;
+        let (base, ms) = self.propfind(url, "0", body)?;
+        ms.responses
+            .iter()
+            .find_map(|r| r.prop(DAV, "current-user-principal")?.href())
+            .map(|href| resolve_href(&base, href))
+            .ok_or(CalDavError::MissingProperty {
+                url: base.to_string(),
+                property: "current-user-principal",
+            })
+    }
+
+    /// principal のカレンダーホーム URL
+    /// Go版: getCalendarHomeSet
+    pub fn calendar_home_set(&self, principal_url: &str) -> Result<String, CalDavError> {
+        let body = r#"<?xml version="1.0" encoding="utf-8" ?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <c:calendar-home-set/>
+  </d:prop>
+</d:propfind>
+"#;
+        let (base, ms) = self.propfind(principal_url, "0", body)?;
+        ms.responses
+            .iter()
+            .find_map(|r| r.prop(CALDAV, "calendar-home-set")?.href())
+            .map(|href| resolve_href(&base, href))
+            .ok_or(CalDavError::MissingProperty {
+                url: base.to_string(),
+                property: "calendar-home-set",
+            })
+    }
+
+    /// カレンダーホーム直下のカレンダー一覧
+    /// Go版: listCalendars
+    pub fn list_calendars(&self, home_url: &str) -> Result<Vec<Calendar>, CalDavError> {
+        let (base, ms) = self.propfind(home_url, "1", CALENDAR_PROPS)?;
+        Ok(ms
+            .responses
+            .iter()
+            .filter_map(|r| calendar_from_response(&base, r))
+            .collect())
+    }
+
+    /// 1つのカレンダーのプロパティ
+    /// Go版: getCalendarDisplayName
+    pub fn calendar(&self, calendar_url: &str) -> Result<Calendar, CalDavError> {
+        let (base, ms) = self.propfind(calendar_url, "0", CALENDAR_PROPS)?;
+        ms.responses
+            .iter()
+            .find_map(|r| calendar_from_response(&base, r))
+            .ok_or(CalDavError::MissingProperty {
+                url: base.to_string(),
+                property: "resourcetype/calendar",
+            })
+    }
+
+    /// `calendar_url` にカレンダーを作る（MKCALENDAR、RFC 4791 5.3.1）
+    /// Go版: createCalendar
+    pub fn make_calendar(
+        &self,
+        calendar_url: &str,
+        calendar: &NewCalendar,
+    ) -> Result<(), CalDavError> {
+        let mut props = format!(
+            "      <d:displayname>{}</d:displayname>\n",
+            xml::escape(&calendar.display_name)
+        );
+        if let Some(description) = &calendar.description {
+            props.push_str(&format!(
+                "      <c:calendar-description>{}</c:calendar-description>\n",
+                xml::escape(description)
+            ));
+        }
+        if let Some(color) = &calendar.color {
+            props.push_str(&format!(
+                "      <a:calendar-color>{}</a:calendar-color>\n",
+                xml::escape(color)
+            ));
+        }
+        if let Some(tz) = calendar.timezone {
+            props.push_str(&format!(
+                "      <c:calendar-timezone>{}</c:calendar-timezone>\n",
+                // CRLF が XML の改行の正規化で LF にならないようにする
+                xml::escape(&generate_timezone_ics(tz, Utc::now())).replace('\r', "&#13;")
+            ));
+        }
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+<c:mkcalendar xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:a="http://apple.com/ns/ical/">
+  <d:set>
+    <d:prop>
+{props}      <c:supported-calendar-component-set>
+        <c:comp name="VEVENT"/>
+      </c:supported-calendar-component-set>
+    </d:prop>
+  </d:set>
+</c:mkcalendar>
+"#
+        );
+        let reply = self.send(
+            "MKCALENDAR",
+            calendar_url,
+            &[("Content-Type", "application/xml; charset=utf-8")],
+            &body,
+        )?;
+        expect_status("MKCALENDAR", reply, &[200, 201]).map(|_| ())
+    }
+
+    /// カレンダーホーム直下に UUID のパスで新しいカレンダーを作り、作られたカレンダーを返す
+    /// Go版: createNewCalendarFlow
+    pub fn create_calendar(
+        &self,
+        home_url: &str,
+        calendar: &NewCalendar,
+    ) -> Result<Calendar, CalDavError> {
+        let url = new_calendar_url(home_url);
+        self.make_calendar(&url, calendar)?;
+        self.calendar(&url)
+    }
+
+    /// カレンダーの現在の sync-token（サーバーが対応していなければ `None`）
+    pub fn sync_token(&self, calendar_url: &str) -> Result<Option<String>, CalDavError> {
+        let body = r#"<?xml version="1.0" encoding="utf-8" ?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:sync-token/>
+  </d:prop>
+</d:propfind>
+"#;
+        let (_, ms) = self.propfind(calendar_url, "0", body)?;
+        Ok(ms
+            .responses
+            .iter()
+            .find_map(|r| r.prop_text(DAV, "sync-token"))
+            .map(str::to_string))
+    }
+
+    /// `start` 〜 `end`（日付、`end` は含まない）にかかる VEVENT を calendar-query REPORT で探す
+    ///
+    /// タイムゾーンの違いで取りこぼさないよう、前後1日ずつ広げて問い合わせる。
+    pub fn query_events(
+        &self,
+        calendar_url: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<ResourceRef>, CalDavError> {
+        let utc = |date: NaiveDate| date.format("%Y%m%dT000000Z").to_string();
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT">
+        <c:time-range start="{}" end="{}"/>
+      </c:comp-filter>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>
+"#,
+            utc(start - Days::new(1)),
+            utc(end + Days::new(1))
+        );
+        let (base, ms) = self.dav_request("REPORT", calendar_url, "1", &body)?;
+        Ok(ms
+            .responses
+            .iter()
+            .filter(|r| !r.href.is_empty())
+            .map(|r| ResourceRef {
+                url: resolve_href(&base, &r.href),
+                etag: r.prop_text(DAV, "getetag").map(str::to_string),
+            })
+            .collect())
+    }
+
+    /// `urls` のカレンダーデータを calendar-multiget REPORT でまとめて取る
+    pub fn multiget(
+        &self,
+        calendar_url: &str,
+        urls: &[String],
+    ) -> Result<Vec<(ResourceRef, String)>, CalDavError> {
+        if urls.is_empty() {
+            return Ok(Vec::new());
+        }
+        let hrefs: String = urls
+            .iter()
+            .map(|url| {
+                let path = Url::parse(url).map(|u| u.path().to_string());
+                format!(
+                    "  <d:href>{}</d:href>\n",
+                    xml::escape(path.as_deref().unwrap_or(url))
+                )
+            })
+            .collect();
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+<c:calendar-multiget xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+    <c:calendar-data/>
+  </d:prop>
+{hrefs}</c:calendar-multiget>
+"#
+        );
+        let (base, ms) = self.dav_request("REPORT", calendar_url, "1", &body)?;
+        Ok(ms
+            .responses
+            .iter()
+            .filter_map(|r| {
+                let ics = r.prop(CALDAV, "calendar-data")?.text.clone();
+                let resource = ResourceRef {
+                    url: resolve_href(&base, &r.href),
+                    etag: r.prop_text(DAV, "getetag").map(str::to_string),
+                };
+                Some((resource, ics))
+            })
+            .collect())
+    }
+
+    /// `sync_token` 以降に変わったリソースを sync-collection REPORT で取る
+    ///
+    /// トークンが無効なら `CalDavError::InvalidSyncToken`。
+    pub fn sync_collection(
+        &self,
+        calendar_url: &str,
+        sync_token: &str,
+    ) -> Result<SyncDelta, CalDavError> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+<d:sync-collection xmlns:d="DAV:">
+  <d:sync-token>{}</d:sync-token>
+  <d:sync-level>1</d:sync-level>
+  <d:prop>
+    <d:getetag/>
+  </d:prop>
+</d:sync-collection>
+"#,
+            xml::escape(sync_token)
+        );
+        let (base, ms) = match self.dav_request("REPORT", calendar_url, "0", &body) {
+            Err(CalDavError::Status { url, body, .. }) if body.contains("valid-sync-token") => {
+                return Err(CalDavError::InvalidSyncToken { url });
+            }
+            result => result?,
+        };
+        let sync_token = ms.sync_token.clone().ok_or(CalDavError::MissingProperty {
+            url: base.to_string(),
+            property: "sync-token",
+        })?;
+
+        let mut changed = Vec::new();
+        let mut removed = Vec::new();
+        for response in ms.responses.iter().filter(|r| !r.href.is_empty()) {
+            let url = resolve_href(&base, &response.href);
+            if response.status == Some(404) {
+                removed.push(url);
+            } else if !is_collection(response) {
+                changed.push(ResourceRef {
+                    url,
+                    etag: response.prop_text(DAV, "getetag").map(str::to_string),
+                });
+            }
+        }
+        Ok(SyncDelta {
+            changed,
+            removed,
+            sync_token,
+        })
+    }
+
+    /// イベントのカレンダーデータと ETag を取得する
+    pub fn get_event(&self, event_url: &str) -> Result<EventBody, CalDavError> {
+        let reply = self.send("GET", event_url, &[], "")?;
+        let reply = expect_status("GET", reply, &[200])?;
+        Ok(EventBody {
+            ics: reply.body,
+            etag: reply.etag,
+        })
+    }
+
+    /// イベントを作成・上書きし、サーバーが返した新しい ETag を返す
+    ///
+    /// 条件が合わなければ `CalDavError::PreconditionFailed`。
+    pub fn put_event(
+        &self,
+        event_url: &str,
+        ics: &str,
+        precondition: Precondition<'_>,
+    ) -> Result<Option<String>, CalDavError> {
+        let mut headers = vec![("Content-Type", "text/calendar; charset=utf-8")];
+        headers.extend(precondition.header());
+        let reply = self.send("PUT", event_url, &headers, ics)?;
+        expect_status("PUT", reply, &[200, 201, 204]).map(|r| r.etag)
+    }
+
+    /// イベントを削除する（すでに無ければ成功扱い）
+    pub fn delete_event(
+        &self,
+        event_url: &str,
+        precondition: Precondition<'_>,
+    ) -> Result<(), CalDavError> {
+        let headers: Vec<_> = precondition.header().into_iter().collect();
+        let reply = self.send("DELETE", event_url, &headers, "")?;
+        expect_status("DELETE", reply, &[200, 204, 404]).map(|_| ())
+    }
+
+    /// `start_url` から principal・カレンダーホーム・カレンダー一覧を探す
+    ///
+    /// `start_url` で current-user-principal が取れなければ、RFC 6764 の
+    /// `/.well-known/caldav` からもう一度探す。
+    /// Go版: discoverCalendars
+    pub fn discover(&self, start_url: &str) -> Result<Discovery, CalDavError> {
+        let principal_url = match self.current_user_principal(start_url) {
+            Ok(url) => url,
+            Err(err) if err.is_unauthorized() => return Err(err),
+            Err(err) => {
+                let well_known = Url::parse(start_url)
+                    .and_then(|u| u.join("/.well-known/caldav"))
+                    .map_err(|_| CalDavError::InvalidUrl(start_url.to_string()))?;
+                if well_known.as_str() == start_url {
+                    return Err(err);
+                }
+                self.current_user_principal(well_known.as_str())
+                    .map_err(|_| err)?
+            }
+        };
+        let home_url = self.calendar_home_set(&principal_url)?;
+        let calendars = self.list_calendars(&home_url)?;
+        Ok(Discovery {
+            principal_url,
+            home_url,
+            calendars,
+        })
+    }
+}
+
+const CALENDAR_PROPS: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:a="http://apple.com/ns/ical/">
+  <d:prop>
+    <d:displayname/>
+    <d:resourcetype/>
+    <c:calendar-description/>
+    <c:supported-calendar-component-set/>
+    <a:calendar-color/>
+  </d:prop>
+</d:propfind>
+"#;
+
+/// resourcetype に calendar を持つ応答だけを Calendar にする
+fn calendar_from_response(base: &Url, response: &xml::Response) -> Option<Calendar> {
+    response
+        .prop(DAV, "resourcetype")?
+        .child(CALDAV, "calendar")?;
+    if response.href.is_empty() {
+        return None;
+    }
+    Some(Calendar {
+        url: resolve_href(base, &response.href),
+        display_name: response.prop_text(DAV, "displayname").map(str::to_string),
+        description: response
+            .prop_text(CALDAV, "calendar-description")
+            .map(str::to_string),
+        color: response
+            .prop_text(APPLE_ICAL, "calendar-color")
+            .map(str::to_string),
+        components: response
+            .prop(CALDAV, "supported-calendar-component-set")
+            .map(|set| {
+                set.children_named(CALDAV, "comp")
+                    .filter_map(|c| c.attribute("name"))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+fn is_collection(response: &xml::Response) -> bool {
+    response
+        .prop(DAV, "resourcetype")
+        .is_some_and(|t| t.child(DAV, "collection").is_some())
+}
+
+/// カレンダー内で UID に対応するリソースの URL
+pub fn event_url(calendar_url: &str, uid: &str) -> String {
+    format!("{}/{uid}.ics", calendar_url.trim_end_matches('/'))
+}
+
+/// カレンダーホーム直下の新しいカレンダーの URL（パスはランダムな UUID）
+/// Go版: generateUUID
+pub fn new_calendar_url(home_url: &str) -> String {
+    format!(
+        "{}/{}/",
+        home_url.trim_end_matches('/'),
+        uuid::Uuid::new_v4()
+    )
+}
+
+/// "FF2968" / "#ff2968" / "#FF2968FF" -> "#FF2968FF"（Apple の `calendar-color` の形）
+pub fn normalize_color(text: &str) -> Option<String> {
+    let hex = text.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let alpha = if hex.len() == 6 { "FF" } else { "" };
+    Some(format!("#{}{alpha}", hex.to_ascii_uppercase()))
+}
+
+fn expect_status(method: &str, reply: Reply, expected: &[u16]) -> Result<Reply, CalDavError> {
+    if expected.contains(&reply.status) {
+        return Ok(reply);
+    }
+    if reply.status == 412 {
+        return Err(CalDavError::PreconditionFailed {
+            method: method.to_string(),
+            url: reply.url.to_string(),
+        });
+    }
+    Err(CalDavError::Status {
+        method: method.to_string(),
+        url: reply.url.to_string(),
+        status: reply.status,
+        body: reply.body,
+    })
+}
+
+/// 応答中の href をリクエスト先 URL 基準の絶対 URL にする
+/// Go版: resolveHref
+pub(crate) fn resolve_href(base: &Url, href: &str) -> String {
+    base.join(href)
+        .map(String::from)
+        .unwrap_or_else(|_| href.to_string())
+}
@@ -0,0 +1,370 @@
+//! 前回の同期で書き込んだイベントの記録
+//!
+//! ETag と内容のハッシュを UID ごとに残しておき、次回の同期で
+//! 変わっていないイベントの GET / PUT を省き、スマホ側での編集を見分ける。
+//! 見つけたシフトのイベントの一覧と sync-token も残し、次回は差分だけを取る（RFC 6578）。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use crate::atomic_file;
+use crate::caldav::{EventResource, SyncDelta};
+use crate::sync::SyncWindow;
+
+const HEADER: &str = "# shift_sync_rc CalDAV state v2";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 前回書き込んだときのイベント
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedEvent {
+    pub etag: String,
+    /// 生成したカレンダーデータの比較対象プロパティのハッシュ
+    pub hash: String,
+}
+
+/// 1つのカレンダーについての同期状態
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncState {
+    pub calendar_url: String,
+    /// 一覧を最後に取ったときの sync-token
+    pub sync_token: Option<String>,
+    /// 一覧を calendar-query で取った日付の範囲
+    pub covered: Option<SyncWindow>,
+    /// UID -> シフトのイベント（最後に取った一覧に、その後の差分と書き込みを反映したもの）
+    resources: BTreeMap<String, EventResource>,
+    /// UID -> このツールが最後に書き込んだ内容
+    events: BTreeMap<String, RecordedEvent>,
+}
+
+impl SyncState {
+    pub fn new(calendar_url: &str) -> Self {
+        SyncState {
+            calendar_url: calendar_url.to_string(),
+            ..SyncState::default()
+        }
+    }
+
+    /// `path` から読む
+    ///
+    /// ファイルがない、または別のカレンダーの記録なら空の状態から始める。
+    pub fn load(path: &Path, calendar_url: &str) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(SyncState::parse(&text)
+                .filter(|s| s.calendar_url == calendar_url)
+                .unwrap_or_else(|| SyncState::new(calendar_url))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(SyncState::new(calendar_url)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// `path` に書き出す（一時ファイルに書いてから置き換える）
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        atomic_file::write(path, self.to_text())
+    }
+
+    pub fn resources(&self) -> impl Iterator<Item = &EventResource> {
+        self.resources.values()
+    }
+
+    pub fn resource(&self, uid: &str) -> Option<&EventResource> {
+        self.resources.get(uid)
+    }
+
+    pub fn resource_by_url(&self, url: &str) -> Option<&EventResource> {
+        self.resources.values().find(|r| r.url == url)
+    }
+
+    /// 前回取った一覧が `range` を含んでいるか（差分だけ取れば足りるか）
+    pub fn covers(&self, range: SyncWindow) -> bool {
+        self.covered
+            .is_some_and(|c| c.start <= range.start && range.end <= c.end)
+    }
+
+    /// `range` を取り直した一覧で置き換える
+    ///
+    /// 一覧を置き換え、書き込みの記録を捨てるのは `range` の日付のイベントだけ
+    /// （日付の分からないものも含む）。前回の一覧が `range` と重なるか隣り合っていれば、
+    /// 一覧を取った範囲をつなげる。
+    pub fn replace_resources(
+        &mut self,
+        events: Vec<EventResource>,
+        sync_token: Option<String>,
+        range: SyncWindow,
+    ) {
+        let in_range = |r: &EventResource| r.date.is_none_or(|d| range.contains(d));
+        let removed: Vec<String> = self
+            .resources
+            .values()
+            .filter(|r| in_range(r))
+            .map(|r| r.uid.clone())
+            .collect();
+        for uid in &removed {
+            self.resources.remove(uid);
+        }
+        for event in events {
+            self.resources.insert(event.uid.clone(), event);
+        }
+        self.sync_token = sync_token;
+        self.covered = Some(match self.covered {
+            Some(c) if c.start <= range.end && range.start <= c.end => SyncWindow {
+                start: c.start.min(range.start),
+                end: c.end.max(range.end),
+            },
+            _ => range,
+        });
+        self.drop_records(removed);
+    }
+
+    /// sync-collection の差分を反映する
+    ///
+    /// `identified` は `delta.changed` のうちシフトのイベントだと分かったもの。
+    pub fn apply_delta(&mut self, delta: &SyncDelta, identified: Vec<EventResource>) {
+        let removed: Vec<String> = self
+            .resources
+            .values()
+            .filter(|r| {
+                delta.removed.contains(&r.url) || delta.changed.iter().any(|c| c.url == r.url)
+            })
+            .map(|r| r.uid.clone())
+            .collect();
+        for uid in &removed {
+            self.resources.remove(uid);
+        }
+        for event in identified {
+            self.resources.insert(event.uid.clone(), event);
+        }
+        self.sync_token = Some(delta.sync_token.clone());
+        self.drop_records(removed);
+    }
+
+    /// 書き込んだリソースを一覧に反映する
+    pub fn upsert_resource(&mut self, resource: EventResource) {
+        self.resources.insert(resource.uid.clone(), resource);
+    }
+
+    pub fn remove_resource(&mut self, uid: &str) {
+        self.resources.remove(uid);
+        self.events.remove(uid);
+    }
+
+    /// 一覧から外れたまま戻らなかったイベントの書き込み記録を捨てる
+    fn drop_records(&mut self, removed: Vec<String>) {
+        for uid in removed {
+            if !self.resources.contains_key(&uid) {
+                self.events.remove(&uid);
+            }
+        }
+    }
+
+    pub fn get(&self, uid: &str) -> Option<&RecordedEvent> {
+        self.events.get(uid)
+    }
+
+    pub fn record(&mut self, uid: &str, etag: &str, hash: &str) {
+        self.events.insert(
+            uid.to_string(),
+            RecordedEvent {
+                etag: etag.to_string(),
+                hash: hash.to_string(),
+            },
+        );
+    }
+
+    pub fn forget(&mut self, uid: &str) {
+        self.events.remove(uid);
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()? != HEADER {
+            return None;
+        }
+        let mut state = SyncState::default();
+        for line in lines {
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields.as_slice() {
+                ["calendar", url] => state.calendar_url = url.to_string(),
+                ["token", token] => state.sync_token = Some(token.to_string()),
+                ["range", start, end] => {
+                    state.covered = Some(SyncWindow {
+                        start: NaiveDate::parse_from_str(start, DATE_FORMAT).ok()?,
+                        end: NaiveDate::parse_from_str(end, DATE_FORMAT).ok()?,
+                    })
+                }
+                ["resource", uid, url, etag, date] => state.upsert_resource(EventResource {
+                    url: url.to_string(),
+                    uid: uid.to_string(),
+                    etag: Some(etag.to_string()).filter(|e| !e.is_empty()),
+                    date: NaiveDate::parse_from_str(date, DATE_FORMAT).ok(),
+                }),
+                ["event", uid, etag, hash] => state.record(uid, etag, hash),
+                _ => {}
+            }
+        }
+        Some(state)
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!("{HEADER}\ncalendar\t{}\n", self.calendar_url);
+        if let Some(token) = &self.sync_token {
+            text.push_str(&format!("token\t{token}\n"));
+        }
+        if let Some(range) = &self.covered {
+            text.push_str(&format!(
+                "range\t{}\t{}\n",
+                range.start.format(DATE_FORMAT),
+                range.end.format(DATE_FORMAT)
+            ));
+        }
+        for (uid, resource) in &self.resources {
+            text.push_str(&format!(
+                "resource\t{uid}\t{}\t{}\t{}\n",
+                resource.url,
+                resource.etag.as_deref().unwrap_or_default(),
+                resource
+                    .date
+                    .map(|d| d.format(DATE_FORMAT).to_string())
+                    .unwrap_or_default()
+            ));
+        }
+        for (uid, event) in &self.events {
+            text.push_str(&format!("event\t{uid}\t{}\t{}\n", event.etag, event.hash));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::caldav::ResourceRef;
+
+    const CALENDAR: &str = "https://caldav.example.com/cal/";
+
+    fn resource(uid: &str) -> EventResource {
+        EventResource {
+            url: format!("{CALENDAR}{uid}.ics"),
+            uid: uid.to_string(),
+            etag: Some("\"1\"".to_string()),
+            date: NaiveDate::from_ymd_opt(2026, 1, 15),
+        }
+    }
+
+    fn month(m: u32) -> SyncWindow {
+        SyncWindow {
+            start: NaiveDate::from_ymd_opt(2026, m, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2026, m + 1, 1).unwrap(),
+        }
+    }
+
+    fn january() -> SyncWindow {
+        month(1)
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let mut state = SyncState::new(CALENDAR);
+        let mut undated = resource("shift-b");
+        undated.etag = None;
+        undated.date = None;
+        state.replace_resources(
+            vec![resource("shift-a"), undated],
+            Some("http://example.com/sync/1".to_string()),
+            january(),
+        );
+        state.record("shift-a", "\"abc\"", "0123");
+        let text = state.to_text();
+        assert_eq!(SyncState::parse(&text), Some(state));
+    }
+
+    #[test]
+    fn delta_replaces_changed_urls() {
+        let mut state = SyncState::new(CALENDAR);
+        state.replace_resources(
+            vec![resource("shift-a"), resource("shift-b")],
+            Some("t1".to_string()),
+            january(),
+        );
+        state.record("shift-a", "\"1\"", "h");
+
+        // shift-a は消え、shift-b のリソースは別のイベントに書き換えられた
+        let delta = SyncDelta {
+            changed: vec![ResourceRef {
+                url: resource("shift-b").url,
+                etag: Some("\"2\"".to_string()),
+            }],
+            removed: vec![resource("shift-a").url],
+            sync_token: "t2".to_string(),
+        };
+        state.apply_delta(&delta, vec![resource("shift-c")]);
+        let uids: Vec<&str> = state.resources().map(|r| r.uid.as_str()).collect();
+        assert_eq!(uids, ["shift-c"]);
+        assert_eq!(state.get("shift-a"), None);
+        assert_eq!(state.sync_token.as_deref(), Some("t2"));
+        assert!(state.covers(january()));
+    }
+
+    #[test]
+    fn listing_replaces_only_its_range() {
+        let march = |uid: &str| EventResource {
+            date: NaiveDate::from_ymd_opt(2026, 3, 10),
+            ..resource(uid)
+        };
+        let mut state = SyncState::new(CALENDAR);
+        state.replace_resources(
+            vec![resource("shift-jan"), resource("shift-gone")],
+            Some("t1".to_string()),
+            january(),
+        );
+        state.record("shift-jan", "\"1\"", "h");
+        state.record("shift-gone", "\"1\"", "h");
+
+        // 3月だけ取り直しても、1月の一覧と書き込みの記録は残る
+        state.replace_resources(vec![march("shift-mar")], Some("t2".to_string()), month(3));
+        assert!(state.resource("shift-jan").is_some());
+        assert!(state.get("shift-jan").is_some());
+        assert!(state.resource("shift-mar").is_some());
+        assert_eq!(state.covered, Some(month(3)));
+
+        // 1月を取り直すと、1月から消えたものだけ捨てる
+        state.replace_resources(
+            vec![resource("shift-jan")],
+            Some("t3".to_string()),
+            january(),
+        );
+        assert!(state.get("shift-jan").is_some());
+        assert_eq!(state.get("shift-gone"), None);
+        assert!(state.resource("shift-gone").is_none());
+        assert!(state.resource("shift-mar").is_some());
+
+        // 隣の月なら、取った範囲をつなげる
+        state.replace_resources(vec![], Some("t4".to_string()), month(2));
+        assert!(state.covers(SyncWindow {
+            start: january().start,
+            end: month(2).end,
+        }));
+        assert_eq!(state.sync_token.as_deref(), Some("t4"));
+    }
+
+    #[test]
+    fn ignores_other_calendar_and_unknown_files() {
+        let dir = std::env::temp_dir().join(format!("shift_sync_state_{}", std::process::id()));
+        let path = dir.join("caldav_state.txt");
+        let mut state = SyncState::new("https://a.example.com/cal/");
+        state.record("shift-x", "\"1\"", "h");
+        state.save(&path).unwrap();
+
+        assert_eq!(
+            SyncState::load(&path, "https://a.example.com/cal/").unwrap(),
+            state
+        );
+        let other = SyncState::load(&path, "https://b.example.com/cal/").unwrap();
+        assert_eq!(other.get("shift-x"), None);
+        assert_eq!(SyncState::parse("garbage"), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
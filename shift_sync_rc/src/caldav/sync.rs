@@ -0,0 +1,236 @@
+//! 同期先としての CalDAV カレンダー
+//! Go版: syncShiftsToCalDAV
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::backend::{BackendError, CalendarBackend, Capabilities, RemoteEvent};
+use crate::caldav::state::SyncState;
+use crate::caldav::{
+    CalDavClient, CalDavError, EventResource, Precondition, ResourceRef, event_url,
+};
+use crate::ics::{IcsOptions, event_hash, generate_event_ics_with};
+use crate::shift::Shift;
+use crate::sync::SyncWindow;
+
+/// 同期先としての CalDAV カレンダー
+///
+/// シフトのイベントはリソース名ではなく中身（UID の接頭辞か `X-SHIFT-SYNC`）で見分ける。
+/// 一覧は calendar-query で範囲内だけを取り、次回からは sync-token からの差分だけを取る。
+/// 書き込んだイベントの ETag と内容のハッシュを `state` に残し、
+/// 前回書き込んだときから ETag が変わっていないイベントは中身も取りに行かない。
+/// 追加は `If-None-Match: *`、更新・削除は `If-Match` で書き込む。
+pub struct CalDavBackend<'a> {
+    client: &'a CalDavClient,
+    state: &'a mut SyncState,
+    /// 一覧を取るときに読んだカレンダーデータ（URL -> データ）
+    bodies: HashMap<String, String>,
+    options: IcsOptions,
+}
+
+impl<'a> CalDavBackend<'a> {
+    pub fn new(client: &'a CalDavClient, state: &'a mut SyncState) -> Self {
+        CalDavBackend {
+            client,
+            state,
+            bodies: HashMap::new(),
+            options: IcsOptions::default(),
+        }
+    }
+
+    /// 書き込むイベントの書き出し方（既定は `IcsOptions::default()`）
+    pub fn with_options(mut self, options: IcsOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn event_ics(&self, shift: &Shift) -> String {
+        generate_event_ics_with(shift, Utc::now(), &self.options)
+    }
+
+    /// 書き込んだ結果を記録と一覧に反映する
+    fn written(&mut self, url: String, shift: &Shift, hash: &str, etag: Option<String>) {
+        let uid = shift.uid();
+        match &etag {
+            Some(etag) => self.state.record(&uid, etag, hash),
+            // ETag を返さないサーバーでは次回中身を取って確かめる
+            None => self.state.forget(&uid),
+        }
+        self.state.upsert_resource(EventResource {
+            url,
+            uid,
+            etag,
+            date: Some(shift.date()),
+        });
+    }
+}
+
+impl BackendError for CalDavError {
+    fn is_conflict(&self) -> bool {
+        CalDavError::is_conflict(self)
+    }
+}
+
+impl CalendarBackend for CalDavBackend<'_> {
+    type Error = CalDavError;
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            conditional_writes: true,
+            detects_edits: true,
+            incremental_listing: true,
+        }
+    }
+
+    fn content_hash(&self, shift: &Shift) -> String {
+        event_hash(&self.event_ics(shift))
+    }
+
+    fn list_events(&mut self, range: SyncWindow) -> Result<Vec<RemoteEvent>, CalDavError> {
+        self.bodies.clear();
+        refresh_listing(self.client, self.state, range, &mut self.bodies)?;
+
+        // 前回書き込んだときと ETag が違う（記録がない）のに中身を読んでいないものを取る
+        let unread: Vec<String> = self
+            .state
+            .resources()
+            .filter(|r| recorded_hash(self.state, r).is_none())
+            .filter(|r| !self.bodies.contains_key(&r.url))
+            .map(|r| r.url.clone())
+            .collect();
+        for (resource, ics) in self.client.multiget(&self.state.calendar_url, &unread)? {
+            self.bodies.insert(resource.url, ics);
+        }
+
+        let mut events = Vec::new();
+        let resources: Vec<EventResource> = self.state.resources().cloned().collect();
+        for resource in resources {
+            let written_hash = self.state.get(&resource.uid).map(|r| r.hash.clone());
+            let hash = match recorded_hash(self.state, &resource) {
+                Some(hash) => hash,
+                None => {
+                    let hash = self
+                        .bodies
+                        .get(&resource.url)
+                        .map(|ics| event_hash(ics))
+                        .unwrap_or_default();
+                    // サーバーが ETag だけ付け直した場合は記録を新しい ETag に合わせる
+                    if let Some(etag) = &resource.etag
+                        && written_hash.as_ref() == Some(&hash)
+                    {
+                        self.state.record(&resource.uid, etag, &hash);
+                    }
+                    hash
+                }
+            };
+            events.push(RemoteEvent {
+                uid: resource.uid,
+                id: resource.url,
+                etag: resource.etag,
+                date: resource.date,
+                hash,
+                written_hash,
+            });
+        }
+        Ok(events)
+    }
+
+    fn create_event(&mut self, shift: &Shift) -> Result<(), CalDavError> {
+        let url = event_url(&self.state.calendar_url, &shift.uid());
+        let ics = self.event_ics(shift);
+        let etag = self.client.put_event(&url, &ics, Precondition::Absent)?;
+        self.written(url, shift, &event_hash(&ics), etag);
+        Ok(())
+    }
+
+    fn update_event(&mut self, current: &RemoteEvent, shift: &Shift) -> Result<(), CalDavError> {
+        let ics = self.event_ics(shift);
+        let etag = self
+            .client
+            .put_event(&current.id, &ics, if_match(current.etag.as_deref()))?;
+        self.written(current.id.clone(), shift, &event_hash(&ics), etag);
+        Ok(())
+    }
+
+    fn delete_event(&mut self, current: &RemoteEvent) -> Result<(), CalDavError> {
+        self.client
+            .delete_event(&current.id, if_match(current.etag.as_deref()))?;
+        self.state.remove_resource(&current.uid);
+        Ok(())
+    }
+}
+
+/// 前回書き込んだまま ETag が変わっていなければ、そのときの内容のハッシュ
+fn recorded_hash(state: &SyncState, resource: &EventResource) -> Option<String> {
+    state
+        .get(&resource.uid)
+        .filter(|r| resource.etag.as_deref() == Some(r.etag.as_str()))
+        .map(|r| r.hash.clone())
+}
+
+/// `state` のシフトのイベント一覧を最新にする
+///
+/// sync-token があれば、まず sync-collection で差分だけを取る。前回の一覧が `range` を
+/// 含んでいればそれで足りる。含んでいなければ（トークンが無効・サーバーが未対応の場合も）
+/// `range` を calendar-query で取り直す。
+fn refresh_listing(
+    client: &CalDavClient,
+    state: &mut SyncState,
+    range: SyncWindow,
+    bodies: &mut HashMap<String, String>,
+) -> Result<(), CalDavError> {
+    let calendar_url = state.calendar_url.clone();
+    if let Some(token) = state.sync_token.clone() {
+        match client.sync_collection(&calendar_url, &token) {
+            Ok(delta) => {
+                let identified = identify_all(client, state, &delta.changed, bodies)?;
+                state.apply_delta(&delta, identified);
+                if state.covers(range) {
+                    return Ok(());
+                }
+            }
+            Err(err) if err.is_unauthorized() => return Err(err),
+            // 差分が取れなければ、前回の一覧が今も正しいとは言えない
+            Err(_) => state.covered = None,
+        }
+    }
+    // 問い合わせ中の変更を取りこぼさないよう、先にトークンを取っておく
+    let token = client.sync_token(&calendar_url)?;
+    let found = client.query_events(&calendar_url, range.start, range.end)?;
+    let events = identify_all(client, state, &found, bodies)?;
+    state.replace_resources(events, token, range);
+    Ok(())
+}
+
+/// リソースの中身を calendar-multiget で取り、シフトのイベントだけを返す
+///
+/// 記録と ETag が同じものは中身を取り直さない。取った中身は `bodies` に入れる。
+fn identify_all(
+    client: &CalDavClient,
+    state: &SyncState,
+    resources: &[ResourceRef],
+    bodies: &mut HashMap<String, String>,
+) -> Result<Vec<EventResource>, CalDavError> {
+    let mut events = Vec::new();
+    let mut unknown = Vec::new();
+    for resource in resources {
+        match state.resource_by_url(&resource.url) {
+            Some(known) if resource.etag.is_some() && known.etag == resource.etag => {
+                events.push(known.clone());
+            }
+            _ => unknown.push(resource.url.clone()),
+        }
+    }
+    for (resource, ics) in client.multiget(&state.calendar_url, &unknown)? {
+        if let Some(event) = EventResource::identify(resource, &ics) {
+            bodies.insert(event.url.clone(), ics);
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+fn if_match(etag: Option<&str>) -> Precondition<'_> {
+    etag.map_or(Precondition::None, Precondition::Matches)
+}
@@ -0,0 +1,218 @@
+//! WebDAV の multistatus 応答の読み取り
+
+use roxmltree::{Document, Node};
+
+pub const DAV: &str = "DAV:";
+pub const CALDAV: &str = "urn:ietf:params:xml:ns:caldav";
+pub const APPLE_ICAL: &str = "http://apple.com/ns/ical/";
+
+/// 名前空間付きの XML 要素（`roxmltree` の木から切り離して持ち回れるようにしたもの）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    pub namespace: String,
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    /// 直下のテキストを連結して前後の空白を落としたもの
+    pub text: String,
+    pub children: Vec<Element>,
+}
+
+impl Element {
+    fn from_node(node: Node<'_, '_>) -> Self {
+        Element {
+            namespace: node.tag_name().namespace().unwrap_or_default().to_string(),
+            name: node.tag_name().name().to_string(),
+            attributes: node
+                .attributes()
+                .map(|a| (a.name().to_string(), a.value().to_string()))
+                .collect(),
+            text: node
+                .children()
+                .filter(|c| c.is_text())
+                .filter_map(|c| c.text())
+                .collect::<String>()
+                .trim()
+                .to_string(),
+            children: node
+                .children()
+                .filter(|c| c.is_element())
+                .map(Element::from_node)
+                .collect(),
+        }
+    }
+
+    pub fn is(&self, namespace: &str, name: &str) -> bool {
+        self.namespace == namespace && self.name == name
+    }
+
+    pub fn child(&self, namespace: &str, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.is(namespace, name))
+    }
+
+    pub fn children_named<'a>(
+        &'a self,
+        namespace: &'a str,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |c| c.is(namespace, name))
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// `<D:href>` 子要素の値
+    pub fn href(&self) -> Option<&str> {
+        self.child(DAV, "href")
+            .map(|h| h.text.as_str())
+            .filter(|h| !h.is_empty())
+    }
+}
+
+/// multistatus 内の `<D:response>` 1件分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub href: String,
+    /// `<D:response>` 直下の `<D:status>`（リソース自体の状態。404 など）
+    pub status: Option<u16>,
+    /// 2xx の propstat に入っていたプロパティ
+    pub props: Vec<Element>,
+}
+
+impl Response {
+    pub fn prop(&self, namespace: &str, name: &str) -> Option<&Element> {
+        self.props.iter().find(|p| p.is(namespace, name))
+    }
+
+    /// プロパティのテキスト（空なら `None`）
+    pub fn prop_text(&self, namespace: &str, name: &str) -> Option<&str> {
+        self.prop(namespace, name)
+            .map(|p| p.text.as_str())
+            .filter(|t| !t.is_empty())
+    }
+}
+
+/// multistatus 全体
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Multistatus {
+    pub responses: Vec<Response>,
+    /// sync-collection REPORT の応答に入っている新しい sync-token（RFC 6578）
+    pub sync_token: Option<String>,
+}
+
+/// `<D:multistatus>` を読む
+pub fn parse_multistatus(body: &str) -> Result<Multistatus, String> {
+    let doc = Document::parse(body).map_err(|err| err.to_string())?;
+    let root = Element::from_node(doc.root_element());
+    if !root.is(DAV, "multistatus") {
+        return Err(format!("multistatus ではなく {} が返ってきた", root.name));
+    }
+
+    let responses = root
+        .children_named(DAV, "response")
+        .map(|r| Response {
+            href: r.href().unwrap_or_default().to_string(),
+            status: r.child(DAV, "status").and_then(|s| parse_status(&s.text)),
+            props: r
+                .children_named(DAV, "propstat")
+                .filter(|ps| {
+                    ps.child(DAV, "status")
+                        .and_then(|s| parse_status(&s.text))
+                        .is_none_or(|code| (200..300).contains(&code))
+                })
+                .filter_map(|ps| ps.child(DAV, "prop"))
+                .flat_map(|p| p.children.iter().cloned())
+                .collect(),
+        })
+        .collect();
+
+    let sync_token = root
+        .child(DAV, "sync-token")
+        .map(|t| t.text.clone())
+        .filter(|t| !t.is_empty());
+    Ok(Multistatus {
+        responses,
+        sync_token,
+    })
+}
+
+/// リクエスト本文に埋め込むテキストのエスケープ
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// "HTTP/1.1 200 OK" -> 200
+fn parse_status(line: &str) -> Option<u16> {
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_props_and_ignores_failed_propstat() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<multistatus xmlns="DAV:">
+  <response>
+    <href>/1234/calendars/home/</href>
+    <propstat>
+      <prop>
+        <displayname>仕事</displayname>
+        <calendar-color xmlns="http://apple.com/ns/ical/">#FF2968FF</calendar-color>
+      </prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+    <propstat>
+      <prop><calendar-description xmlns="urn:ietf:params:xml:ns:caldav"/></prop>
+      <status>HTTP/1.1 404 Not Found</status>
+    </propstat>
+  </response>
+  <response>
+    <href>/1234/calendars/gone/</href>
+    <status>HTTP/1.1 404 Not Found</status>
+  </response>
+</multistatus>"#;
+        let ms = parse_multistatus(body).unwrap();
+        assert_eq!(ms.responses.len(), 2);
+
+        let home = &ms.responses[0];
+        assert_eq!(home.href, "/1234/calendars/home/");
+        assert_eq!(home.status, None);
+        assert_eq!(home.prop_text(DAV, "displayname"), Some("仕事"));
+        assert_eq!(
+            home.prop_text(APPLE_ICAL, "calendar-color"),
+            Some("#FF2968FF")
+        );
+        assert!(home.prop(CALDAV, "calendar-description").is_none());
+
+        assert_eq!(ms.responses[1].status, Some(404));
+    }
+
+    #[test]
+    fn reads_sync_token() {
+        let body = r#"<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/cal/shift-a.ics</d:href>
+    <d:status>HTTP/1.1 404 Not Found</d:status>
+  </d:response>
+  <d:sync-token>http://example.com/sync/42</d:sync-token>
+</d:multistatus>"#;
+        let ms = parse_multistatus(body).unwrap();
+        assert_eq!(ms.sync_token.as_deref(), Some("http://example.com/sync/42"));
+        assert_eq!(ms.responses[0].status, Some(404));
+        assert_eq!(escape("a&b<c>\"d\""), "a&amp;b&lt;c&gt;&quot;d&quot;");
+    }
+
+    #[test]
+    fn rejects_non_multistatus() {
+        assert!(parse_multistatus("<error xmlns=\"DAV:\"/>").is_err());
+        assert!(parse_multistatus("not xml").is_err());
+    }
+}
@@ -0,0 +1,646 @@
+//! 設定ファイル（~/.shift_sync/config.toml）
+//!
+//! Go版と同じファイル・同じ形で `[shiftweb]` と `[icloud]` を読み書きするので、
+//! Go版でセットアップした設定がそのまま使える。Rust版だけの設定は `[shift_sync_rc]` の下に
+//! 版番号付きで置く（Go版は知らない表を読み飛ばす）。
+//!
+//! ```toml
+//! [shiftweb]
+//! id = "12345"
+//!
+//! [icloud]
+//! apple_id = "user@example.com"
+//! calendar_url = "https://caldav.icloud.com/123/calendars/work/"
+//!
+//! [shift_sync_rc]
+//! version = 1
+//! sources = ["shiftweb", "csv:second_job.csv;location=マック 渋谷"]
+//!
+//! [shift_sync_rc.templates]
+//! summary = "🍔 {shop} ({hours}h)"
+//! description = "{start}〜{end} 見込み{wage}円"
+//! hourly_wage = 1200
+//!
+//! [shift_sync_rc.templates.hourly_wages]
+//! "マック 渋谷" = 1300
+//!
+//! [shift_sync_rc.reminders]
+//! default = [60, 15]
+//! by_location = { "マック 渋谷" = [30] }
+//!
+//! [shift_sync_rc.google]
+//! calendar_id = "abc@group.calendar.google.com"
+//! ```
+//!
+//! 古い版のファイルは読み込むときに今の版へ直す。テンプレートなども読み込むときに確かめ、
+//! 書き間違いは同期を始める前にエラーにする。
+//! Go版が設定を保存すると `[shift_sync_rc]` は消えるので、そのときは設定し直す。
+//! Go版: config, loadConfig, saveConfig
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use crate::atomic_file;
+use crate::reminder::{MAX_MINUTES, Reminders};
+use crate::source::SourceSpec;
+use crate::template::{EventTemplates, Template, TemplateError, Variable};
+
+/// Go版: configFileName
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Rust版だけの設定を置く表
+pub const EXTENSION_TABLE: &str = "shift_sync_rc";
+
+/// `[shift_sync_rc]` の今の版
+pub const CONFIG_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` は版 n+1 のファイルを版 n+2 に直す（書き換えたら true）
+///
+/// 版 0 は `[shift_sync_rc]` のない Go版のファイルで、直すものはない（Rust版の設定は既定のまま）。
+const MIGRATIONS: [fn(&mut Table) -> bool; CONFIG_VERSION as usize - 1] = [];
+
+/// 設定ファイルが読み書きできない理由
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        /// "読み込み" / "保存"
+        action: &'static str,
+        source: io::Error,
+    },
+    /// TOML として読めない・型が合わない
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    /// このツールより新しい版（か、版が数でない）
+    UnsupportedVersion { path: PathBuf, version: String },
+    /// テンプレートが読めない（`field` は "shift_sync_rc.templates.summary" など）
+    Template {
+        path: PathBuf,
+        field: String,
+        source: TemplateError,
+    },
+    /// `{wage}` を使っているのに時給がない
+    MissingWage { path: PathBuf, field: String },
+    /// 取得元・通知などの値が不正
+    Invalid {
+        path: PathBuf,
+        field: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io {
+                path,
+                action,
+                source,
+            } => write!(f, "{} の{action}に失敗: {source}", path.display()),
+            ConfigError::Parse { path, source } => {
+                write!(f, "{} が読めない: {source}", path.display())
+            }
+            ConfigError::UnsupportedVersion { path, version } => write!(
+                f,
+                "{} の {EXTENSION_TABLE}.version = {version} には対応していません（v{CONFIG_VERSION} まで）",
+                path.display()
+            ),
+            ConfigError::Template {
+                path,
+                field,
+                source,
+            } => write!(f, "{} の {field} が不正です: {source}", path.display()),
+            ConfigError::MissingWage { path, field } => write!(
+                f,
+                "{} の {field} で {{wage}} を使うには hourly_wage か hourly_wages が必要です",
+                path.display()
+            ),
+            ConfigError::Invalid {
+                path,
+                field,
+                message,
+            } => write!(f, "{} の {field} が不正です: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            ConfigError::Parse { source, .. } => Some(source),
+            ConfigError::Template { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// `[shiftweb]`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShiftWebConfig {
+    /// ログインID（パスワードは保存しない）
+    pub id: String,
+}
+
+/// `[icloud]`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ICloudConfig {
+    /// アプリ用パスワードは保存しない
+    pub apple_id: String,
+    /// 同期先のカレンダー（-setup で選んだもの）
+    pub calendar_url: String,
+}
+
+/// `[shift_sync_rc.google]`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GoogleConfig {
+    /// -sync -google の同期先カレンダー
+    pub calendar_id: Option<String>,
+}
+
+/// 設定
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub shiftweb: ShiftWebConfig,
+    pub icloud: ICloudConfig,
+    /// シフトの取得元（空なら ShiftWeb だけ）
+    pub sources: Vec<SourceSpec>,
+    /// イベントの中身のテンプレート
+    pub templates: EventTemplates,
+    /// イベントに付ける通知
+    pub reminders: Reminders,
+    pub google: GoogleConfig,
+    /// 読み込んだファイルを今の版に直したとき、元の版
+    pub migrated_from: Option<u32>,
+    /// このツールの知らない表・キー（保存するときにそのまま書き戻す）
+    other: Table,
+}
+
+/// ファイルに書かれたままの設定
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RawConfig {
+    #[serde(default)]
+    shiftweb: RawShiftWeb,
+    #[serde(default)]
+    icloud: RawICloud,
+    #[serde(rename = "shift_sync_rc", default)]
+    extension: RawExtension,
+    #[serde(flatten)]
+    other: Table,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RawShiftWeb {
+    #[serde(default)]
+    id: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RawICloud {
+    #[serde(default)]
+    apple_id: String,
+    #[serde(default)]
+    calendar_url: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RawExtension {
+    #[serde(default)]
+    version: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sources: Vec<String>,
+    #[serde(default, skip_serializing_if = "RawTemplates::is_empty")]
+    templates: RawTemplates,
+    #[serde(default, skip_serializing_if = "RawReminders::is_empty")]
+    reminders: RawReminders,
+    #[serde(default, skip_serializing_if = "RawGoogle::is_empty")]
+    google: RawGoogle,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RawTemplates {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hourly_wage: Option<u32>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    hourly_wages: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RawReminders {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    default: Vec<u32>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    by_location: BTreeMap<String, Vec<u32>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RawGoogle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    calendar_id: Option<String>,
+}
+
+impl RawTemplates {
+    fn is_empty(&self) -> bool {
+        self.summary.is_none()
+            && self.location.is_none()
+            && self.description.is_none()
+            && self.hourly_wage.is_none()
+            && self.hourly_wages.is_empty()
+    }
+}
+
+impl RawReminders {
+    fn is_empty(&self) -> bool {
+        self.default.is_empty() && self.by_location.is_empty()
+    }
+}
+
+impl RawGoogle {
+    fn is_empty(&self) -> bool {
+        self.calendar_id.is_none()
+    }
+}
+
+impl Config {
+    /// `path` の設定を読む（ファイルがなければ既定の設定）
+    ///
+    /// 古い版なら今の版に直して読む（ファイルは書き換えないので、`migrated_from` を見て `save` する）。
+    /// Go版: loadConfig
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Config::parse(path, &text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(source) => Err(ConfigError::Io {
+                path: path.to_path_buf(),
+                action: "読み込み",
+                source,
+            }),
+        }
+    }
+
+    /// TOML の本文を読む（`path` はエラーの表示用）
+    pub fn parse(path: &Path, text: &str) -> Result<Self, ConfigError> {
+        Config::parse_with(path, text, &MIGRATIONS)
+    }
+
+    /// `migrations` で直しながら読む（テストで架空の版を試す用）
+    fn parse_with(
+        path: &Path,
+        text: &str,
+        migrations: &[fn(&mut Table) -> bool],
+    ) -> Result<Self, ConfigError> {
+        let parse_error = |source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut table: Table = toml::from_str(text).map_err(parse_error)?;
+        let migrated_from = migrate(&mut table, path, migrations)?;
+        let raw: RawConfig = Value::Table(table).try_into().map_err(parse_error)?;
+        let field = |name: &str| format!("{EXTENSION_TABLE}.{name}");
+        let invalid = |name: &str, message: String| ConfigError::Invalid {
+            path: path.to_path_buf(),
+            field: field(name),
+            message,
+        };
+
+        let ext = raw.extension;
+        let sources = ext
+            .sources
+            .iter()
+            .map(|text| text.parse::<SourceSpec>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| invalid("sources", err.to_string()))?;
+
+        let mut reminders = Reminders::new(&ext.reminders.default);
+        for (location, minutes) in &ext.reminders.by_location {
+            reminders = reminders.at(location.as_str(), minutes);
+        }
+        let all_minutes = ext
+            .reminders
+            .by_location
+            .values()
+            .flatten()
+            .chain(&ext.reminders.default);
+        if let Some(minutes) = all_minutes.into_iter().find(|&&m| m > MAX_MINUTES) {
+            return Err(invalid(
+                "reminders",
+                format!("{minutes} 分前（{MAX_MINUTES} 分まで）"),
+            ));
+        }
+
+        Ok(Config {
+            shiftweb: ShiftWebConfig {
+                id: raw.shiftweb.id,
+            },
+            icloud: ICloudConfig {
+                apple_id: raw.icloud.apple_id,
+                calendar_url: raw.icloud.calendar_url,
+            },
+            sources,
+            templates: ext.templates.validate(path)?,
+            reminders,
+            google: GoogleConfig {
+                calendar_id: ext.google.calendar_id.filter(|id| !id.is_empty()),
+            },
+            migrated_from,
+            other: raw.other,
+        })
+    }
+
+    /// 今の版の TOML
+    ///
+    /// `[shiftweb]` と `[icloud]` は値が空でも Go版と同じく書く。
+    pub fn to_toml(&self) -> String {
+        let templates = &self.templates;
+        let text = |t: &Option<Template>| t.as_ref().map(|t| t.as_str().to_string());
+        let raw = RawConfig {
+            shiftweb: RawShiftWeb {
+                id: self.shiftweb.id.clone(),
+            },
+            icloud: RawICloud {
+                apple_id: self.icloud.apple_id.clone(),
+                calendar_url: self.icloud.calendar_url.clone(),
+            },
+            extension: RawExtension {
+                version: CONFIG_VERSION,
+                sources: self.sources.iter().map(SourceSpec::to_string).collect(),
+                templates: RawTemplates {
+                    summary: text(&templates.summary),
+                    location: text(&templates.location),
+                    description: text(&templates.description),
+                    hourly_wage: templates.hourly_wage,
+                    hourly_wages: templates.hourly_wages.iter().cloned().collect(),
+                },
+                reminders: RawReminders {
+                    default: self.reminders.default.clone(),
+                    by_location: self.reminders.by_location.iter().cloned().collect(),
+                },
+                google: RawGoogle {
+                    calendar_id: self.google.calendar_id.clone(),
+                },
+            },
+            other: self.other.clone(),
+        };
+        toml::to_string(&raw).expect("設定は TOML に書ける値だけでできている")
+    }
+
+    /// `path` に保存する（ディレクトリがなければ作る）
+    /// Go版: saveConfig
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        atomic_file::write(path, self.to_toml()).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            action: "保存",
+            source,
+        })
+    }
+}
+
+/// `table` を `migrations` で今の版（`migrations.len() + 1`）に直す（何か書き換えたら元の版）
+fn migrate(
+    table: &mut Table,
+    path: &Path,
+    migrations: &[fn(&mut Table) -> bool],
+) -> Result<Option<u32>, ConfigError> {
+    let current = migrations.len() as u32 + 1;
+    let version = match table
+        .get(EXTENSION_TABLE)
+        .and_then(|ext| ext.get("version"))
+    {
+        None => 0,
+        Some(Value::Integer(v)) if (0..=i64::from(current)).contains(v) => *v as u32,
+        Some(other) => {
+            return Err(ConfigError::UnsupportedVersion {
+                path: path.to_path_buf(),
+                version: other.to_string(),
+            });
+        }
+    };
+    let mut changed = false;
+    for step in &migrations[version.saturating_sub(1) as usize..] {
+        changed |= step(table);
+    }
+    if let Some(Value::Table(ext)) = table.get_mut(EXTENSION_TABLE) {
+        ext.insert("version".to_string(), Value::Integer(current.into()));
+    }
+    Ok(Some(version).filter(|_| changed))
+}
+
+impl RawTemplates {
+    fn validate(self, path: &Path) -> Result<EventTemplates, ConfigError> {
+        let has_wage = self.hourly_wage.is_some() || !self.hourly_wages.is_empty();
+        let parse = |name: &str, text: Option<String>| {
+            let field = format!("{EXTENSION_TABLE}.templates.{name}");
+            let Some(text) = text else {
+                return Ok(None);
+            };
+            let template = Template::parse(&text).map_err(|source| ConfigError::Template {
+                path: path.to_path_buf(),
+                field: field.clone(),
+                source,
+            })?;
+            if template.uses(Variable::Wage) && !has_wage {
+                return Err(ConfigError::MissingWage {
+                    path: path.to_path_buf(),
+                    field,
+                });
+            }
+            Ok(Some(template))
+        };
+        Ok(EventTemplates {
+            summary: parse("summary", self.summary)?,
+            location: parse("location", self.location)?,
+            description: parse("description", self.description)?,
+            hourly_wage: self.hourly_wage,
+            hourly_wages: self.hourly_wages.into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Go版の saveConfig が書くファイル（BurntSushi/toml は表の中を字下げする）
+    const GO_CONFIG: &str = "[shiftweb]\n  id = \"12345\"\n\n\
+[icloud]\n  apple_id = \"user@example.com\"\n  calendar_url = \"https://caldav.icloud.com/1/calendars/work/\"\n";
+
+    fn path() -> &'static Path {
+        Path::new("config.toml")
+    }
+
+    #[test]
+    fn reads_go_config() {
+        let config = Config::parse(path(), GO_CONFIG).unwrap();
+        assert_eq!(config.shiftweb.id, "12345");
+        assert_eq!(config.icloud.apple_id, "user@example.com");
+        assert_eq!(
+            config.icloud.calendar_url,
+            "https://caldav.icloud.com/1/calendars/work/"
+        );
+        assert_eq!(config.migrated_from, None);
+        assert!(config.templates.is_empty() && config.reminders.is_empty());
+
+        assert_eq!(Config::parse(path(), "").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn round_trips_extension_and_unknown_tables() {
+        let text = format!(
+            "{GO_CONFIG}\n[future]\nkey = \"kept\"\n\n\
+[shift_sync_rc]\nversion = 1\nsources = [\"shiftweb\", \"csv:second.csv;location=マック 渋谷\"]\n\n\
+[shift_sync_rc.templates]\nsummary = \"🍔 {{shop}} ({{hours}}h)\"\nhourly_wage = 1200\n\n\
+[shift_sync_rc.reminders]\ndefault = [15, 60]\nby_location = {{ \"マック 渋谷\" = [30] }}\n\n\
+[shift_sync_rc.google]\ncalendar_id = \"abc@group.calendar.google.com\"\n"
+        );
+        let config = Config::parse(path(), &text).unwrap();
+        assert_eq!(config.sources.len(), 2);
+        assert_eq!(config.templates.hourly_wage, Some(1200));
+        assert_eq!(
+            config.reminders,
+            Reminders::new(&[60, 15]).at("マック 渋谷", &[30])
+        );
+        assert_eq!(
+            config.google.calendar_id.as_deref(),
+            Some("abc@group.calendar.google.com")
+        );
+
+        let written = config.to_toml();
+        assert!(written.contains("[future]"), "{written}");
+        assert!(written.find("[shiftweb]") < written.find("[shift_sync_rc]"));
+        assert_eq!(Config::parse(path(), &written).unwrap(), config);
+
+        // Go版の読み方でも読める
+        let go: Table = toml::from_str(&written).unwrap();
+        assert_eq!(go["shiftweb"]["id"].as_str(), Some("12345"));
+        assert_eq!(
+            go["icloud"]["calendar_url"].as_str(),
+            Some("https://caldav.icloud.com/1/calendars/work/")
+        );
+    }
+
+    /// 架空の版1→2: `sources` を1つの文字列でも書けた
+    fn sources_to_array(table: &mut Table) -> bool {
+        let Some(Value::Table(ext)) = table.get_mut(EXTENSION_TABLE) else {
+            return false;
+        };
+        let Some(Value::String(source)) = ext.get("sources").cloned() else {
+            return false;
+        };
+        ext.insert("sources".to_string(), Value::Array(vec![source.into()]));
+        true
+    }
+
+    /// 架空の版2→3: 通った印を残す
+    fn mark_v3(table: &mut Table) -> bool {
+        let ext = table
+            .entry(EXTENSION_TABLE)
+            .or_insert_with(|| Value::Table(Table::new()));
+        ext.as_table_mut()
+            .unwrap()
+            .insert("v3".to_string(), Value::Boolean(true));
+        true
+    }
+
+    const FAKE_MIGRATIONS: [fn(&mut Table) -> bool; 2] = [sources_to_array, mark_v3];
+
+    fn migrated(text: &str) -> (Result<Option<u32>, ConfigError>, Table) {
+        let mut table: Table = toml::from_str(text).unwrap();
+        let result = migrate(&mut table, path(), &FAKE_MIGRATIONS);
+        (result, table)
+    }
+
+    #[test]
+    fn migrates_older_versions_step_by_step() {
+        // 版 0（Go版のファイル）は版 1 と同じく最初の手順から
+        let (result, table) = migrated(GO_CONFIG);
+        assert_eq!(result.unwrap(), Some(0));
+        assert_eq!(table[EXTENSION_TABLE]["version"].as_integer(), Some(3));
+        assert_eq!(table[EXTENSION_TABLE]["v3"].as_bool(), Some(true));
+
+        let (result, table) = migrated("[shift_sync_rc]\nversion = 1\nsources = \"shiftweb\"\n");
+        assert_eq!(result.unwrap(), Some(1));
+        let ext = &table[EXTENSION_TABLE];
+        assert_eq!(ext["version"].as_integer(), Some(3));
+        assert!(ext["sources"].is_array());
+        assert_eq!(ext["v3"].as_bool(), Some(true));
+
+        // 版 2 は2つ目の手順だけ
+        let (result, table) = migrated("[shift_sync_rc]\nversion = 2\nsources = \"shiftweb\"\n");
+        assert_eq!(result.unwrap(), Some(2));
+        let ext = &table[EXTENSION_TABLE];
+        assert_eq!(ext["version"].as_integer(), Some(3));
+        assert!(ext["sources"].is_str());
+        assert_eq!(ext["v3"].as_bool(), Some(true));
+
+        // 今の版は何もしない
+        let (result, table) = migrated("[shift_sync_rc]\nversion = 3\n");
+        assert_eq!(result.unwrap(), None);
+        assert!(table[EXTENSION_TABLE].get("v3").is_none());
+
+        let (result, _) = migrated("[shift_sync_rc]\nversion = 4\n");
+        assert!(matches!(
+            result,
+            Err(ConfigError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn parsed_config_records_migrated_version() {
+        let text = "[shift_sync_rc]\nversion = 1\nsources = \"csv:second.csv\"\n";
+        let config = Config::parse_with(path(), text, &FAKE_MIGRATIONS[..1]).unwrap();
+        assert_eq!(config.migrated_from, Some(1));
+        assert_eq!(config.sources.len(), 1);
+        assert!(config.to_toml().contains("sources = [\"csv:second.csv\"]"));
+
+        let text = "[shift_sync_rc]\nversion = 1\nsources = [\"shiftweb\"]\n";
+        let config = Config::parse_with(path(), text, &FAKE_MIGRATIONS[..1]).unwrap();
+        assert_eq!(config.migrated_from, None);
+    }
+
+    #[test]
+    fn rejects_invalid_settings_on_load() {
+        let err = Config::parse(path(), "[shift_sync_rc]\nversion = 2\n").unwrap_err();
+        assert!(
+            matches!(err, ConfigError::UnsupportedVersion { .. }),
+            "{err}"
+        );
+
+        let err = Config::parse(path(), "[shift_sync_rc.templates]\nsummary = \"{store}\"\n")
+            .unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::Template { field, .. } if field == "shift_sync_rc.templates.summary"),
+            "{err}"
+        );
+
+        let err = Config::parse(
+            path(),
+            "[shift_sync_rc.templates]\ndescription = \"{wage}円\"\n",
+        )
+        .unwrap_err();
+        assert!(matches!(err, ConfigError::MissingWage { .. }), "{err}");
+
+        let err = Config::parse(path(), "[shift_sync_rc]\nsources = [\"ftp:x\"]\n").unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { .. }), "{err}");
+
+        let err =
+            Config::parse(path(), "[shift_sync_rc.reminders]\ndefault = [50000]\n").unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { .. }), "{err}");
+
+        let err = Config::parse(path(), "[shiftweb]\nid = 12345\n").unwrap_err();
+        assert!(matches!(err, ConfigError::Parse { .. }), "{err}");
+    }
+}
@@ -0,0 +1,536 @@
+//! Google Calendar API (calendar/v3) クライアント
+//! Swift版: GoogleCalendarService
+//!
+//! API の入口は差し替えられる（テストではローカルのモックを使う）。
+
+pub mod oauth;
+pub mod sync;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use ureq::Agent;
+use ureq::http::Request;
+use url::Url;
+
+use crate::shift::{Shift, uid_date};
+
+/// Google Calendar API の入口
+pub const DEFAULT_API_BASE: &str = "https://www.googleapis.com/calendar/v3/";
+
+/// `extendedProperties.private` に入れるシフトの UID のキー
+pub const UID_PROPERTY: &str = "shiftSyncUid";
+/// `extendedProperties.private` に入れる、最後に書き込んだ内容のハッシュのキー
+///
+/// 今の内容のハッシュと違えば、書き込んだ後にスマホなどで編集されている。
+pub const HASH_PROPERTY: &str = "shiftSyncHash";
+
+/// Swift版がイベントの説明の先頭に書く UID の印
+/// Swift版: GoogleCalendarService.createEventBody
+const SWIFT_UID_PREFIX: &str = "shift-uid:";
+
+/// events.list の1ページの件数（API の上限）
+const MAX_RESULTS: &str = "2500";
+
+/// Google Calendar API とのやりとりで起きたエラー
+#[derive(Debug)]
+pub enum GoogleError {
+    /// URL として読めない
+    InvalidUrl(String),
+    /// 接続できない・応答が読めないなど
+    Http { url: String, source: ureq::Error },
+    /// 想定外のステータスが返ってきた
+    Status {
+        method: String,
+        url: String,
+        status: u16,
+        body: String,
+    },
+    /// 応答の JSON が読めない
+    Json { url: String, message: String },
+    /// If-Match の ETag が合わなかった（412、他で変更された）
+    PreconditionFailed { method: String, url: String },
+}
+
+impl fmt::Display for GoogleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoogleError::InvalidUrl(url) => write!(f, "URL が変やで: {url}"),
+            GoogleError::Http { url, source } => write!(f, "{url}: {source}"),
+            GoogleError::Status {
+                method,
+                url,
+                status,
+                body,
+            } => write!(f, "{method} {url} status={status} body={body}"),
+            GoogleError::Json { url, message } => write!(f, "{url} の応答が読めない: {message}"),
+            GoogleError::PreconditionFailed { method, url } => {
+                write!(f, "{method} {url}: 他で変更されています (412)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GoogleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GoogleError::Http { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl GoogleError {
+    /// 認証エラー（アクセストークンの期限切れ・権限不足）かどうか
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(
+            self,
+            GoogleError::Status {
+                status: 401 | 403,
+                ..
+            }
+        )
+    }
+
+    /// 412（ETag が合わない）かどうか
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, GoogleError::PreconditionFailed { .. })
+    }
+}
+
+/// カレンダー一覧の1件
+/// Swift版: GoogleCalendar
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoogleCalendar {
+    pub id: String,
+    #[serde(default)]
+    pub summary: String,
+    /// "owner" / "writer" / "reader" / "freeBusyReader"
+    #[serde(default)]
+    pub access_role: String,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+impl GoogleCalendar {
+    /// イベントを書き込めるかどうか
+    pub fn is_writable(&self) -> bool {
+        matches!(self.access_role.as_str(), "owner" | "writer")
+    }
+}
+
+/// イベントの開始・終了（終日なら `date`）
+/// Swift版: GoogleEventDateTime
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDateTime {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+}
+
+impl EventDateTime {
+    /// 時刻（終日や読めない値なら `None`）
+    pub fn instant(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(self.date_time.as_deref()?).ok()
+    }
+}
+
+/// `extendedProperties`（このツールは `private` だけを使う）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtendedProperties {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub private: BTreeMap<String, String>,
+}
+
+/// 通知の上書き1件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReminderOverride {
+    /// "popup" / "email"
+    pub method: String,
+    pub minutes: u32,
+}
+
+/// イベントの通知（`useDefault` ならカレンダーの既定の通知）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventReminders {
+    #[serde(default)]
+    pub use_default: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<ReminderOverride>,
+}
+
+impl EventReminders {
+    /// 開始 `minutes` 分前のポップアップ通知だけにする（空なら通知なし）
+    pub fn popup(minutes: &[u32]) -> Self {
+        EventReminders {
+            use_default: false,
+            overrides: minutes
+                .iter()
+                .map(|&minutes| ReminderOverride {
+                    method: "popup".to_string(),
+                    minutes,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// イベント（一覧の応答と、POST / PUT の本文を兼ねる）
+/// Swift版: GoogleEvent, GoogleEventRequest
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoogleEvent {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// "confirmed" / "tentative" / "cancelled"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub start: EventDateTime,
+    #[serde(default)]
+    pub end: EventDateTime,
+    /// 書き込むときに `None` ならカレンダーの既定の通知になる
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reminders: Option<EventReminders>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extended_properties: Option<ExtendedProperties>,
+}
+
+impl GoogleEvent {
+    /// シフトから書き込むイベントを作る
+    ///
+    /// 説明の先頭には Swift版と同じ `shift-uid:` を書き、アプリからも同じイベントだと分かるようにする。
+    /// Swift版: GoogleCalendarService.createEventBody
+    pub fn from_shift(shift: &Shift) -> Self {
+        let uid = shift.uid();
+        let mut description = format!("{SWIFT_UID_PREFIX}{uid}");
+        if !shift.memo.is_empty() {
+            description.push('\n');
+            description.push_str(&shift.memo);
+        }
+        let time = |at: DateTime<chrono_tz::Tz>| EventDateTime {
+            date_time: Some(at.to_rfc3339()),
+            date: None,
+            time_zone: Some(at.timezone().name().to_string()),
+        };
+        GoogleEvent {
+            summary: Some(shift.title.clone()),
+            location: Some(shift.location.clone()).filter(|l| !l.is_empty()),
+            description: Some(description),
+            start: time(shift.start()),
+            end: time(shift.end()),
+            extended_properties: Some(ExtendedProperties {
+                private: BTreeMap::from([(UID_PROPERTY.to_string(), uid)]),
+            }),
+            ..GoogleEvent::default()
+        }
+    }
+
+    pub fn private_property(&self, key: &str) -> Option<&str> {
+        self.extended_properties
+            .as_ref()?
+            .private
+            .get(key)
+            .map(String::as_str)
+    }
+
+    /// シフトのイベントならその UID
+    ///
+    /// `extendedProperties.private` の印を見て、なければ Swift版が説明に書いた `shift-uid:` を見る。
+    /// Swift版: GoogleCalendarService.extractShiftUID
+    pub fn shift_uid(&self) -> Option<&str> {
+        self.private_property(UID_PROPERTY)
+            .or_else(|| {
+                let rest = self.description.as_deref()?.split_once(SWIFT_UID_PREFIX)?.1;
+                Some(rest.lines().next().unwrap_or_default().trim())
+            })
+            .filter(|uid| uid.starts_with("shift-"))
+    }
+
+    /// イベントの日付（UID から、読めなければ開始時刻のその地域での日付）
+    pub fn date(&self) -> Option<NaiveDate> {
+        self.shift_uid()
+            .and_then(uid_date)
+            .or_else(|| Some(self.start.instant()?.date_naive()))
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.status.as_deref() == Some("cancelled")
+    }
+}
+
+/// 一覧系 API の1ページ
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Page<T> {
+    #[serde(default = "Vec::new")]
+    items: Vec<T>,
+    next_page_token: Option<String>,
+}
+
+struct Reply {
+    url: Url,
+    status: u16,
+    body: String,
+}
+
+/// アクセストークン（Bearer）で Google Calendar API と話すクライアント
+pub struct GoogleClient {
+    agent: Agent,
+    base: Url,
+    authorization: String,
+}
+
+impl GoogleClient {
+    pub fn new(access_token: &str) -> Self {
+        GoogleClient::with_base_url(DEFAULT_API_BASE, access_token)
+            .expect("DEFAULT_API_BASE is a valid URL")
+    }
+
+    /// API の入口を指定して作る（"https://www.googleapis.com/calendar/v3/" の代わり）
+    pub fn with_base_url(base_url: &str, access_token: &str) -> Result<Self, GoogleError> {
+        let base = Url::parse(base_url)
+            .ok()
+            .filter(|u| !u.cannot_be_a_base())
+            .ok_or_else(|| GoogleError::InvalidUrl(base_url.to_string()))?;
+        let agent = Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(30)))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Ok(GoogleClient {
+            agent,
+            base,
+            authorization: format!("Bearer {access_token}"),
+        })
+    }
+
+    /// 入口の下の `segments` のパス（各要素はエスケープされる）
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .expect("checked in with_base_url")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+
+    fn send(
+        &self,
+        method: &str,
+        url: &Url,
+        headers: &[(&str, &str)],
+        body: Option<String>,
+    ) -> Result<Reply, GoogleError> {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(url.as_str())
+            .header("Authorization", &self.authorization)
+            .header("Accept", "application/json");
+        if body.is_some() {
+            builder = builder.header("Content-Type", "application/json; charset=utf-8");
+        }
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let request = builder
+            .body(body.unwrap_or_default())
+            .map_err(|_| GoogleError::InvalidUrl(url.to_string()))?;
+        let http = |source| GoogleError::Http {
+            url: url.to_string(),
+            source,
+        };
+        let mut resp = self.agent.run(request).map_err(http)?;
+        let status = resp.status().as_u16();
+        let body = resp.body_mut().read_to_string().map_err(http)?;
+        Ok(Reply {
+            url: url.clone(),
+            status,
+            body,
+        })
+    }
+
+    /// リクエストを送り、2xx なら JSON を読む
+    fn request_json<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        url: &Url,
+        headers: &[(&str, &str)],
+        body: Option<String>,
+    ) -> Result<T, GoogleError> {
+        let reply = expect_success(method, self.send(method, url, headers, body)?)?;
+        serde_json::from_str(&reply.body).map_err(|err| GoogleError::Json {
+            url: reply.url.to_string(),
+            message: err.to_string(),
+        })
+    }
+
+    /// `nextPageToken` をたどって全ページの `items` を集める
+    fn list_all<T: DeserializeOwned>(&self, url: Url) -> Result<Vec<T>, GoogleError> {
+        let mut items = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut page_url = url.clone();
+            if let Some(token) = &page_token {
+                page_url.query_pairs_mut().append_pair("pageToken", token);
+            }
+            let page: Page<T> = self.request_json("GET", &page_url, &[], None)?;
+            items.extend(page.items);
+            match page.next_page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => return Ok(items),
+            }
+        }
+    }
+
+    /// ログインユーザーのカレンダー一覧
+    /// Swift版: GoogleCalendarService.getCalendars
+    pub fn list_calendars(&self) -> Result<Vec<GoogleCalendar>, GoogleError> {
+        self.list_all(self.url(&["users", "me", "calendarList"]))
+    }
+
+    /// `time_min` 〜 `time_max` にかかるイベント（繰り返しは1回ずつに展開）
+    /// Swift版: GoogleCalendarService.getExistingShiftEvents
+    pub fn list_events(
+        &self,
+        calendar_id: &str,
+        time_min: DateTime<FixedOffset>,
+        time_max: DateTime<FixedOffset>,
+    ) -> Result<Vec<GoogleEvent>, GoogleError> {
+        let mut url = self.url(&["calendars", calendar_id, "events"]);
+        url.query_pairs_mut()
+            .append_pair("timeMin", &time_min.to_rfc3339())
+            .append_pair("timeMax", &time_max.to_rfc3339())
+            .append_pair("maxResults", MAX_RESULTS)
+            .append_pair("singleEvents", "true");
+        self.list_all(url)
+    }
+
+    /// イベントを作成する（POST）
+    /// Swift版: GoogleCalendarService.createEvent
+    pub fn insert_event(
+        &self,
+        calendar_id: &str,
+        event: &GoogleEvent,
+    ) -> Result<GoogleEvent, GoogleError> {
+        let url = self.url(&["calendars", calendar_id, "events"]);
+        self.request_json("POST", &url, &[], Some(to_json(event)))
+    }
+
+    /// イベントを置き換える（PUT）。`etag` があれば If-Match を付ける
+    /// Swift版: GoogleCalendarService.updateEvent
+    pub fn update_event(
+        &self,
+        calendar_id: &str,
+        event_id: &str,
+        event: &GoogleEvent,
+        etag: Option<&str>,
+    ) -> Result<GoogleEvent, GoogleError> {
+        let url = self.url(&["calendars", calendar_id, "events", event_id]);
+        let headers: Vec<_> = etag.map(|e| ("If-Match", e)).into_iter().collect();
+        self.request_json("PUT", &url, &headers, Some(to_json(event)))
+    }
+
+    /// イベントを削除する（すでに無ければ成功扱い）。`etag` があれば If-Match を付ける
+    /// Swift版: GoogleCalendarService.deleteEvent
+    pub fn delete_event(
+        &self,
+        calendar_id: &str,
+        event_id: &str,
+        etag: Option<&str>,
+    ) -> Result<(), GoogleError> {
+        let url = self.url(&["calendars", calendar_id, "events", event_id]);
+        let headers: Vec<_> = etag.map(|e| ("If-Match", e)).into_iter().collect();
+        let reply = self.send("DELETE", &url, &headers, None)?;
+        if matches!(reply.status, 404 | 410) {
+            return Ok(());
+        }
+        expect_success("DELETE", reply).map(|_| ())
+    }
+}
+
+fn to_json(event: &GoogleEvent) -> String {
+    serde_json::to_string(event).expect("GoogleEvent always serializes")
+}
+
+fn expect_success(method: &str, reply: Reply) -> Result<Reply, GoogleError> {
+    if (200..300).contains(&reply.status) {
+        return Ok(reply);
+    }
+    if reply.status == 412 {
+        return Err(GoogleError::PreconditionFailed {
+            method: method.to_string(),
+            url: reply.url.to_string(),
+        });
+    }
+    Err(GoogleError::Status {
+        method: method.to_string(),
+        url: reply.url.to_string(),
+        status: reply.status,
+        body: reply.body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shift::{DEFAULT_TITLE, DEFAULT_TZ};
+
+    #[test]
+    fn event_body_is_marked_and_readable_by_swift() {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let shift = Shift::on_date(DEFAULT_TITLE, day, "10:00", "19:00", "渋谷店", DEFAULT_TZ)
+            .unwrap()
+            .with_memo("レジ");
+        let event = GoogleEvent::from_shift(&shift);
+        let json: serde_json::Value = serde_json::from_str(&to_json(&event)).unwrap();
+
+        assert_eq!(json["start"]["dateTime"], "2026-01-15T10:00:00+09:00");
+        assert_eq!(json["start"]["timeZone"], "Asia/Tokyo");
+        assert_eq!(
+            json["extendedProperties"]["private"][UID_PROPERTY],
+            shift.uid()
+        );
+        assert_eq!(
+            json["description"],
+            format!("shift-uid:{}\nレジ", shift.uid())
+        );
+        assert!(json.get("id").is_none());
+        assert_eq!(event.shift_uid(), Some(shift.uid().as_str()));
+        assert_eq!(event.date(), Some(day));
+    }
+
+    #[test]
+    fn reads_swift_marker_from_description() {
+        let event: GoogleEvent = serde_json::from_str(
+            r#"{"id":"abc","description":"shift-uid:shift-20260115-1000-1900-0000\nメモ",
+                "start":{"dateTime":"2026-01-15T01:00:00Z"}}"#,
+        )
+        .unwrap();
+        assert_eq!(event.shift_uid(), Some("shift-20260115-1000-1900-0000"));
+
+        let other: GoogleEvent =
+            serde_json::from_str(r#"{"id":"x","summary":"歯医者","start":{"date":"2026-01-15"}}"#)
+                .unwrap();
+        assert_eq!(other.shift_uid(), None);
+        assert_eq!(other.date(), None);
+    }
+}
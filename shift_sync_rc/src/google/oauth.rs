@@ -0,0 +1,642 @@
+//! Google の OAuth 2.0 ログイン（ブラウザのない端末向け）
+//!
+//! Swift版は GoogleSignIn の画面でログインするが、CLI では使えないので次の2通りを用意する。
+//! - デバイス認可グラント（RFC 8628）: 表示したコードを別の端末のブラウザで入力する
+//! - ループバックリダイレクト + PKCE（RFC 8252, RFC 7636）: 同じマシンのブラウザでログインし、
+//!   `127.0.0.1` の一時サーバーで認可コードを受け取る
+//!
+//! 取得したトークンは `TokenStore` に保存し、期限が切れたらリフレッシュトークンで取り直す。
+//! 認可・トークンのエンドポイントは `OAuthConfig` で差し替えられる（テストではローカルのモックを使う）。
+
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ureq::Agent;
+use url::{Url, form_urlencoded};
+
+use crate::atomic_file;
+
+/// Google の認可エンドポイント
+pub const GOOGLE_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+/// Google のトークンエンドポイント
+pub const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+/// Google のデバイス認可エンドポイント
+pub const GOOGLE_DEVICE_URL: &str = "https://oauth2.googleapis.com/device/code";
+/// カレンダーの読み書きに必要なスコープ
+/// Swift版: GoogleCalendarService.signIn の additionalScopes
+pub const CALENDAR_SCOPE: &str = "https://www.googleapis.com/auth/calendar";
+
+const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+/// 期限ぎりぎりのトークンを使わないための余裕（秒）
+const EXPIRY_MARGIN_SECS: i64 = 60;
+/// slow_down を返されたときに延ばすポーリング間隔（RFC 8628 3.5）
+const SLOW_DOWN_SECS: u64 = 5;
+/// ループバックでブラウザからのリダイレクトを待つ時間の既定値
+pub const LOOPBACK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// 接続したまま何も送ってこないブラウザ（先読みの接続など）を待つ時間
+const LOOPBACK_READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// 接続が来ていないか確かめる間隔
+const LOOPBACK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// ログイン後にブラウザに表示するページ
+const LOGIN_DONE_PAGE: &str = "<!DOCTYPE html>\n<html lang=\"ja\"><head><meta charset=\"UTF-8\">\
+<title>shift_sync_rc</title></head><body><p>ログインできました。このタブは閉じて大丈夫です。</p></body></html>\n";
+const LOGIN_FAILED_PAGE: &str = "<!DOCTYPE html>\n<html lang=\"ja\"><head><meta charset=\"UTF-8\">\
+<title>shift_sync_rc</title></head><body><p>ログインできませんでした。ターミナルを確認してください。</p></body></html>\n";
+
+/// OAuth のやりとりで起きたエラー
+#[derive(Debug)]
+pub enum OAuthError {
+    /// URL として読めない
+    InvalidUrl(String),
+    /// 接続できない・応答が読めないなど
+    Http { url: String, source: ureq::Error },
+    /// サーバーが OAuth のエラー（`invalid_grant` など）を返した
+    Server {
+        url: String,
+        error: String,
+        description: Option<String>,
+    },
+    /// 想定外のステータスが返ってきた
+    Status {
+        url: String,
+        status: u16,
+        body: String,
+    },
+    /// 応答の JSON が読めない
+    Json { url: String, message: String },
+    /// ユーザーがログインを拒否した
+    Denied,
+    /// ユーザーが入力する前にデバイスコードの期限が切れた
+    Expired,
+    /// ループバックで受け取った state がこちらの送ったものと違う
+    StateMismatch,
+    /// ループバックで待っている間にブラウザからのリダイレクトが来なかった
+    TimedOut(Duration),
+    /// ループバックの待ち受けや保存ファイルの読み書きに失敗した
+    Io(io::Error),
+    /// 保存されたトークンにリフレッシュトークンがなく、取り直せない
+    NoRefreshToken,
+}
+
+impl fmt::Display for OAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OAuthError::InvalidUrl(url) => write!(f, "URL が変やで: {url}"),
+            OAuthError::Http { url, source } => write!(f, "{url}: {source}"),
+            OAuthError::Server {
+                url,
+                error,
+                description,
+            } => match description {
+                Some(description) => write!(f, "{url}: {error} ({description})"),
+                None => write!(f, "{url}: {error}"),
+            },
+            OAuthError::Status { url, status, body } => {
+                write!(f, "{url} status={status} body={body}")
+            }
+            OAuthError::Json { url, message } => write!(f, "{url} の応答が読めない: {message}"),
+            OAuthError::Denied => f.write_str("ログインが拒否されました"),
+            OAuthError::Expired => {
+                f.write_str("コードの期限が切れました。もう一度やり直してください")
+            }
+            OAuthError::StateMismatch => f.write_str("ログインの応答が合いません（state が違う）"),
+            OAuthError::TimedOut(waited) => write!(
+                f,
+                "ブラウザからの応答がありませんでした（{}秒待ちました）。もう一度やり直してください",
+                waited.as_secs()
+            ),
+            OAuthError::Io(err) => write!(f, "{err}"),
+            OAuthError::NoRefreshToken => {
+                f.write_str("リフレッシュトークンがありません。もう一度ログインしてください")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OAuthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OAuthError::Http { source, .. } => Some(source),
+            OAuthError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for OAuthError {
+    fn from(err: io::Error) -> Self {
+        OAuthError::Io(err)
+    }
+}
+
+/// OAuth クライアントの設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthConfig {
+    pub client_id: String,
+    /// デスクトップアプリ用クライアントのシークレット（Google では秘密扱いされない）
+    pub client_secret: Option<String>,
+    pub auth_url: String,
+    pub token_url: String,
+    pub device_url: String,
+    pub scope: String,
+}
+
+impl OAuthConfig {
+    /// Google のエンドポイントとカレンダーのスコープを使う設定
+    pub fn google(client_id: &str, client_secret: Option<&str>) -> Self {
+        OAuthConfig {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.map(str::to_string),
+            auth_url: GOOGLE_AUTH_URL.to_string(),
+            token_url: GOOGLE_TOKEN_URL.to_string(),
+            device_url: GOOGLE_DEVICE_URL.to_string(),
+            scope: CALENDAR_SCOPE.to_string(),
+        }
+    }
+}
+
+/// 取得したトークン（`TokenStore` にこの形で保存する）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// アクセストークンの期限（UNIX 秒）。分からなければ `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+impl Token {
+    /// 期限が切れている（もうすぐ切れる）かどうか
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at
+            .is_some_and(|at| now + EXPIRY_MARGIN_SECS >= at)
+    }
+}
+
+/// トークンエンドポイントの応答
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+impl TokenResponse {
+    fn into_token(self, now: i64) -> Token {
+        Token {
+            access_token: self.access_token,
+            refresh_token: self.refresh_token,
+            expires_at: self.expires_in.map(|secs| now + secs),
+            scope: self.scope,
+        }
+    }
+}
+
+/// OAuth のエラー応答（RFC 6749 5.2）
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// デバイス認可の応答（RFC 8628 3.2）
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    /// ユーザーがブラウザで入力するコード
+    pub user_code: String,
+    /// コードを入力するページ（Google は `verification_url` で返す）
+    #[serde(alias = "verification_url")]
+    pub verification_uri: String,
+    /// コードの有効期間（秒）
+    pub expires_in: u64,
+    /// ポーリング間隔（秒）
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+/// OAuth のエンドポイントと話すクライアント
+pub struct OAuthClient {
+    agent: Agent,
+    config: OAuthConfig,
+}
+
+impl OAuthClient {
+    pub fn new(config: OAuthConfig) -> Self {
+        let agent = Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(30)))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        OAuthClient { agent, config }
+    }
+
+    pub fn config(&self) -> &OAuthConfig {
+        &self.config
+    }
+
+    /// フォームを POST して 2xx なら JSON を読む。OAuth のエラー応答は `OAuthError::Server` にする
+    fn post_form<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T, OAuthError> {
+        let mut form = form_urlencoded::Serializer::new(String::new());
+        form.append_pair("client_id", &self.config.client_id);
+        if let Some(secret) = &self.config.client_secret {
+            form.append_pair("client_secret", secret);
+        }
+        form.extend_pairs(params);
+
+        let http = |source| OAuthError::Http {
+            url: url.to_string(),
+            source,
+        };
+        let mut resp = self
+            .agent
+            .post(url)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Accept", "application/json")
+            .send(form.finish())
+            .map_err(http)?;
+        let status = resp.status().as_u16();
+        let body = resp.body_mut().read_to_string().map_err(http)?;
+        if !(200..300).contains(&status) {
+            return Err(match serde_json::from_str::<ErrorResponse>(&body) {
+                Ok(err) => OAuthError::Server {
+                    url: url.to_string(),
+                    error: err.error,
+                    description: err.error_description,
+                },
+                Err(_) => OAuthError::Status {
+                    url: url.to_string(),
+                    status,
+                    body,
+                },
+            });
+        }
+        serde_json::from_str(&body).map_err(|err| OAuthError::Json {
+            url: url.to_string(),
+            message: err.to_string(),
+        })
+    }
+
+    fn request_token(&self, params: &[(&str, &str)]) -> Result<Token, OAuthError> {
+        let now = Utc::now().timestamp();
+        let response: TokenResponse = self.post_form(&self.config.token_url, params)?;
+        Ok(response.into_token(now))
+    }
+
+    /// デバイス認可を始め、ユーザーに見せるコードと URL を受け取る
+    pub fn start_device(&self) -> Result<DeviceAuthorization, OAuthError> {
+        self.post_form(&self.config.device_url, &[("scope", &self.config.scope)])
+    }
+
+    /// ユーザーがコードを入力し終えるまでトークンエンドポイントをポーリングする
+    ///
+    /// 待つ処理は `sleep` に任せる（テストでは待たない）。
+    pub fn poll_device(
+        &self,
+        device: &DeviceAuthorization,
+        mut sleep: impl FnMut(Duration),
+    ) -> Result<Token, OAuthError> {
+        let mut interval = device.interval;
+        let mut waited = 0;
+        loop {
+            sleep(Duration::from_secs(interval));
+            waited += interval;
+            let result = self.request_token(&[
+                ("grant_type", DEVICE_GRANT_TYPE),
+                ("device_code", &device.device_code),
+            ]);
+            match result {
+                Err(OAuthError::Server { error, .. }) if error == "authorization_pending" => {}
+                Err(OAuthError::Server { error, .. }) if error == "slow_down" => {
+                    interval += SLOW_DOWN_SECS;
+                }
+                Err(OAuthError::Server { error, .. }) if error == "access_denied" => {
+                    return Err(OAuthError::Denied);
+                }
+                Err(OAuthError::Server { error, .. }) if error == "expired_token" => {
+                    return Err(OAuthError::Expired);
+                }
+                other => return other,
+            }
+            if waited >= device.expires_in {
+                return Err(OAuthError::Expired);
+            }
+        }
+    }
+
+    /// ループバックでの認可コードの受け取りを準備する（`127.0.0.1` の空きポートで待ち受ける）
+    pub fn start_loopback(&self) -> Result<LoopbackLogin, OAuthError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let redirect_uri = format!("http://127.0.0.1:{port}/");
+        let verifier = random_token(32)?;
+        let state = random_token(16)?;
+
+        let mut authorize_url = Url::parse(&self.config.auth_url)
+            .map_err(|_| OAuthError::InvalidUrl(self.config.auth_url.clone()))?;
+        authorize_url
+            .query_pairs_mut()
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &redirect_uri)
+            .append_pair("response_type", "code")
+            .append_pair("scope", &self.config.scope)
+            .append_pair("code_challenge", &pkce_challenge(&verifier))
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", &state)
+            // リフレッシュトークンを毎回もらう
+            .append_pair("access_type", "offline")
+            .append_pair("prompt", "consent");
+
+        Ok(LoopbackLogin {
+            listener,
+            redirect_uri,
+            authorize_url: authorize_url.into(),
+            verifier,
+            state,
+            timeout: LOOPBACK_TIMEOUT,
+        })
+    }
+
+    /// リフレッシュトークンでアクセストークンを取り直す
+    ///
+    /// 応答にリフレッシュトークンがなければ元のものを引き継ぐ。
+    pub fn refresh(&self, token: &Token) -> Result<Token, OAuthError> {
+        let refresh_token = token
+            .refresh_token
+            .as_deref()
+            .ok_or(OAuthError::NoRefreshToken)?;
+        let mut refreshed = self.request_token(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ])?;
+        if refreshed.refresh_token.is_none() {
+            refreshed.refresh_token = Some(refresh_token.to_string());
+        }
+        Ok(refreshed)
+    }
+
+    /// 保存されたトークンを返す。期限切れならリフレッシュして保存し直す
+    ///
+    /// まだログインしていなければ `None`。
+    pub fn stored_token(&self, store: &TokenStore) -> Result<Option<Token>, OAuthError> {
+        let Some(token) = store.load()? else {
+            return Ok(None);
+        };
+        if !token.is_expired(Utc::now().timestamp()) {
+            return Ok(Some(token));
+        }
+        let refreshed = self.refresh(&token)?;
+        store.save(&refreshed)?;
+        Ok(Some(refreshed))
+    }
+}
+
+/// ループバックでのログインの途中経過
+pub struct LoopbackLogin {
+    listener: TcpListener,
+    redirect_uri: String,
+    authorize_url: String,
+    verifier: String,
+    state: String,
+    timeout: Duration,
+}
+
+impl LoopbackLogin {
+    /// リダイレクトを待つ時間を変える（既定は LOOPBACK_TIMEOUT）
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// ユーザーがブラウザで開く URL
+    pub fn authorize_url(&self) -> &str {
+        &self.authorize_url
+    }
+
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// ブラウザからのリダイレクトを待ち、認可コードをトークンに換える
+    pub fn finish(self, client: &OAuthClient) -> Result<Token, OAuthError> {
+        let code = self.receive_code()?;
+        client.request_token(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("code_verifier", &self.verifier),
+            ("redirect_uri", &self.redirect_uri),
+        ])
+    }
+
+    /// リダイレクトを1回受け付けて認可コードを取り出す（favicon などの別のリクエストは読み飛ばす）
+    ///
+    /// `timeout` のうちに来なければ `OAuthError::TimedOut`（タブを閉じられたときなど）。
+    fn receive_code(&self) -> Result<String, OAuthError> {
+        let deadline = Instant::now() + self.timeout;
+        self.listener.set_nonblocking(true)?;
+        loop {
+            let mut stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(OAuthError::TimedOut(self.timeout));
+                    }
+                    std::thread::sleep(LOOPBACK_POLL_INTERVAL.min(deadline - now));
+                    continue;
+                }
+                Err(err) => return Err(err.into()),
+            };
+            stream.set_nonblocking(false)?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            stream.set_read_timeout(Some(
+                LOOPBACK_READ_TIMEOUT
+                    .min(remaining)
+                    .max(Duration::from_millis(1)),
+            ))?;
+            let mut request_line = String::new();
+            match BufReader::new(&stream).read_line(&mut request_line) {
+                Ok(_) => {}
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue;
+                }
+                Err(err) => return Err(err.into()),
+            }
+            let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+            let Some(query) = target
+                .strip_prefix("/?")
+                .filter(|q| q.contains("code=") || q.contains("error="))
+            else {
+                write_response(&mut stream, "404 Not Found", "")?;
+                continue;
+            };
+            let param = |name: &str| {
+                form_urlencoded::parse(query.as_bytes())
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| v.into_owned())
+            };
+            let result = if param("state").as_deref() != Some(self.state.as_str()) {
+                Err(OAuthError::StateMismatch)
+            } else if let Some(code) = param("code") {
+                Ok(code)
+            } else if param("error").as_deref() == Some("access_denied") {
+                Err(OAuthError::Denied)
+            } else {
+                Err(OAuthError::Server {
+                    url: self.redirect_uri.clone(),
+                    error: param("error").unwrap_or_default(),
+                    description: param("error_description"),
+                })
+            };
+            let page = if result.is_ok() {
+                LOGIN_DONE_PAGE
+            } else {
+                LOGIN_FAILED_PAGE
+            };
+            write_response(&mut stream, "200 OK", page)?;
+            return result;
+        }
+    }
+}
+
+fn write_response(stream: &mut impl Write, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\n\
+Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+/// PKCE の code_challenge（S256）
+pub fn pkce_challenge(verifier: &str) -> String {
+    BASE64_URL.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// `bytes` バイトの乱数を URL で使える文字列にしたもの
+fn random_token(bytes: usize) -> Result<String, OAuthError> {
+    let mut buf = vec![0; bytes];
+    getrandom::fill(&mut buf).map_err(|err| OAuthError::Io(io::Error::other(err)))?;
+    Ok(BASE64_URL.encode(buf))
+}
+
+/// トークンの保存先（JSON ファイル、本人だけが読める権限で書く）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenStore {
+    path: PathBuf,
+}
+
+impl TokenStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        TokenStore { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 保存されたトークン（ファイルがなければ `None`）
+    pub fn load(&self) -> Result<Option<Token>, OAuthError> {
+        match fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map(Some)
+                .map_err(|err| OAuthError::Json {
+                    url: self.path.display().to_string(),
+                    message: err.to_string(),
+                }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// 書き出す（一時ファイルに書いてから置き換える）
+    pub fn save(&self, token: &Token) -> Result<(), OAuthError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = atomic_file::temp_path(&self.path);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&tmp)?;
+        let json = serde_json::to_string_pretty(token).expect("Token always serializes");
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// ログアウト（保存したトークンを消す）
+    pub fn clear(&self) -> Result<(), OAuthError> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pkce_challenge_matches_rfc7636_example() {
+        // RFC 7636 Appendix B
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn token_expires_with_margin() {
+        let token = Token {
+            access_token: "a".to_string(),
+            refresh_token: None,
+            expires_at: Some(1_000),
+            scope: None,
+        };
+        assert!(!token.is_expired(900));
+        assert!(token.is_expired(950));
+        assert!(
+            !Token {
+                expires_at: None,
+                ..token
+            }
+            .is_expired(i64::MAX / 2)
+        );
+    }
+}
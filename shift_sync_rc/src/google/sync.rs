@@ -0,0 +1,239 @@
+//! 同期先としての Google カレンダー
+//! Swift版: GoogleCalendarService.syncShifts
+
+use chrono::{DateTime, Days, FixedOffset, NaiveDate, NaiveTime, Utc};
+use sha1::{Digest, Sha1};
+
+use crate::backend::{BackendError, CalendarBackend, Capabilities, RemoteEvent};
+use crate::google::{
+    EventDateTime, EventReminders, GoogleClient, GoogleError, GoogleEvent, HASH_PROPERTY,
+};
+use crate::reminder::Reminders;
+use crate::shift::Shift;
+use crate::sync::SyncWindow;
+
+/// 同期先としての Google カレンダー
+///
+/// 書き込むイベントには `extendedProperties.private` に UID と内容のハッシュを入れておき、
+/// ハッシュが今の内容と合わないもの（書き込んだ後に他で編集されたもの）を見分ける。
+/// 記録を手元に持たないので、別の端末から同期しても同じように判断できる。
+/// 書き込みは `If-Match` 付きで、412 は競合として扱う。
+pub struct GoogleBackend<'a> {
+    client: &'a GoogleClient,
+    calendar_id: String,
+    reminders: Reminders,
+}
+
+impl<'a> GoogleBackend<'a> {
+    pub fn new(client: &'a GoogleClient, calendar_id: &str) -> Self {
+        GoogleBackend {
+            client,
+            calendar_id: calendar_id.to_string(),
+            reminders: Reminders::default(),
+        }
+    }
+
+    /// イベントに付ける通知（`reminders.overrides` のポップアップ通知になる）
+    ///
+    /// 指定のないシフトは `reminders` を送らず、カレンダーの既定の通知に任せる。
+    pub fn with_reminders(mut self, reminders: Reminders) -> Self {
+        self.reminders = reminders;
+        self
+    }
+
+    /// 書き込むイベント（ハッシュの印はまだ付けない）
+    fn event(&self, shift: &Shift) -> GoogleEvent {
+        let mut event = GoogleEvent::from_shift(shift);
+        event.reminders = self.reminders.overrides(shift).map(EventReminders::popup);
+        event
+    }
+
+    /// 書き込むイベント（内容のハッシュの印付き）
+    fn event_for(&self, shift: &Shift) -> GoogleEvent {
+        let mut event = self.event(shift);
+        let hash = content_hash(&event);
+        if let Some(props) = event.extended_properties.as_mut() {
+            props.private.insert(HASH_PROPERTY.to_string(), hash);
+        }
+        event
+    }
+}
+
+impl BackendError for GoogleError {
+    fn is_conflict(&self) -> bool {
+        GoogleError::is_conflict(self)
+    }
+}
+
+impl CalendarBackend for GoogleBackend<'_> {
+    type Error = GoogleError;
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            conditional_writes: true,
+            detects_edits: true,
+            incremental_listing: false,
+        }
+    }
+
+    fn content_hash(&self, shift: &Shift) -> String {
+        content_hash(&self.event(shift))
+    }
+
+    fn list_events(&mut self, range: SyncWindow) -> Result<Vec<RemoteEvent>, GoogleError> {
+        // タイムゾーンの違いで取りこぼさないよう、前後1日ずつ広げて問い合わせる
+        let time_min = utc_midnight(range.start - Days::new(1));
+        let time_max = utc_midnight(range.end + Days::new(1));
+        let events = self
+            .client
+            .list_events(&self.calendar_id, time_min, time_max)?;
+        Ok(events
+            .into_iter()
+            .filter(|event| !event.is_cancelled())
+            .filter_map(|event| {
+                Some(RemoteEvent {
+                    uid: event.shift_uid()?.to_string(),
+                    date: event.date(),
+                    hash: content_hash(&event),
+                    written_hash: event.private_property(HASH_PROPERTY).map(str::to_string),
+                    etag: event.etag,
+                    id: event.id,
+                })
+            })
+            .collect())
+    }
+
+    fn create_event(&mut self, shift: &Shift) -> Result<(), GoogleError> {
+        self.client
+            .insert_event(&self.calendar_id, &self.event_for(shift))
+            .map(|_| ())
+    }
+
+    fn update_event(&mut self, current: &RemoteEvent, shift: &Shift) -> Result<(), GoogleError> {
+        self.client
+            .update_event(
+                &self.calendar_id,
+                &current.id,
+                &self.event_for(shift),
+                current.etag.as_deref(),
+            )
+            .map(|_| ())
+    }
+
+    fn delete_event(&mut self, current: &RemoteEvent) -> Result<(), GoogleError> {
+        self.client
+            .delete_event(&self.calendar_id, &current.id, current.etag.as_deref())
+    }
+}
+
+fn utc_midnight(date: NaiveDate) -> DateTime<FixedOffset> {
+    date.and_time(NaiveTime::MIN).and_utc().fixed_offset()
+}
+
+/// 更新が必要かどうかを決める内容のハッシュ
+///
+/// 時刻は UTC に揃えて比べる（Google はカレンダーのタイムゾーンで返すことがある）。
+/// Swift版: GoogleCalendarService.needsUpdate
+fn content_hash(event: &GoogleEvent) -> String {
+    let instant = |t: &EventDateTime| {
+        t.instant()
+            .map(|at| at.with_timezone(&Utc).to_rfc3339())
+            .or_else(|| t.date.clone())
+            .unwrap_or_default()
+    };
+    let mut fields = vec![
+        event.summary.clone().unwrap_or_default(),
+        event.location.clone().unwrap_or_default(),
+        event.description.clone().unwrap_or_default(),
+        instant(&event.start),
+        instant(&event.end),
+    ];
+    // 既定の通知のままなら何も足さず、通知を付ける前と同じ値にする
+    if let Some(reminders) = event.reminders.as_ref().filter(|r| !r.use_default) {
+        let mut minutes: Vec<u32> = reminders.overrides.iter().map(|o| o.minutes).collect();
+        minutes.sort_unstable();
+        let minutes: Vec<String> = minutes.iter().map(u32::to_string).collect();
+        fields.push(format!("reminders:{}", minutes.join(",")));
+    }
+    let mut hasher = Sha1::new();
+    for field in fields {
+        hasher.update(field.as_bytes());
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shift::{DEFAULT_TITLE, DEFAULT_TZ};
+
+    #[test]
+    fn hash_ignores_offset_and_marker_properties() {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let shift =
+            Shift::on_date(DEFAULT_TITLE, day, "10:00", "19:00", "渋谷店", DEFAULT_TZ).unwrap();
+        let ours = GoogleEvent::from_shift(&shift);
+
+        // Google が UTC で返し、ID や ETag が付いていても同じ内容
+        let mut theirs = ours.clone();
+        theirs.id = "abc".to_string();
+        theirs.etag = Some("\"1\"".to_string());
+        theirs.start.date_time = Some("2026-01-15T01:00:00Z".to_string());
+        theirs.end.date_time = Some("2026-01-15T10:00:00Z".to_string());
+        theirs.extended_properties = None;
+        assert_eq!(content_hash(&ours), content_hash(&theirs));
+
+        theirs.location = Some("新宿店".to_string());
+        assert_ne!(content_hash(&ours), content_hash(&theirs));
+    }
+
+    #[test]
+    fn reminders_become_popup_overrides() {
+        let client = GoogleClient::new("token");
+        let day = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let shift =
+            Shift::on_date(DEFAULT_TITLE, day, "10:00", "19:00", "渋谷店", DEFAULT_TZ).unwrap();
+
+        // 指定がなければ reminders を送らず、ハッシュも通知を付ける前と同じ
+        let plain = GoogleBackend::new(&client, "primary");
+        let event = plain.event(&shift);
+        assert_eq!(event.reminders, None);
+        let mut listed = event.clone();
+        listed.reminders = Some(EventReminders {
+            use_default: true,
+            overrides: Vec::new(),
+        });
+        assert_eq!(content_hash(&event), content_hash(&listed));
+
+        let backend = GoogleBackend::new(&client, "primary")
+            .with_reminders(Reminders::new(&[60, 15]).at("新宿店", &[]));
+        let body = serde_json::to_value(backend.event_for(&shift)).unwrap();
+        assert_eq!(
+            body["reminders"],
+            serde_json::json!({
+                "useDefault": false,
+                "overrides": [
+                    {"method": "popup", "minutes": 60},
+                    {"method": "popup", "minutes": 15},
+                ],
+            })
+        );
+        assert_ne!(backend.content_hash(&shift), plain.content_hash(&shift));
+
+        // 通知なしの場所は既定の通知にも戻さない
+        let mut shinjuku = shift.clone();
+        shinjuku.location = "新宿店".to_string();
+        let body = serde_json::to_value(backend.event_for(&shinjuku)).unwrap();
+        assert_eq!(body["reminders"], serde_json::json!({"useDefault": false}));
+
+        // 場所だけの指定なら、ほかのシフトは既定の通知のまま
+        let only_shinjuku = GoogleBackend::new(&client, "primary")
+            .with_reminders(Reminders::default().at("新宿店", &[30]));
+        assert_eq!(only_shinjuku.event(&shift).reminders, None);
+    }
+}
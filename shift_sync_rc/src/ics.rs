@@ -1,18 +1,1153 @@
-pub fn generate_ics(shifts: Vec<Shift>) -> String {
-    let mut ics_format=String::from("BEGIN:VCALENDAR\nVERSION:2.0\n");
+use std::fmt;
 
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Offset, TimeDelta, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, Tz};
+use sha1::{Digest, Sha1};
+
+use crate::reminder::Reminders;
+use crate::shift::{DEFAULT_TITLE, Shift, ShiftError, uid_date};
+
+/// Go版・Swift版と共通の PRODID
+pub const PRODID: &str = "-//Inazumi Shift Sync//JP";
+
+/// CalDAV に書き込むイベントに付ける印（値はシフトの UID）
+///
+/// 他のクライアントが UID やリソース名を書き換えても、このツールのイベントだと分かるようにする。
+pub const MARKER_PROPERTY: &str = "X-SHIFT-SYNC";
+
+/// 内容が変わったかどうかを決めるプロパティ（DTSTAMP などは比べない）
+const COMPARED_PROPERTIES: [&str; 5] = ["DTSTART", "DTEND", "SUMMARY", "LOCATION", "DESCRIPTION"];
+
+/// 1行の最大オクテット数（RFC 5545 3.1、CRLF を除く）
+const MAX_LINE_OCTETS: usize = 75;
+
+/// DTSTART / DTEND の書き方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// ゾーンなしのローカル時刻（Go版・Swift版と同じ。見る端末のゾーンで解釈されてしまう）
+    Floating,
+    /// `TZID=` 付きのシフトのゾーンの時刻と、tz データから作った VTIMEZONE
+    #[default]
+    Zoned,
+    /// UTC（末尾 `Z`）
+    Utc,
+}
+
+/// iCalendar の書き出し方
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IcsOptions {
+    pub times: TimeFormat,
+    /// イベントに付ける VALARM（既定はなし）
+    pub reminders: Reminders,
+}
+
+/// シフト一覧を iCalendar (RFC 5545) 形式の文字列に変換
+///
+/// PRODID・プロパティの順番・LOCATION/DESCRIPTION の省略・エスケープは Swift版と同じだが、
+/// RFC 5545 に合わせたので出力はバイト単位では一致しない。
+/// - 改行はすべて CRLF（Swift版は複数行リテラルの行が LF、LOCATION 以降だけ CRLF）
+/// - 75 オクテットを超える行は折り返す（Swift版は折り返さない）
+/// - DTSTAMP は UTC で末尾に `Z` を付ける（Swift版は端末のゾーンのローカル時刻で `Z` なし）
+/// - DTSTART/DTEND は既定で `TZID=` 付きの時刻と VTIMEZONE（Swift版は端末のゾーンのフローティング時刻。
+///   `TimeFormat::Floating` でも、使うのは端末ではなくシフトのゾーン）
+///
+/// Swift版: ICSExporter.exportShifts
+pub fn generate_ics(shifts: &[Shift]) -> String {
+    generate_ics_at(shifts, Utc::now())
+}
+
+/// DTSTAMP を指定して iCalendar を生成する（出力を固定したいテスト・比較用）
+pub fn generate_ics_at(shifts: &[Shift], dtstamp: DateTime<Utc>) -> String {
+    generate_ics_with(shifts, dtstamp, &IcsOptions::default())
+}
+
+/// 書き出し方を指定して iCalendar を生成する
+pub fn generate_ics_with(shifts: &[Shift], dtstamp: DateTime<Utc>, options: &IcsOptions) -> String {
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, &format!("PRODID:{PRODID}"));
+    push_line(&mut ics, "CALSCALE:GREGORIAN");
+    push_line(&mut ics, "METHOD:PUBLISH");
+
+    push_timezones(&mut ics, shifts, options);
     for shift in shifts {
-	let mut start = String::new();
-	let mut end = String::new;
-	
-	fomrat!("{shift.start[]}",start);	
-
-	format!("
-BEGIN:VEVENT\n
-SUMMERY:{shift.titile}\n
-DTSTART:{shift.start}\n
-DTEND:{shift.end}\n
-LOCATION:{shift.location}\n
-END:VEVENT\n",ics_format);
+        push_event(&mut ics, shift, dtstamp, options, false);
+    }
+
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+/// CalDAV に PUT する1件分のカレンダーオブジェクト
+///
+/// RFC 4791 4.1 によりカレンダーコレクションのリソースには METHOD を付けない。
+/// Go版: buildSingleEventICAL
+pub fn generate_event_ics(shift: &Shift) -> String {
+    generate_event_ics_at(shift, Utc::now())
+}
+
+/// DTSTAMP を指定して1件分のカレンダーオブジェクトを生成する
+pub fn generate_event_ics_at(shift: &Shift, dtstamp: DateTime<Utc>) -> String {
+    generate_event_ics_with(shift, dtstamp, &IcsOptions::default())
+}
+
+/// 書き出し方を指定して1件分のカレンダーオブジェクトを生成する
+pub fn generate_event_ics_with(
+    shift: &Shift,
+    dtstamp: DateTime<Utc>,
+    options: &IcsOptions,
+) -> String {
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, &format!("PRODID:{PRODID}"));
+    push_line(&mut ics, "CALSCALE:GREGORIAN");
+    push_timezones(&mut ics, std::slice::from_ref(shift), options);
+    push_event(&mut ics, shift, dtstamp, options, true);
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+/// VEVENT 1件分だけ（既存のカレンダーに差し込む用、MARKER_PROPERTY 付き）
+///
+/// `TimeFormat::Zoned` なら、合う VTIMEZONE（`generate_vtimezone`）は呼び出し側で用意する。
+pub fn generate_vevent(shift: &Shift, dtstamp: DateTime<Utc>, options: &IcsOptions) -> String {
+    let mut ics = String::new();
+    push_event(&mut ics, shift, dtstamp, options, true);
+    ics
+}
+
+/// `tz` の VTIMEZONE だけ（`from` から `to` までの UTC オフセットの切り替わりを書く）
+pub fn generate_vtimezone(tz: Tz, from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    let mut ics = String::new();
+    push_timezone(&mut ics, tz, from, to);
+    ics
+}
+
+/// タイムゾーン `tz` の VTIMEZONE だけを入れたカレンダーオブジェクト
+///
+/// MKCALENDAR の `calendar-timezone` に使う。`at` から1年分の夏時間の切り替わりを書く。
+pub fn generate_timezone_ics(tz: Tz, at: DateTime<Utc>) -> String {
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, &format!("PRODID:{PRODID}"));
+    push_line(&mut ics, "CALSCALE:GREGORIAN");
+    push_timezone(&mut ics, tz, at, at + TimeDelta::days(366));
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+/// `TimeFormat::Zoned` のとき、`shifts` が使うゾーンごとに VTIMEZONE を追記する
+fn push_timezones(ics: &mut String, shifts: &[Shift], options: &IcsOptions) {
+    if options.times != TimeFormat::Zoned {
+        return;
+    }
+    let mut zones: Vec<(Tz, DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for shift in shifts {
+        let (start, end) = (shift.start().to_utc(), shift.end().to_utc());
+        match zones.iter_mut().find(|(tz, _, _)| *tz == shift.timezone()) {
+            Some((_, from, to)) => (*from, *to) = ((*from).min(start), (*to).max(end)),
+            None => zones.push((shift.timezone(), start, end)),
+        }
+    }
+    for (tz, from, to) in zones {
+        push_timezone(ics, tz, from, to);
+    }
+}
+
+/// `tz` の VTIMEZONE を追記する
+///
+/// 最初の定義は `from` 時点の UTC オフセットで、あとは `to` までの切り替わりごとに
+/// STANDARD / DAYLIGHT を1つずつ書く（RRULE は使わない）。範囲の外の時刻には使わないこと。
+fn push_timezone(ics: &mut String, tz: Tz, from: DateTime<Utc>, to: DateTime<Utc>) {
+    push_line(ics, "BEGIN:VTIMEZONE");
+    push_line(ics, &format!("TZID:{}", tz.name()));
+    let first = from.with_timezone(&tz);
+    let first_offset = first.offset().fix().local_minus_utc();
+    push_observance(ics, first, "19700101T000000", first_offset);
+    let mut before = first_offset;
+    for at in offset_changes(tz, from, to) {
+        let local = at.with_timezone(&tz);
+        // DTSTART は切り替わる直前のオフセットでのローカル時刻
+        let onset = at + TimeDelta::seconds(i64::from(before));
+        push_observance(
+            ics,
+            local,
+            &onset.format("%Y%m%dT%H%M%S").to_string(),
+            before,
+        );
+        before = local.offset().fix().local_minus_utc();
+    }
+    push_line(ics, "END:VTIMEZONE");
+}
+
+/// `local` の時点から始まる STANDARD / DAYLIGHT
+fn push_observance(ics: &mut String, local: DateTime<Tz>, dtstart: &str, from_offset: i32) {
+    let kind = if local.offset().dst_offset().is_zero() {
+        "STANDARD"
+    } else {
+        "DAYLIGHT"
+    };
+    push_line(ics, &format!("BEGIN:{kind}"));
+    push_line(ics, &format!("DTSTART:{dtstart}"));
+    push_line(ics, &format!("TZOFFSETFROM:{}", format_offset(from_offset)));
+    push_line(
+        ics,
+        &format!(
+            "TZOFFSETTO:{}",
+            format_offset(local.offset().fix().local_minus_utc())
+        ),
+    );
+    push_line(ics, &format!("TZNAME:{}", local.format("%Z")));
+    push_line(ics, &format!("END:{kind}"));
+}
+
+/// `from` より後、`to` までに UTC オフセットか夏時間かどうかが変わる時刻
+///
+/// 1日ごとに見て、変わっていればその日の中を1分単位まで二分探索する。
+fn offset_changes(tz: Tz, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let state = |at: DateTime<Utc>| {
+        let offset = tz.offset_from_utc_datetime(&at.naive_utc());
+        (offset.fix().local_minus_utc(), offset.dst_offset())
+    };
+    let mut changes = Vec::new();
+    let mut day_start = from;
+    while day_start < to {
+        let day_end = (day_start + TimeDelta::days(1)).min(to);
+        if state(day_start) != state(day_end) {
+            let (mut lo, mut hi) = (day_start, day_end);
+            while hi - lo > TimeDelta::minutes(1) {
+                let mid = lo + (hi - lo) / 2;
+                if state(mid) == state(lo) {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            // 切り替わりは分の境目に揃える
+            let secs = hi.timestamp() - hi.timestamp().rem_euclid(60);
+            changes.push(DateTime::from_timestamp(secs, 0).unwrap_or(hi));
+        }
+        day_start = day_end;
+    }
+    changes
+}
+
+/// 32400 -> "+0900"
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{sign}{:02}{:02}", minutes / 60, minutes % 60)
+}
+
+/// 単一のシフトを VEVENT として追記（`marked` なら MARKER_PROPERTY も付ける）
+/// Swift版: ICSExporter.buildEvent
+fn push_event(
+    ics: &mut String,
+    shift: &Shift,
+    dtstamp: DateTime<Utc>,
+    options: &IcsOptions,
+    marked: bool,
+) {
+    push_line(ics, "BEGIN:VEVENT");
+    push_line(ics, &format!("UID:{}", shift.uid()));
+    if marked {
+        push_line(ics, &format!("{MARKER_PROPERTY}:{}", shift.uid()));
+    }
+    push_line(
+        ics,
+        &format!("DTSTAMP:{}", dtstamp.format("%Y%m%dT%H%M%SZ")),
+    );
+    push_line(ics, &format_dt("DTSTART", shift.start(), options.times));
+    push_line(ics, &format_dt("DTEND", shift.end(), options.times));
+    push_line(ics, &format!("SUMMARY:{}", escape_text(&shift.title)));
+    if !shift.location.is_empty() {
+        push_line(ics, &format!("LOCATION:{}", escape_text(&shift.location)));
+    }
+    if !shift.memo.is_empty() {
+        push_line(ics, &format!("DESCRIPTION:{}", escape_text(&shift.memo)));
+    }
+    for &minutes in options.reminders.for_shift(shift) {
+        push_alarm(ics, shift, minutes);
+    }
+    push_line(ics, "END:VEVENT");
+}
+
+/// 開始 `minutes` 分前に表示する VALARM
+fn push_alarm(ics: &mut String, shift: &Shift, minutes: u32) {
+    push_line(ics, "BEGIN:VALARM");
+    push_line(ics, "ACTION:DISPLAY");
+    push_line(ics, &format!("DESCRIPTION:{}", escape_text(&shift.title)));
+    push_line(ics, &format!("TRIGGER:{}", format_trigger(minutes)));
+    push_line(ics, "END:VALARM");
+}
+
+/// 開始前の TRIGGER の値（"-PT15M"、0分なら "PT0M"）
+fn format_trigger(minutes: u32) -> String {
+    if minutes == 0 {
+        "PT0M".to_string()
+    } else {
+        format!("-PT{minutes}M")
+    }
+}
+
+/// DTSTART / DTEND の1行
+///
+/// フローティングならシフトのゾーンでのローカル時刻をそのまま書く。
+/// Go版: formatDT
+fn format_dt(name: &str, dt: DateTime<Tz>, times: TimeFormat) -> String {
+    match times {
+        TimeFormat::Floating => format!("{name}:{}", dt.format("%Y%m%dT%H%M%S")),
+        TimeFormat::Zoned => format!(
+            "{name};TZID={}:{}",
+            dt.timezone().name(),
+            dt.format("%Y%m%dT%H%M%S")
+        ),
+        TimeFormat::Utc => format!("{name}:{}", dt.to_utc().format("%Y%m%dT%H%M%SZ")),
+    }
+}
+
+/// iCalendar テキストのエスケープ
+/// Go版: escapeICalText
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 最初の VEVENT のプロパティを取り出す
+///
+/// 折り返しを戻し、名前は大文字にしてパラメータ（`;TZID=...` など）を除く。値はエスケープされたまま。
+pub fn event_properties(ics: &str) -> Vec<(String, String)> {
+    unfold(ics)
+        .lines()
+        .skip_while(|line| !line.eq_ignore_ascii_case("BEGIN:VEVENT"))
+        .skip(1)
+        .take_while(|line| !line.eq_ignore_ascii_case("END:VEVENT"))
+        .filter_map(|line| {
+            let (head, value) = line.split_once(':')?;
+            let name = head.split(';').next()?.to_ascii_uppercase();
+            Some((name, value.to_string()))
+        })
+        .collect()
+}
+
+/// 最初の VEVENT がこのツールのシフトなら、シフトの UID と開始日
+///
+/// `X-SHIFT-SYNC` があればその値を、なければ `shift-` で始まる UID をシフトの UID とする。
+pub fn shift_event_uid(ics: &str) -> Option<(String, Option<NaiveDate>)> {
+    let props = event_properties(ics);
+    let get = |name: &str| {
+        props
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+            .filter(|v| !v.is_empty())
+    };
+    let uid = get(MARKER_PROPERTY).or_else(|| get("UID").filter(|u| u.starts_with("shift-")))?;
+    let date = uid_date(uid).or_else(|| {
+        let start = get("DTSTART")?;
+        NaiveDate::parse_from_str(start.get(..8)?, "%Y%m%d").ok()
+    });
+    Some((uid.to_string(), date))
+}
+
+/// 最初の VEVENT の比較対象プロパティのハッシュ
+///
+/// 折り返し・プロパティの順番・DTSTAMP の違いでは変わらない。DTSTART・DTEND は TZID も比べる。
+/// VALARM は TRIGGER だけを比べる（VALARM がなければ通知を付ける前と同じ値になる）。
+pub fn event_hash(ics: &str) -> String {
+    let event = read_events(ics).into_iter().next().unwrap_or_default();
+    let triggers: Vec<(String, String)> = event
+        .alarms
+        .iter()
+        .map(|alarm| ("VALARM:TRIGGER".to_string(), alarm.text("TRIGGER")))
+        .collect();
+    let mut props: Vec<(String, String)> = event
+        .properties
+        .into_iter()
+        .filter(|p| COMPARED_PROPERTIES.contains(&p.name.as_str()))
+        .map(|p| {
+            // 同じ時刻でもゾーンの書き方が変われば書き直す
+            let zone = p
+                .param("TZID")
+                .map(|tz| format!(";TZID={tz}"))
+                .unwrap_or_default();
+            (format!("{}{zone}", p.name), p.value)
+        })
+        .chain(triggers)
+        .collect();
+    props.sort();
+    let mut hasher = Sha1::new();
+    for (name, value) in props {
+        hasher.update(format!("{name}:{value}\n").as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// iCalendar の VEVENT をシフトにできなかった理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcsError {
+    /// DTSTART がない
+    MissingStart { uid: String },
+    /// DTSTART・DTEND・DURATION の値が読めない
+    InvalidValue {
+        uid: String,
+        property: String,
+        value: String,
+    },
+    /// 開始・終了がシフトとして成り立たない（終了が開始より前など）
+    Shift { uid: String, source: ShiftError },
+    /// 繰り返しの予定（RRULE・RDATE）。回ごとの日時には展開しない
+    Recurring { uid: String },
+}
+
+impl fmt::Display for IcsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IcsError::MissingStart { uid } => write!(f, "予定 {uid} に DTSTART がない"),
+            IcsError::InvalidValue {
+                uid,
+                property,
+                value,
+            } => write!(f, "予定 {uid} の {property} {value:?} が読めない"),
+            IcsError::Shift { uid, source } => write!(f, "予定 {uid}: {source}"),
+            IcsError::Recurring { uid } => {
+                write!(
+                    f,
+                    "予定 {uid} は繰り返しの予定なので読み込めない（回ごとに登録してください）"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for IcsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IcsError::Shift { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// プロパティ1つ（名前とパラメータ名は大文字、値はエスケープされたまま）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IcsProperty {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    pub value: String,
+}
+
+impl IcsProperty {
+    /// "DTSTART;TZID=Asia/Tokyo:20260115T100000" を名前・パラメータ・値に分ける
+    ///
+    /// パラメータの値は `"..."` で囲まれていれば `:` や `;` を含められる（囲みは外す）。
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut in_quotes = false;
+        let mut head_end = None;
+        let mut cuts = Vec::new();
+        for (i, c) in line.char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                ';' if !in_quotes => cuts.push(i),
+                ':' if !in_quotes => {
+                    head_end = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let head_end = head_end?;
+        let name_end = cuts.first().copied().unwrap_or(head_end);
+        let name = line[..name_end].trim().to_ascii_uppercase();
+        if name.is_empty() {
+            return None;
+        }
+        let mut bounds = cuts;
+        bounds.push(head_end);
+        let params = bounds
+            .windows(2)
+            .filter_map(|w| line[w[0] + 1..w[1]].split_once('='))
+            .map(|(n, v)| {
+                (
+                    n.trim().to_ascii_uppercase(),
+                    v.trim_matches('"').to_string(),
+                )
+            })
+            .collect();
+        Some(IcsProperty {
+            name,
+            params,
+            value: line[head_end + 1..].to_string(),
+        })
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// エスケープを戻した値
+    pub fn text(&self) -> String {
+        unescape_text(&self.value)
+    }
+}
+
+/// 読み取った VEVENT 1件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IcsEvent {
+    pub properties: Vec<IcsProperty>,
+    /// 中の VALARM（プロパティだけ。ほかの入れ子のコンポーネントは読まない）
+    pub alarms: Vec<IcsEvent>,
+}
+
+impl IcsEvent {
+    /// 名前が `name` の最初のプロパティ
+    pub fn get(&self, name: &str) -> Option<&IcsProperty> {
+        self.properties
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// エスケープを戻した値（なければ空）
+    pub fn text(&self, name: &str) -> String {
+        self.get(name).map(IcsProperty::text).unwrap_or_default()
+    }
+
+    pub fn uid(&self) -> String {
+        self.text("UID")
+    }
+
+    /// 時刻の決まった予定ならシフトにする
+    ///
+    /// 日時は `tz` に揃える。ゾーンのない時刻（フローティング）は `tz` の時刻として読む。
+    /// 終日の予定（`VALUE=DATE`）、取り消された予定（`STATUS:CANCELLED`）、
+    /// 長さのない予定（DTEND も DURATION もない、または終了が開始と同じ）はシフトではないので `None`。
+    /// 繰り返しの予定は最初の回だけにならないよう `IcsError::Recurring` にする。
+    /// SUMMARY がなければ DEFAULT_TITLE にする。
+    pub fn to_shift(&self, tz: Tz) -> Result<Option<Shift>, IcsError> {
+        if self
+            .get("STATUS")
+            .is_some_and(|p| p.value.trim().eq_ignore_ascii_case("CANCELLED"))
+        {
+            return Ok(None);
+        }
+        let Some(dtstart) = self.get("DTSTART") else {
+            return Err(IcsError::MissingStart { uid: self.uid() });
+        };
+        if is_date_only(dtstart) {
+            return Ok(None);
+        }
+        if self.get("RRULE").is_some() || self.get("RDATE").is_some() {
+            return Err(IcsError::Recurring { uid: self.uid() });
+        }
+        let start = self.datetime(dtstart, tz)?;
+        let end = match (self.get("DTEND"), self.get("DURATION")) {
+            (Some(dtend), _) => self.datetime(dtend, tz)?,
+            (None, Some(duration)) => {
+                start + parse_duration(&duration.value).ok_or_else(|| self.invalid(duration))?
+            }
+            (None, None) => return Ok(None),
+        };
+        if end == start {
+            return Ok(None);
+        }
+
+        let title = Some(self.text("SUMMARY"))
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| DEFAULT_TITLE.to_string());
+        let shift = Shift::new(title, start, end, self.text("LOCATION")).map_err(|source| {
+            IcsError::Shift {
+                uid: self.uid(),
+                source,
+            }
+        })?;
+        Ok(Some(shift.with_memo(self.text("DESCRIPTION"))))
+    }
+
+    /// DTSTART の日付（ゾーンは見ない。シフトにできない予定がいつのものか見当をつける用）
+    pub fn start_date(&self) -> Option<NaiveDate> {
+        let value = self.get("DTSTART")?.value.trim();
+        NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+    }
+
+    /// 繰り返しの予定の最後の回の日付（ゾーンは見ない）
+    ///
+    /// RRULE の UNTIL と RDATE のうち遅いほう。UNTIL のない RRULE（COUNT で終わるものも）は
+    /// 回を展開しないと分からないので `None`。繰り返しでない予定も `None`。
+    pub fn recurrence_end(&self) -> Option<NaiveDate> {
+        let date = |value: &str| NaiveDate::parse_from_str(value.trim().get(..8)?, "%Y%m%d").ok();
+        let until = match self.get("RRULE") {
+            Some(rrule) => Some(rrule.value.split(';').find_map(|part| {
+                let (name, value) = part.split_once('=')?;
+                name.trim()
+                    .eq_ignore_ascii_case("UNTIL")
+                    .then(|| date(value))?
+            })?),
+            None => None,
+        };
+        let rdates = self
+            .properties
+            .iter()
+            .filter(|p| p.name.eq_ignore_ascii_case("RDATE"))
+            .flat_map(|p| p.value.split(','))
+            .map(date)
+            .collect::<Option<Vec<_>>>()?;
+        until.into_iter().chain(rdates).max()
+    }
+
+    /// DTSTART・DTEND の値を `tz` の日時にする
+    ///
+    /// UTC（末尾 `Z`）、`TZID` 付き、ゾーンなしのどれでもよい。秒のない値や知らない TZID も受け付ける。
+    fn datetime(&self, property: &IcsProperty, tz: Tz) -> Result<DateTime<Tz>, IcsError> {
+        let value = property.value.trim();
+        let naive = |text: &str| {
+            NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%S")
+                .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M"))
+                .ok()
+        };
+        if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+            let naive = naive(utc).ok_or_else(|| self.invalid(property))?;
+            return Ok(Utc.from_utc_datetime(&naive).with_timezone(&tz));
+        }
+        let naive = naive(value).ok_or_else(|| self.invalid(property))?;
+        let zone = property.param("TZID").and_then(parse_tzid).unwrap_or(tz);
+        // 夏時間で重なる時刻は早い方、飛んで存在しない時刻は1時間後として読み、予定を落とさない
+        zone.from_local_datetime(&naive)
+            .earliest()
+            .or_else(|| {
+                zone.from_local_datetime(&(naive + TimeDelta::hours(1)))
+                    .earliest()
+            })
+            .map(|dt| dt.with_timezone(&tz))
+            .ok_or_else(|| self.invalid(property))
+    }
+
+    fn invalid(&self, property: &IcsProperty) -> IcsError {
+        IcsError::InvalidValue {
+            uid: self.uid(),
+            property: property.name.clone(),
+            value: property.value.clone(),
+        }
+    }
+}
+
+/// カレンダーの VEVENT をすべて読む
+///
+/// CRLF / LF のどちらの改行でもよく、折り返し（空白・タブ）を戻す。
+/// 名前の大文字小文字は問わず、`:` のない行や VEVENT の外のプロパティは読み飛ばす。
+pub fn read_events(ics: &str) -> Vec<IcsEvent> {
+    let mut events = Vec::new();
+    let mut event: Option<IcsEvent> = None;
+    let mut alarm: Option<IcsEvent> = None;
+    // VEVENT の中の VALARM などの深さ
+    let mut nested = 0;
+    for line in unfold(ics).lines() {
+        let Some(property) = IcsProperty::parse(line) else {
+            continue;
+        };
+        let component = property.value.trim();
+        match property.name.as_str() {
+            "BEGIN" if event.is_none() => {
+                if component.eq_ignore_ascii_case("VEVENT") {
+                    event = Some(IcsEvent::default());
+                }
+            }
+            "BEGIN" => {
+                nested += 1;
+                if nested == 1 && component.eq_ignore_ascii_case("VALARM") {
+                    alarm = Some(IcsEvent::default());
+                }
+            }
+            "END" if nested > 0 => {
+                nested -= 1;
+                if nested == 0
+                    && let (Some(event), Some(alarm)) = (event.as_mut(), alarm.take())
+                {
+                    event.alarms.push(alarm);
+                }
+            }
+            "END" if component.eq_ignore_ascii_case("VEVENT") => events.extend(event.take()),
+            _ if nested == 1 && alarm.is_some() => {
+                if let Some(alarm) = alarm.as_mut() {
+                    alarm.properties.push(property);
+                }
+            }
+            _ if nested > 0 => {}
+            _ => {
+                if let Some(event) = event.as_mut() {
+                    event.properties.push(property);
+                }
+            }
+        }
+    }
+    events
+}
+
+/// カレンダーを読んだ結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadCalendar {
+    pub shifts: Vec<Shift>,
+    /// シフトにできなかった予定（日時が読めない・終了が開始より前など）
+    pub skipped: Vec<IcsError>,
+}
+
+/// カレンダーの VEVENT のうち、シフトにできるものをシフトにする
+///
+/// 読めない予定があっても残りは読み、理由を `skipped` に残す（`parser::ParseReport` と同じ）。
+/// `generate_ics` の出力はそのまま元のシフトに戻る。
+pub fn read_shifts(ics: &str, tz: Tz) -> ReadCalendar {
+    let mut read = ReadCalendar::default();
+    for event in read_events(ics) {
+        match event.to_shift(tz) {
+            Ok(shift) => read.shifts.extend(shift),
+            Err(err) => read.skipped.push(err),
+        }
+    }
+    read
+}
+
+/// iCalendar テキストのエスケープを戻す（`escape_text` の逆）
+///
+/// `\N` も改行として読み、知らないエスケープは `\` を外すだけにする。
+pub fn unescape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// 折り返しを戻す（RFC 5545 3.1）
+fn unfold(ics: &str) -> String {
+    ics.replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "")
+}
+
+fn is_date_only(property: &IcsProperty) -> bool {
+    property
+        .param("VALUE")
+        .is_some_and(|v| v.eq_ignore_ascii_case("DATE"))
+        || property.value.trim().len() == 8
+}
+
+/// TZID をゾーンにする（`/Asia/Tokyo` のような書き方や Outlook の名前も少し受け付ける）
+fn parse_tzid(id: &str) -> Option<Tz> {
+    let id = id.trim().trim_start_matches('/');
+    match id {
+        "Tokyo Standard Time" => Some(chrono_tz::Asia::Tokyo),
+        _ => id.parse().ok(),
+    }
+}
+
+/// "PT9H"、"PT8H30M"、"P1DT2H" のような DURATION（負の値は読まない）
+fn parse_duration(text: &str) -> Option<TimeDelta> {
+    let rest = text.trim().trim_start_matches('+').strip_prefix('P')?;
+    let mut total = TimeDelta::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => in_time = true,
+            _ => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (c, in_time) {
+                    ('W', false) => TimeDelta::weeks(n),
+                    ('D', false) => TimeDelta::days(n),
+                    ('H', true) => TimeDelta::hours(n),
+                    ('M', true) => TimeDelta::minutes(n),
+                    ('S', true) => TimeDelta::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    number.is_empty().then_some(total)
+}
+
+/// 1行を 75 オクテットで折り返して CRLF 付きで追記する
+///
+/// マルチバイト文字の途中では折り返さない。継続行は先頭の空白1つ分を含めて 75 オクテットに収める。
+fn push_line(ics: &mut String, line: &str) {
+    let mut limit = MAX_LINE_OCTETS;
+    let mut used = 0;
+    for c in line.chars() {
+        if used + c.len_utf8() > limit {
+            ics.push_str("\r\n ");
+            limit = MAX_LINE_OCTETS - 1;
+            used = 0;
+        }
+        ics.push(c);
+        used += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shift::{DEFAULT_TITLE, DEFAULT_TZ};
+    use chrono::{NaiveDate, TimeZone};
+
+    fn shift(location: &str) -> Shift {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        Shift::from_local(
+            DEFAULT_TITLE,
+            day.and_hms_opt(10, 0, 0).unwrap(),
+            day.and_hms_opt(19, 0, 0).unwrap(),
+            location,
+            DEFAULT_TZ,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn follows_swift_exporter_property_order() {
+        let dtstamp = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let floating = IcsOptions {
+            times: TimeFormat::Floating,
+            ..IcsOptions::default()
+        };
+        let ics = generate_ics_with(&[shift("渋谷店, 2F")], dtstamp, &floating);
+        assert_eq!(
+            ics,
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//Inazumi Shift Sync//JP\r\n\
+             CALSCALE:GREGORIAN\r\n\
+             METHOD:PUBLISH\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:shift-20260115-1000-1900-99055630\r\n\
+             DTSTAMP:20260101T000000Z\r\n\
+             DTSTART:20260115T100000\r\n\
+             DTEND:20260115T190000\r\n\
+             SUMMARY:バイト\r\n\
+             LOCATION:渋谷店\\, 2F\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n"
+        );
+    }
+
+    #[test]
+    fn single_event_has_no_method() {
+        let dtstamp = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let ics = generate_event_ics_at(&shift(""), dtstamp);
+        assert!(!ics.contains("METHOD:"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("\r\nUID:shift-20260115-1000-1900-"));
+        assert!(ics.contains("\r\nX-SHIFT-SYNC:shift-20260115-1000-1900-"));
+    }
+
+    #[test]
+    fn timezone_object_for_tokyo() {
+        let at = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let ics = generate_timezone_ics(DEFAULT_TZ, at);
+        assert!(ics.contains("\r\nBEGIN:VTIMEZONE\r\nTZID:Asia/Tokyo\r\n"));
+        assert!(ics.contains("\r\nTZOFFSETFROM:+0900\r\nTZOFFSETTO:+0900\r\nTZNAME:JST\r\n"));
+        assert!(!ics.contains("BEGIN:VEVENT"));
+        assert_eq!(format_offset(-(3 * 3600 + 30 * 60)), "-0330");
+    }
+
+    #[test]
+    fn zoned_times_come_with_vtimezone() {
+        let dtstamp = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let ics = generate_event_ics_at(&shift(""), dtstamp);
+        assert!(ics.contains(
+            "\r\nBEGIN:VTIMEZONE\r\nTZID:Asia/Tokyo\r\nBEGIN:STANDARD\r\nDTSTART:19700101T000000\r\n\
+             TZOFFSETFROM:+0900\r\nTZOFFSETTO:+0900\r\nTZNAME:JST\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\n"
+        ));
+        assert!(ics.contains("\r\nDTSTART;TZID=Asia/Tokyo:20260115T100000\r\n"));
+        assert!(ics.contains("\r\nDTEND;TZID=Asia/Tokyo:20260115T190000\r\n"));
+
+        let utc = IcsOptions {
+            times: TimeFormat::Utc,
+            ..IcsOptions::default()
+        };
+        let ics = generate_event_ics_with(&shift(""), dtstamp, &utc);
+        assert!(!ics.contains("VTIMEZONE"));
+        assert!(ics.contains("\r\nDTSTART:20260115T010000Z\r\nDTEND:20260115T100000Z\r\n"));
+        assert_eq!(read_shifts(&ics, DEFAULT_TZ).shifts, [shift("")]);
+
+        // 同じ時刻でも書き方が変われば書き直す
+        let floating = IcsOptions {
+            times: TimeFormat::Floating,
+            ..IcsOptions::default()
+        };
+        assert_ne!(
+            event_hash(&generate_event_ics_with(&shift(""), dtstamp, &floating)),
+            event_hash(&generate_event_ics_at(&shift(""), dtstamp))
+        );
+    }
+
+    #[test]
+    fn vtimezone_follows_daylight_saving() {
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let before = Shift::on_date(DEFAULT_TITLE, day, "09:00", "17:00", "", new_york).unwrap();
+        let after = Shift::on_date(
+            DEFAULT_TITLE,
+            NaiveDate::from_ymd_opt(2026, 3, 20).unwrap(),
+            "09:00",
+            "17:00",
+            "",
+            new_york,
+        )
+        .unwrap();
+        let dtstamp = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let ics = generate_ics_at(&[before.clone(), after.clone()], dtstamp);
+        assert_eq!(ics.matches("BEGIN:VTIMEZONE").count(), 1);
+        assert!(ics.contains(
+            "\r\nBEGIN:STANDARD\r\nDTSTART:19700101T000000\r\n\
+             TZOFFSETFROM:-0500\r\nTZOFFSETTO:-0500\r\nTZNAME:EST\r\nEND:STANDARD\r\n"
+        ));
+        // 2026年は3月8日 2:00 (EST) に夏時間になる
+        assert!(ics.contains(
+            "\r\nBEGIN:DAYLIGHT\r\nDTSTART:20260308T020000\r\n\
+             TZOFFSETFROM:-0500\r\nTZOFFSETTO:-0400\r\nTZNAME:EDT\r\nEND:DAYLIGHT\r\n"
+        ));
+        assert!(ics.contains("\r\nDTSTART;TZID=America/New_York:20260320T090000\r\n"));
+        assert_eq!(
+            read_shifts(&ics, new_york).shifts,
+            [before.clone(), after.clone()]
+        );
+
+        let calendar = generate_timezone_ics(new_york, dtstamp);
+        assert!(calendar.contains("\r\nDTSTART:20261101T020000\r\nTZOFFSETFROM:-0400\r\n"));
+        assert_eq!(calendar.matches("BEGIN:DAYLIGHT").count(), 1);
+        assert_eq!(calendar.matches("BEGIN:STANDARD").count(), 2);
+    }
+
+    #[test]
+    fn reads_first_event_properties() {
+        let ics = "BEGIN:VCALENDAR\r\nPRODID:x\r\nBEGIN:VEVENT\r\nUID:shift-1\r\n\
+DTSTART;TZID=Asia/Tokyo:20260115T100000\r\nSUMMARY:バ\r\n イト\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:other\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        assert_eq!(
+            event_properties(ics),
+            [
+                ("UID".to_string(), "shift-1".to_string()),
+                ("DTSTART".to_string(), "20260115T100000".to_string()),
+                ("SUMMARY".to_string(), "バイト".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn hash_ignores_dtstamp_and_folding() {
+        let ours = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:shift-1\r\nDTSTAMP:20260101T000000Z\r\n\
+DTSTART:20260115T100000\r\nDTEND:20260115T190000\r\nSUMMARY:バイト\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let theirs = "BEGIN:VCALENDAR\nPRODID:server\nBEGIN:VEVENT\nSUMMARY:バ\n イト\n\
+DTSTAMP:20260301T120000Z\nDTEND:20260115T190000\nDTSTART:20260115T100000\nUID:shift-1\nEND:VEVENT\nEND:VCALENDAR\n";
+        assert_eq!(event_hash(ours), event_hash(theirs));
+        assert_ne!(
+            event_hash(ours),
+            event_hash(&theirs.replace("190000", "200000"))
+        );
+        assert_ne!(
+            event_hash(ours),
+            event_hash(&theirs.replace("UID:shift-1", "UID:shift-1\nLOCATION:渋谷店"))
+        );
+    }
+
+    #[test]
+    fn reminders_become_valarms() {
+        let dtstamp = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let options = IcsOptions {
+            reminders: Reminders::new(&[15, 60]).at("渋谷店", &[0]),
+            ..IcsOptions::default()
+        };
+        let ics = generate_event_ics_with(&shift(""), dtstamp, &options);
+        assert!(ics.contains(
+            "\r\nBEGIN:VALARM\r\nACTION:DISPLAY\r\nDESCRIPTION:バイト\r\n\
+TRIGGER:-PT60M\r\nEND:VALARM\r\nBEGIN:VALARM\r\nACTION:DISPLAY\r\nDESCRIPTION:バイト\r\n\
+TRIGGER:-PT15M\r\nEND:VALARM\r\nEND:VEVENT\r\n"
+        ));
+        let event = &read_events(&ics)[0];
+        assert_eq!(event.alarms.len(), 2);
+        assert_eq!(event.text("DESCRIPTION"), "");
+        assert_eq!(read_shifts(&ics, DEFAULT_TZ).shifts, [shift("")]);
+
+        let at_shibuya = generate_event_ics_with(&shift("渋谷店"), dtstamp, &options);
+        assert!(at_shibuya.contains("\r\nTRIGGER:PT0M\r\n"));
+
+        // 通知がなければ通知を付ける前と同じハッシュで、通知を変えれば書き直す
+        let plain = generate_event_ics_at(&shift(""), dtstamp);
+        assert!(!plain.contains("VALARM"));
+        let no_alarm_here = IcsOptions {
+            reminders: Reminders::new(&[30]).at("", &[]),
+            ..IcsOptions::default()
+        };
+        assert_eq!(
+            event_hash(&plain),
+            event_hash(&generate_event_ics_with(
+                &shift(""),
+                dtstamp,
+                &no_alarm_here
+            ))
+        );
+        assert_ne!(event_hash(&plain), event_hash(&ics));
+        assert_ne!(
+            event_hash(&ics),
+            event_hash(&ics.replace("TRIGGER:-PT15M", "TRIGGER:-PT10M"))
+        );
+    }
+
+    #[test]
+    fn round_trips_generated_calendar() {
+        let night = Shift::on_date(
+            "🍔 マック",
+            NaiveDate::from_ymd_opt(2026, 1, 31).unwrap(),
+            "22:00",
+            "06:00",
+            "店".repeat(40),
+            DEFAULT_TZ,
+        )
+        .unwrap()
+        .with_memo("早番\n持ち物: エプロン, 名札; \\ 以上");
+        let shifts = vec![shift("渋谷店, 2F"), shift(""), night];
+        let dtstamp = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            read_shifts(&generate_ics_at(&shifts, dtstamp), DEFAULT_TZ).shifts,
+            shifts
+        );
+        let single = read_shifts(&generate_event_ics_at(&shifts[2], dtstamp), DEFAULT_TZ);
+        assert_eq!(single.shifts, [shifts[2].clone()]);
+        assert!(single.skipped.is_empty());
+    }
+
+    #[test]
+    fn reads_utc_zoned_and_floating_times() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\nUID:a\r\nDTSTART:20260115T010000Z\r\nDTEND:20260115T100000Z\r\n\
+SUMMARY:レジ\r\nLOCATION:渋谷店\\, 2F\r\nDESCRIPTION:早番\\N朝礼あり\r\n\
+BEGIN:VALARM\r\nDESCRIPTION:通知\r\nEND:VALARM\r\nEND:VEVENT\r\n\
+begin:vevent\nuid:b\ndtstart;tzid=\"America/New_York\":20260116T080000\n\
+DTEND;TZID=/America/New_York:20260116T1\n\t70000\nend:vevent\n\
+BEGIN:VEVENT\r\nUID:c\r\nDTSTART;TZID=Tokyo Standard Time:20260117T2200\r\nDURATION:PT8H\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let shifts = read_shifts(ics, DEFAULT_TZ).shifts;
+        assert_eq!(shifts.len(), 3);
+        assert_eq!(shifts[0].title, "レジ");
+        assert_eq!(shifts[0].location, "渋谷店, 2F");
+        assert_eq!(shifts[0].memo, "早番\n朝礼あり");
+        assert_eq!(shifts[0].time_range_string(), "10:00 - 19:00");
+        // ニューヨークの 8:00 は東京の 22:00
+        assert_eq!(shifts[1].title, DEFAULT_TITLE);
+        assert_eq!(shifts[1].time_range_string(), "22:00 - 07:00");
+        assert_eq!(shifts[2].time_range_string(), "22:00 - 06:00");
+        assert!(shifts.iter().all(|s| s.timezone() == DEFAULT_TZ));
+    }
+
+    #[test]
+    fn skips_events_that_are_not_shifts() {
+        let ics = "BEGIN:VCALENDAR\n\
+BEGIN:VEVENT\nDTSTART;VALUE=DATE:20260115\nDTEND;VALUE=DATE:20260116\nSUMMARY:休み\nEND:VEVENT\n\
+BEGIN:VEVENT\nSTATUS:CANCELLED\nDTSTART:20260115T100000\nDTEND:20260115T190000\nEND:VEVENT\n\
+BEGIN:VEVENT\nDTSTART:20260115T100000\nSUMMARY:リマインダー\nEND:VEVENT\n\
+BEGIN:VEVENT\nDTSTART:20260115T100000\nDTEND:20260115T100000\nSUMMARY:目印\nEND:VEVENT\n\
+BEGIN:VTODO\nDTSTART:20260115T100000\nDTEND:20260115T190000\nEND:VTODO\n\
+END:VCALENDAR\n";
+        let read = read_shifts(ics, DEFAULT_TZ);
+        assert_eq!(read, ReadCalendar::default());
+        assert_eq!(read_events(ics).len(), 4);
+    }
+
+    #[test]
+    fn keeps_reading_past_broken_events() {
+        let ics = "BEGIN:VCALENDAR\n\
+BEGIN:VEVENT\nUID:x\nDTSTART:2026-01-15 10:00\nDTEND:20260115T190000\nEND:VEVENT\n\
+BEGIN:VEVENT\nUID:y\nDTSTART:20260115T190000\nDTEND:20260115T100000\nEND:VEVENT\n\
+BEGIN:VEVENT\nUID:z\nDTSTART:20260116T100000\nDTEND:20260116T190000\nEND:VEVENT\n\
+BEGIN:VEVENT\nUID:w\nDTSTART:20260117T100000\nDTEND:20260117T190000\nRRULE:FREQ=WEEKLY\nEND:VEVENT\n\
+END:VCALENDAR\n";
+        let read = read_shifts(ics, DEFAULT_TZ);
+        assert_eq!(read.shifts.len(), 1);
+        assert_eq!(read.shifts[0].time_range_string(), "10:00 - 19:00");
+        assert_eq!(read.skipped.len(), 3);
+        assert_eq!(
+            read.skipped[0],
+            IcsError::InvalidValue {
+                uid: "x".to_string(),
+                property: "DTSTART".to_string(),
+                value: "2026-01-15 10:00".to_string(),
+            }
+        );
+        assert!(
+            matches!(&read.skipped[1], IcsError::Shift { uid, .. } if uid == "y"),
+            "{}",
+            read.skipped[1]
+        );
+        assert_eq!(
+            read.skipped[2],
+            IcsError::Recurring {
+                uid: "w".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_properties_and_durations() {
+        let p =
+            IcsProperty::parse("ATTENDEE;CN=\"Doe; J:\";ROLE=CHAIR:mailto:j@example.com").unwrap();
+        assert_eq!(p.name, "ATTENDEE");
+        assert_eq!(p.param("cn"), Some("Doe; J:"));
+        assert_eq!(p.param("ROLE"), Some("CHAIR"));
+        assert_eq!(p.value, "mailto:j@example.com");
+        assert!(IcsProperty::parse("no colon").is_none());
+
+        assert_eq!(parse_duration("PT8H30M"), Some(TimeDelta::minutes(510)));
+        assert_eq!(parse_duration("P1DT2H"), Some(TimeDelta::hours(26)));
+        assert_eq!(parse_duration("P1W"), Some(TimeDelta::weeks(1)));
+        assert_eq!(parse_duration("-PT1H"), None);
+        assert_eq!(parse_duration("PT8"), None);
+        assert_eq!(unescape_text(&escape_text("a\\b;c,d\ne")), "a\\b;c,d\ne");
+    }
+
+    #[test]
+    fn escapes_like_go() {
+        assert_eq!(escape_text("a\\b;c,d\ne"), "a\\\\b\\;c\\,d\\ne");
+    }
+
+    #[test]
+    fn folds_without_splitting_multibyte_chars() {
+        let mut out = String::new();
+        push_line(&mut out, &format!("LOCATION:{}", "店".repeat(40)));
+        let lines: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(lines.len() > 1);
+        for line in &lines {
+            assert!(line.len() <= MAX_LINE_OCTETS, "{line:?}");
+        }
+        assert!(lines[1..].iter().all(|l| l.starts_with(' ')));
+        let unfolded: String = lines.join("\r\n").replace("\r\n ", "");
+        assert_eq!(unfolded, format!("LOCATION:{}", "店".repeat(40)));
     }
 }
@@ -0,0 +1,14 @@
+mod atomic_file;
+pub mod backend;
+pub mod caldav;
+pub mod config;
+pub mod google;
+pub mod ics;
+pub mod month;
+pub mod parser;
+pub mod reminder;
+pub mod shift;
+pub mod shiftweb;
+pub mod source;
+pub mod sync;
+pub mod template;
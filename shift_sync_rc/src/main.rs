@@ -1,17 +1,935 @@
-mod shift;
-use shift::Shift;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process;
 
+use shift_sync_rc::backend::CalendarBackend;
+use shift_sync_rc::backend::ics_file::IcsFileBackend;
+use shift_sync_rc::caldav::state::SyncState;
+use shift_sync_rc::caldav::sync::CalDavBackend;
+use shift_sync_rc::caldav::{CalDavClient, Calendar, ICLOUD_URL, NewCalendar, normalize_color};
+use shift_sync_rc::config::{CONFIG_FILE_NAME, CONFIG_VERSION, Config};
+use shift_sync_rc::google::oauth::{LOOPBACK_TIMEOUT, OAuthClient, OAuthConfig, TokenStore};
+use shift_sync_rc::google::sync::GoogleBackend;
+use shift_sync_rc::google::{DEFAULT_API_BASE, GoogleClient};
+use shift_sync_rc::ics::{IcsOptions, TimeFormat};
+use shift_sync_rc::month::{YearMonth, month_range};
+use shift_sync_rc::parser::{ParseReport, parse_shifts};
+use shift_sync_rc::reminder::{ReminderSpec, Reminders};
+use shift_sync_rc::shift::{DEFAULT_TZ, Shift};
+use shift_sync_rc::shiftweb::{DEFAULT_BASE_URL, ShiftWebClient};
+use shift_sync_rc::source::csv_file::CsvSource;
+use shift_sync_rc::source::ics_feed::IcsFeedSource;
+use shift_sync_rc::source::shiftweb::ShiftWebSource;
+use shift_sync_rc::source::{self, LabeledSource, SourceKind, SourceReport, SourceSpec};
+use shift_sync_rc::sync::{self, SyncSummary, SyncWindow};
+
+const USAGE: &str = "\
+ShiftWeb からシフトを取得して表示するツールです。
+
+使い方:
+  shift_sync_rc -list      ShiftWeb 側のシフト一覧を表示する（今月＋来月）
+  shift_sync_rc -html=FILE 保存したシフトページ（HTML）のシフト一覧を表示する
+  shift_sync_rc -calendars CalDAV（iCloud）のカレンダー一覧を表示する
+  shift_sync_rc -setup     同期先のカレンダーを選んで設定ファイルに保存する（新しく作ることもできる）
+  shift_sync_rc -sync [-calendar-url=URL]
+                           ShiftWeb のシフトを CalDAV カレンダーに同期する
+  shift_sync_rc -google-login
+                           Google にログインしてトークンを保存する
+  shift_sync_rc -calendars -google
+                           Google カレンダーの一覧を表示する
+  shift_sync_rc -sync -google [-calendar-id=ID]
+                           ShiftWeb のシフトを Google カレンダーに同期する
+  shift_sync_rc -sync -ics-file=FILE
+                           ShiftWeb のシフトを .ics ファイルに書き出す（カレンダーアプリの購読用）
+
+Options:
+  -list
+      ShiftWeb にログインしてシフト一覧を表示する
+  -sync
+      ShiftWeb のシフトを -calendar-url のカレンダーに同期する
+      （取得した月の範囲にあるシフトだけを追加・更新・削除する）
+  -calendar-url=URL
+      -sync の同期先カレンダー（-calendars で表示される URL、既定は設定ファイルの icloud.calendar_url）
+  -ics-file=FILE
+      -sync の同期先を .ics ファイルにする（なければ作る）
+      シフトの予定だけを追加・更新・削除し、ほかの予定やカレンダーの設定はそのまま残す
+  -utc
+      -sync と併用。CalDAV / .ics ファイルに時刻を UTC で書く
+      （既定は TZID=Asia/Tokyo 付きの時刻と VTIMEZONE）
+  -remind=SPEC
+      -sync と併用。シフト開始の何分前に通知するか（何度でも指定できる）
+        60,15          すべてのシフトに60分前と15分前の通知を付ける
+        渋谷店=30      場所が「渋谷店」のシフトだけ30分前にする（渋谷店= なら通知なし）
+      CalDAV / .ics ファイルでは VALARM、Google カレンダーではポップアップ通知になる
+      （指定しなければ通知を書かず、カレンダーの既定の通知に任せる）
+  -google-login
+      ブラウザで Google にログインし、トークンを ~/.shift_sync/google_token.json に保存する
+      （このマシンの 127.0.0.1 でリダイレクトを受け取る）
+  -device
+      -google-login と併用。表示されたコードを別の端末のブラウザで入力してログインする
+      （ブラウザのないサーバー向け）
+  -google
+      -calendars / -sync で CalDAV の代わりに Google カレンダーを使う
+  -calendar-id=ID
+      -sync -google の同期先カレンダー（-calendars -google で表示される ID、
+      既定は設定ファイルの shift_sync_rc.google.calendar_id）
+  -google-api-url=URL
+      Google Calendar API の URL（既定: https://www.googleapis.com/calendar/v3/）
+  -from=YYYY-MM
+      -list / -sync と併用。取得開始月を指定（例: 2025-01）
+  -to=YYYY-MM
+      -list / -sync と併用。取得終了月を指定（例: 2025-03）
+  -source=SPEC
+      -list / -sync と併用。シフトの取得元（何度でも指定でき、すべてのシフトを1つにまとめる）
+        shiftweb   ShiftWeb（-source を指定しないときはこれだけ）
+        csv:PATH   date,start,end[,location,title,memo] の見出しが付いた CSV ファイル
+        ics:URL    iCalendar の URL（webcal:// も可）
+      後ろに ;title=イベント名 や ;location=場所 を付けると、その取得元のシフトの名前・場所を置き換える
+      （例: -source=\"csv:second_job.csv;title=🍔 マック;location=渋谷店\"）
+  -html=FILE
+      ShiftWeb にアクセスせず、保存した HTML を読む
+  -base-url=URL
+      ShiftWeb の URL（既定: https://example-shift.com）
+  -calendars
+      CalDAV サーバーからカレンダーを探して一覧表示する
+  -setup
+      CalDAV のカレンダー一覧から同期先を選び、Apple ID と一緒に設定ファイルに保存する
+      [0] で新しいカレンダーを作る（名前・色・説明を入力、タイムゾーンは Asia/Tokyo）
+  -caldav-url=URL
+      CalDAV サーバーの URL（既定: https://caldav.icloud.com/）
+  -strict
+      読み取れなかった行・予定が1つでもあればエラー終了する
+  -report
+      勤務なしの行も含め、スキップした行をすべて表示する
+
+設定ファイル ~/.shift_sync/config.toml（Go版と共通）:
+  [shiftweb] id / [icloud] apple_id, calendar_url
+      Go版と同じ。ID はログイン情報の入力の既定、calendar_url は -sync の既定の同期先
+      （パスワードは保存しない）
+  [shift_sync_rc]
+      Rust版だけの設定（version = 1）
+        sources = [\"shiftweb\", \"csv:second_job.csv\"]   -source を指定しないときの取得元
+  [shift_sync_rc.templates]
+      -sync で書き込むイベントの中身（省略した項目は元のまま）
+        summary = \"🍔 {shop} ({hours}h)\"     イベント名
+        location = \"{shop}\"                 場所（変えると UID も変わる）
+        description = \"見込み{wage}円\"      メモ
+        hourly_wage = 1200                   {wage} に使う時給
+      変数: {title} {shop} {memo} {date} {weekday} {start} {end} {hours} {wage}
+      （{ } そのものは {{ }} と書く）
+  [shift_sync_rc.templates.hourly_wages]
+      場所ごとの時給（例: \"マック 渋谷\" = 1300）
+  [shift_sync_rc.reminders]
+      -remind の既定（例: default = [60, 15]、by_location = { \"渋谷店\" = [30] }）
+  [shift_sync_rc.google]
+      calendar_id = \"...\"                 -sync -google の既定の同期先
+
+環境変数:
+  ShiftWeb_ID, ShiftWeb_PASSWORD
+      ShiftWeb のログイン情報（未設定なら入力を求める）
+  ICLOUD_APPLE_ID, ICLOUD_APP_PASSWORD
+      CalDAV（iCloud）のログイン情報（未設定なら入力を求める）
+  GOOGLE_CLIENT_ID, GOOGLE_CLIENT_SECRET
+      Google の OAuth クライアント（デスクトップアプリ）の ID とシークレット
+      （-google-login とトークンの更新に使う。ID が未設定なら入力を求める）
+  GOOGLE_ACCESS_TOKEN
+      Google Calendar API のアクセストークン（設定すると保存したトークンの代わりに使う）
+";
+
+/// Go版: configDirName
+const CONFIG_DIR_NAME: &str = ".shift_sync";
+/// CalDAV に書き込んだイベントの ETag と sync-token の記録
+const CALDAV_STATE_FILE: &str = "caldav_state.txt";
+/// Google の OAuth トークン
+const GOOGLE_TOKEN_FILE: &str = "google_token.json";
+
+#[derive(Default)]
+struct Options {
+    list: bool,
+    calendars: bool,
+    setup: bool,
+    sync: bool,
+    google_login: bool,
+    device: bool,
+    google: bool,
+    utc: bool,
+    html: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    base_url: Option<String>,
+    caldav_url: Option<String>,
+    calendar_url: Option<String>,
+    ics_file: Option<String>,
+    calendar_id: Option<String>,
+    google_api_url: Option<String>,
+    sources: Vec<String>,
+    reminders: Reminders,
+    strict: bool,
+    report: bool,
+    /// 設定ファイル（フラグで指定しなかった値に使う。-list・-calendars・-setup・-sync のときだけ読む）
+    config: Config,
+}
 
 fn main() {
-    //Shiftのインスタンス作成
-    let my_shift = Shift {
-	title: String::from("バイト"),
-	start: String::from("2026-01-10 09:00"),
-	end: String::from("2026-01-10 18:00"),
-	location: String::from("本社"),
-    };
-    println!("シフト: {}",my_shift.title);
-    println!("開始: {}",my_shift.start);
-    println!("終了: {}",my_shift.end);
-    println!("場所: {}",my_shift.location);
+    let mut opts = match parse_args(std::env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(msg) => {
+            println!("{msg}");
+            print!("{USAGE}");
+            process::exit(1);
+        }
+    };
+    // 設定ファイルを使うモードでだけ読む（-html などは設定ファイルが壊れていても使える）
+    if opts.list || opts.calendars || opts.setup || opts.sync {
+        opts.config = match load_config() {
+            Ok(config) => config,
+            Err(msg) => {
+                println!("{msg}");
+                process::exit(1);
+            }
+        };
+    }
+
+    let modes = (
+        &opts.html,
+        opts.list,
+        opts.calendars,
+        opts.setup,
+        opts.sync,
+        opts.google_login,
+    );
+    let result = match modes {
+        (Some(path), false, false, false, false, false) => run_html(&opts, path),
+        (None, true, false, false, false, false) => run_list(&opts),
+        (None, false, true, false, false, false) => run_calendars(&opts),
+        (None, false, false, true, false, false) => run_setup(&opts),
+        (None, false, false, false, true, false) => run_sync(&opts),
+        (None, false, false, false, false, true) => run_google_login(&opts),
+        _ => {
+            print!("{USAGE}");
+            process::exit(1);
+        }
+    };
+    if let Err(msg) = result {
+        println!("{msg}");
+        process::exit(1);
+    }
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut opts = Options::default();
+    for arg in args {
+        let Some(flag) = arg.strip_prefix("--").or_else(|| arg.strip_prefix('-')) else {
+            return Err("位置引数は不要です。".to_string());
+        };
+        let (name, value) = match flag.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (flag, None),
+        };
+        let required = |value: Option<String>| {
+            value
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| format!("`-{name}` には値が必要です（例: -{name}=...）"))
+        };
+        match name {
+            "list" => opts.list = true,
+            "calendars" => opts.calendars = true,
+            "setup" => opts.setup = true,
+            "sync" => opts.sync = true,
+            "google" => opts.google = true,
+            "utc" => opts.utc = true,
+            "google-login" => opts.google_login = true,
+            "device" => opts.device = true,
+            "strict" => opts.strict = true,
+            "report" => opts.report = true,
+            "html" => opts.html = Some(required(value)?),
+            "from" => opts.from = Some(required(value)?),
+            "to" => opts.to = Some(required(value)?),
+            "base-url" => opts.base_url = Some(required(value)?),
+            "caldav-url" => opts.caldav_url = Some(required(value)?),
+            "calendar-url" => opts.calendar_url = Some(required(value)?),
+            "ics-file" => opts.ics_file = Some(required(value)?),
+            "calendar-id" => opts.calendar_id = Some(required(value)?),
+            "google-api-url" => opts.google_api_url = Some(required(value)?),
+            "source" => opts.sources.push(required(value)?),
+            "remind" => {
+                let spec: ReminderSpec =
+                    required(value)?.parse().map_err(|err| format!("{err}"))?;
+                opts.reminders.set(spec);
+            }
+            "h" | "help" => {
+                print!("{USAGE}");
+                process::exit(0);
+            }
+            _ => return Err(format!("不明なフラグです: {arg}")),
+        }
+    }
+    if (opts.from.is_some() || opts.to.is_some()) && !(opts.list || opts.sync) {
+        return Err("`-from` と `-to` は `-list` か `-sync` と一緒に使ってください。".to_string());
+    }
+    if !(opts.sources.is_empty() || opts.list || opts.sync) {
+        return Err("`-source` は `-list` か `-sync` と一緒に使ってください。".to_string());
+    }
+    if opts.google && !(opts.calendars || opts.sync) {
+        return Err("`-google` は `-calendars` か `-sync` と一緒に使ってください。".to_string());
+    }
+    if opts.device && !opts.google_login {
+        return Err("`-device` は `-google-login` と一緒に使ってください。".to_string());
+    }
+    if opts.google && opts.calendar_url.is_some() {
+        return Err(
+            "`-google` では `-calendar-url` ではなく `-calendar-id` を指定してください。"
+                .to_string(),
+        );
+    }
+    if !opts.google && opts.calendar_id.is_some() {
+        return Err("`-calendar-id` は `-google` と一緒に使ってください。".to_string());
+    }
+    if opts.utc && (!opts.sync || opts.google) {
+        return Err(
+            "`-utc` は `-sync`（CalDAV か `-ics-file`）と一緒に使ってください。".to_string(),
+        );
+    }
+    if !opts.reminders.is_empty() && !opts.sync {
+        return Err("`-remind` は `-sync` と一緒に使ってください。".to_string());
+    }
+    if opts.ics_file.is_some() && !opts.sync {
+        return Err("`-ics-file` は `-sync` と一緒に使ってください。".to_string());
+    }
+    if opts.ics_file.is_some() && (opts.google || opts.calendar_url.is_some()) {
+        return Err(
+            "`-ics-file` は `-google` や `-calendar-url` と一緒には使えません。".to_string(),
+        );
+    }
+    Ok(opts)
+}
+
+fn run_html(opts: &Options, path: &str) -> Result<(), String> {
+    let html =
+        std::fs::read_to_string(path).map_err(|err| format!("{path} の読み込みに失敗: {err}"))?;
+    let page = parse_shifts(&html, DEFAULT_TZ).map_err(|err| format!("シフト解析に失敗: {err}"))?;
+
+    let mut shifts = page.shifts;
+    print_shifts(&mut shifts);
+    print_report(opts, &page.report, None);
+    check_strict(opts, page.report.has_problems())
+}
+
+fn run_list(opts: &Options) -> Result<(), String> {
+    let fetched = fetch_shifts(opts)?;
+    let has_problems = fetched.has_problems();
+    let mut shifts = fetched.shifts;
+    print_shifts(&mut shifts);
+    check_strict(opts, has_problems)
+}
+
+fn run_sync(opts: &Options) -> Result<(), String> {
+    // 取得を始める前に同期先を決めておく
+    if opts.google {
+        google_calendar_id(opts)?;
+    } else if opts.ics_file.is_none() {
+        caldav_calendar_url(opts)?;
+    }
+    let mut fetched = fetch_shifts(opts)?;
+    check_strict(opts, fetched.has_problems())?;
+    fetched.shifts = fetched
+        .shifts
+        .into_iter()
+        .map(|s| opts.config.templates.apply(s))
+        .collect();
+    let window = SyncWindow::from_months(&fetched.months).expect("month_range is never empty");
+
+    let summary = if opts.google {
+        sync_google(opts, &fetched, window)?
+    } else if let Some(path) = &opts.ics_file {
+        sync_ics_file(opts, path, &fetched, window)?
+    } else {
+        sync_caldav(opts, &fetched, window)?
+    };
+
+    for conflict in &summary.conflicts {
+        println!("  => {conflict}");
+    }
+    for failure in &summary.failures {
+        println!("  => {failure}");
+    }
+    println!("同期完了: {}", summary.short_description());
+    if !summary.failures.is_empty() {
+        return Err(format!("{} 件の同期に失敗しました", summary.failures.len()));
+    }
+    Ok(())
+}
+
+/// どの書き込み先にも同じ手順で同期する
+fn sync_to<B: CalendarBackend>(
+    backend: &mut B,
+    fetched: &Fetched,
+    window: SyncWindow,
+) -> Result<SyncSummary, B::Error> {
+    println!(
+        "{} 件のシフトを同期中（{} 〜 {}）…",
+        fetched.shifts.len(),
+        fetched.months.first().unwrap(),
+        fetched.months.last().unwrap()
+    );
+    if !backend.capabilities().detects_edits {
+        println!(
+            "※ このカレンダーでは他での編集を見分けられないため、変わったシフトは上書きします。"
+        );
+    }
+    sync::sync_shifts(backend, &fetched.shifts, window)
+}
+
+fn sync_caldav(
+    opts: &Options,
+    fetched: &Fetched,
+    window: SyncWindow,
+) -> Result<SyncSummary, String> {
+    let calendar_url = caldav_calendar_url(opts)?;
+    let (apple_id, app_password) = icloud_credentials(opts)?;
+    let client = CalDavClient::new(&apple_id, &app_password);
+    let state_path = config_dir()?.join(CALDAV_STATE_FILE);
+    let mut state = SyncState::load(&state_path, calendar_url)
+        .map_err(|err| format!("{} の読み込みに失敗: {err}", state_path.display()))?;
+    let result = sync_to(
+        &mut CalDavBackend::new(&client, &mut state).with_options(ics_options(opts)),
+        fetched,
+        window,
+    );
+    // 途中まで書き込んだ分の ETag も残す
+    state
+        .save(&state_path)
+        .map_err(|err| format!("{} の保存に失敗: {err}", state_path.display()))?;
+    result.map_err(|err| format!("CalDAV 同期に失敗: {err}"))
+}
+
+fn sync_google(
+    opts: &Options,
+    fetched: &Fetched,
+    window: SyncWindow,
+) -> Result<SyncSummary, String> {
+    let calendar_id = google_calendar_id(opts)?;
+    let client = google_client(opts)?;
+    sync_to(
+        &mut GoogleBackend::new(&client, calendar_id).with_reminders(reminders(opts)),
+        fetched,
+        window,
+    )
+    .map_err(|err| format!("Google カレンダー同期に失敗: {err}"))
+}
+
+/// -sync の CalDAV の同期先（フラグ、なければ設定ファイル）
+fn caldav_calendar_url(opts: &Options) -> Result<&str, String> {
+    opts.calendar_url
+        .as_deref()
+        .or(Some(opts.config.icloud.calendar_url.as_str()).filter(|url| !url.is_empty()))
+        .ok_or_else(|| {
+            "`-sync` には `-calendar-url` か `-ics-file` が必要です（`-setup` で保存もできます）。"
+                .to_string()
+        })
+}
+
+/// -sync -google の同期先（フラグ、なければ設定ファイル）
+fn google_calendar_id(opts: &Options) -> Result<&str, String> {
+    opts.calendar_id
+        .as_deref()
+        .or(opts.config.google.calendar_id.as_deref())
+        .ok_or_else(|| "`-sync -google` には `-calendar-id` が必要です。".to_string())
+}
+
+/// 設定ファイルの通知に `-remind` の指定を重ねたもの
+fn reminders(opts: &Options) -> Reminders {
+    let mut reminders = opts.config.reminders.clone();
+    if !opts.reminders.default.is_empty() {
+        reminders.default = opts.reminders.default.clone();
+    }
+    for (location, minutes) in &opts.reminders.by_location {
+        reminders.set(ReminderSpec {
+            location: Some(location.clone()),
+            minutes: minutes.clone(),
+        });
+    }
+    reminders
+}
+
+/// CalDAV・.ics ファイルに書くイベントの書き出し方
+fn ics_options(opts: &Options) -> IcsOptions {
+    IcsOptions {
+        times: if opts.utc {
+            TimeFormat::Utc
+        } else {
+            TimeFormat::Zoned
+        },
+        reminders: reminders(opts),
+    }
+}
+
+fn sync_ics_file(
+    opts: &Options,
+    path: &str,
+    fetched: &Fetched,
+    window: SyncWindow,
+) -> Result<SyncSummary, String> {
+    let mut backend = IcsFileBackend::open(path)
+        .map_err(|err| err.to_string())?
+        .with_options(ics_options(opts));
+    sync_to(&mut backend, fetched, window)
+        .map_err(|err| format!(".ics ファイルの同期に失敗: {err}"))
+}
+
+/// 設定などを置くディレクトリ（Go版と同じ ~/.shift_sync）
+fn config_dir() -> Result<PathBuf, String> {
+    std::env::home_dir()
+        .map(|home| home.join(CONFIG_DIR_NAME))
+        .ok_or_else(|| "ホームディレクトリ取得失敗".to_string())
+}
+
+/// 設定ファイルを読む（なければ既定の設定）
+///
+/// 古い形式のファイルだったら今の形式で書き直しておく。
+fn load_config() -> Result<Config, String> {
+    let path = config_path()?;
+    let config = Config::load(&path).map_err(|err| err.to_string())?;
+    if let Some(version) = config.migrated_from {
+        config.save(&path).map_err(|err| err.to_string())?;
+        println!(
+            "設定ファイルを新しい形式に書き直しました（v{version} → v{CONFIG_VERSION}）: {}",
+            path.display()
+        );
+    }
+    Ok(config)
+}
+
+/// Go版: configPath
+fn config_path() -> Result<PathBuf, String> {
+    Ok(config_dir()?.join(CONFIG_FILE_NAME))
+}
+
+/// 取得元から取得したシフト
+struct Fetched {
+    months: Vec<YearMonth>,
+    shifts: Vec<Shift>,
+    reports: Vec<SourceReport>,
+}
+
+impl Fetched {
+    fn has_problems(&self) -> bool {
+        self.reports.iter().any(SourceReport::has_problems)
+    }
+}
+
+/// `-from` / `-to` の範囲の月のシフトを、すべての取得元から取得してまとめる
+fn fetch_shifts(opts: &Options) -> Result<Fetched, String> {
+    let parse_month = |text: &Option<String>, label: &str| {
+        text.as_deref()
+            .map(|t| t.parse::<YearMonth>())
+            .transpose()
+            .map_err(|err| format!("{label} が不正です: {err}"))
+    };
+    let from = parse_month(&opts.from, "from")?;
+    let to = parse_month(&opts.to, "to")?;
+    let months =
+        month_range(from, to, YearMonth::now(DEFAULT_TZ)).map_err(|err| err.to_string())?;
+
+    let mut sources = shift_sources(opts)?;
+    let fetched = source::fetch_all(&mut sources, &months).map_err(|err| err.to_string())?;
+
+    for report in &fetched.reports {
+        match report {
+            SourceReport::Page { month, report } => print_report(opts, report, Some(*month)),
+            SourceReport::Events { url, skipped } => {
+                for err in skipped {
+                    println!("[{url}] 読み取れなかった予定: {err}");
+                }
+            }
+            SourceReport::Rows { path, skipped } => {
+                for (line, message) in skipped {
+                    println!("[{}] {line}行目が読めない: {message}", path.display());
+                }
+            }
+        }
+    }
+    Ok(Fetched {
+        months,
+        shifts: fetched.shifts,
+        reports: fetched.reports,
+    })
+}
+
+/// `-source` の取得元（指定がなければ ShiftWeb だけ）
+///
+/// ShiftWeb のログイン情報は ShiftWeb を使うときだけ聞く。
+fn shift_sources(opts: &Options) -> Result<Vec<LabeledSource>, String> {
+    let specs: Vec<SourceSpec> = if !opts.sources.is_empty() {
+        opts.sources
+            .iter()
+            .map(|text| text.parse().map_err(|err| format!("{err}")))
+            .collect::<Result<_, _>>()?
+    } else if !opts.config.sources.is_empty() {
+        opts.config.sources.clone()
+    } else {
+        vec![SourceSpec {
+            kind: SourceKind::ShiftWeb,
+            title: None,
+            location: None,
+        }]
+    };
+    let mut sources = Vec::new();
+    for spec in specs {
+        let mut labeled = match spec.kind {
+            SourceKind::ShiftWeb => {
+                let (id, password) = shiftweb_credentials(opts)?;
+                let client =
+                    ShiftWebClient::new(opts.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL));
+                LabeledSource::new(ShiftWebSource::new(client, &id, &password, DEFAULT_TZ))
+            }
+            SourceKind::Csv(path) => LabeledSource::new(CsvSource::new(path, DEFAULT_TZ)),
+            SourceKind::IcsFeed(url) => LabeledSource::new(IcsFeedSource::new(&url, DEFAULT_TZ)),
+        };
+        labeled.title = spec.title;
+        labeled.location = spec.location;
+        sources.push(labeled);
+    }
+    Ok(sources)
+}
+
+fn run_calendars(opts: &Options) -> Result<(), String> {
+    if opts.google {
+        return run_google_calendars(opts);
+    }
+    let (apple_id, app_password) = icloud_credentials(opts)?;
+    let client = CalDavClient::new(&apple_id, &app_password);
+    println!("カレンダーを検索中…");
+    let found = client
+        .discover(opts.caldav_url.as_deref().unwrap_or(ICLOUD_URL))
+        .map_err(|err| format!("カレンダーの検索に失敗: {err}"))?;
+
+    let calendars: Vec<_> = found
+        .calendars
+        .iter()
+        .filter(|c| c.supports_events())
+        .collect();
+    if calendars.is_empty() {
+        println!("既存のカレンダーが見つかりませんでした。");
+    }
+    for (i, cal) in calendars.iter().enumerate() {
+        println!("[{}] {}  ->  {}", i + 1, cal.name(), cal.url);
+    }
+    Ok(())
+}
+
+/// 書き込める Google カレンダーの一覧を表示する
+/// Swift版: GoogleCalendarService.getCalendars
+fn run_google_calendars(opts: &Options) -> Result<(), String> {
+    let client = google_client(opts)?;
+    println!("カレンダーを検索中…");
+    let calendars: Vec<_> = client
+        .list_calendars()
+        .map_err(|err| format!("カレンダーの検索に失敗: {err}"))?
+        .into_iter()
+        .filter(|c| c.is_writable())
+        .collect();
+    if calendars.is_empty() {
+        println!("書き込めるカレンダーが見つかりませんでした。");
+    }
+    for (i, cal) in calendars.iter().enumerate() {
+        println!("[{}] {}  ->  {}", i + 1, cal.summary, cal.id);
+    }
+    Ok(())
+}
+
+/// Google API のクライアント
+///
+/// GOOGLE_ACCESS_TOKEN があればそれを使い、なければ -google-login で保存したトークンを使う
+/// （期限が切れていればリフレッシュトークンで取り直す）。
+fn google_client(opts: &Options) -> Result<GoogleClient, String> {
+    let token = match std::env::var("GOOGLE_ACCESS_TOKEN") {
+        Ok(token) if !token.is_empty() => token,
+        _ => stored_google_token()?,
+    };
+    let base_url = opts.google_api_url.as_deref().unwrap_or(DEFAULT_API_BASE);
+    GoogleClient::with_base_url(base_url, &token).map_err(|err| err.to_string())
+}
+
+fn stored_google_token() -> Result<String, String> {
+    let store = google_token_store()?;
+    let not_logged_in = || "Google にログインしていません。先に -google-login を実行してください。";
+    let token = store
+        .load()
+        .map_err(|err| format!("トークンの読み込みに失敗: {err}"))?
+        .ok_or_else(not_logged_in)?;
+    if !token.is_expired(chrono::Utc::now().timestamp()) {
+        return Ok(token.access_token);
+    }
+    // 期限切れのときだけクライアントの情報が要る
+    oauth_client()?
+        .stored_token(&store)
+        .map_err(|err| format!("トークンの更新に失敗: {err}"))?
+        .map(|token| token.access_token)
+        .ok_or_else(|| not_logged_in().to_string())
+}
+
+fn google_token_store() -> Result<TokenStore, String> {
+    Ok(TokenStore::new(config_dir()?.join(GOOGLE_TOKEN_FILE)))
+}
+
+fn oauth_client() -> Result<OAuthClient, String> {
+    let client_id = env_or_prompt(
+        "GOOGLE_CLIENT_ID",
+        "",
+        "Google の OAuth クライアントID: ",
+        "クライアントID",
+    )?;
+    let client_secret = std::env::var("GOOGLE_CLIENT_SECRET")
+        .ok()
+        .filter(|s| !s.is_empty());
+    Ok(OAuthClient::new(OAuthConfig::google(
+        &client_id,
+        client_secret.as_deref(),
+    )))
+}
+
+/// Google にログインしてトークンを保存する
+/// Swift版: GoogleCalendarService.signIn（GoogleSignIn の代わりに OAuth を直接話す）
+fn run_google_login(opts: &Options) -> Result<(), String> {
+    let client = oauth_client()?;
+    let token = if opts.device {
+        let device = client
+            .start_device()
+            .map_err(|err| format!("ログインの開始に失敗: {err}"))?;
+        println!("別の端末のブラウザで次のページを開いて、コードを入力してください。");
+        println!("  {}", device.verification_uri);
+        println!("  コード: {}", device.user_code);
+        println!("入力を待っています…");
+        client.poll_device(&device, std::thread::sleep)
+    } else {
+        let login = client
+            .start_loopback()
+            .map_err(|err| format!("ログインの開始に失敗: {err}"))?;
+        println!("ブラウザで次の URL を開いて Google にログインしてください。");
+        println!("  {}", login.authorize_url());
+        println!(
+            "ログインを待っています…（{}分で打ち切ります）",
+            LOOPBACK_TIMEOUT.as_secs() / 60
+        );
+        login.finish(&client)
+    }
+    .map_err(|err| format!("Google ログインに失敗: {err}"))?;
+
+    let store = google_token_store()?;
+    store
+        .save(&token)
+        .map_err(|err| format!("{} の保存に失敗: {err}", store.path().display()))?;
+    println!("ログインしました。（{}）", store.path().display());
+    if token.refresh_token.is_none() {
+        println!(
+            "※ リフレッシュトークンがもらえませんでした。期限が切れたらもう一度ログインしてください。"
+        );
+    }
+    Ok(())
+}
+
+/// 同期先のカレンダーを選ぶ（[0] なら新しく作る）
+/// Go版: runSetup のカレンダー選択部分
+fn run_setup(opts: &Options) -> Result<(), String> {
+    let (apple_id, app_password) = icloud_credentials(opts)?;
+    let client = CalDavClient::new(&apple_id, &app_password);
+    println!("\nカレンダーを検索中…");
+    let found = client
+        .discover(opts.caldav_url.as_deref().unwrap_or(ICLOUD_URL))
+        .map_err(|err| format!("カレンダーの検索に失敗: {err}"))?;
+
+    let calendars: Vec<Calendar> = found
+        .calendars
+        .into_iter()
+        .filter(Calendar::supports_events)
+        .collect();
+    if calendars.is_empty() {
+        println!("既存のカレンダーが見つかりませんでした。");
+    }
+    println!("\n=== 見つかったカレンダー ===");
+    println!("[0] 新しいカレンダーを作成");
+    if calendars.is_empty() {
+        println!("  (既存カレンダーなし)");
+    }
+    for (i, cal) in calendars.iter().enumerate() {
+        println!("[{}] {}  ->  {}", i + 1, cal.name(), cal.url);
+    }
+
+    let selected = loop {
+        let prompt = format!(
+            "\nどのカレンダーにシフトを登録する？ [0-{}]: ",
+            calendars.len()
+        );
+        match parse_choice(&read_line(&prompt, "番号")?, calendars.len()) {
+            None => println!("番号を入れて〜。"),
+            Some(0) => {
+                let new = ask_new_calendar()?;
+                println!("新しいカレンダーを作成します: {}", new.display_name);
+                match client.create_calendar(&found.home_url, &new) {
+                    Ok(calendar) => break calendar,
+                    Err(err) => println!("新しいカレンダーの作成に失敗: {err}"),
+                }
+            }
+            Some(i) => break calendars[i - 1].clone(),
+        }
+    };
+
+    println!("\n同期先カレンダー: {} ({})", selected.name(), selected.url);
+    let path = config_path()?;
+    let mut config = opts.config.clone();
+    config.icloud.apple_id = apple_id;
+    config.icloud.calendar_url = selected.url.to_string();
+    config.save(&path).map_err(|err| err.to_string())?;
+    println!("\n設定を保存したよ: {}", path.display());
+    println!("これからは -sync だけでこのカレンダーに同期します。");
+    Ok(())
+}
+
+/// 新しく作るカレンダーの名前・色・説明を聞く
+/// Go版: createNewCalendarFlow
+fn ask_new_calendar() -> Result<NewCalendar, String> {
+    const DEFAULT_CALENDAR_NAME: &str = "バイト";
+
+    let name = read_line(
+        &format!("新しく作るカレンダーの名前を入力して下さい（例: {DEFAULT_CALENDAR_NAME}）: "),
+        "カレンダー名",
+    )?;
+    let color = loop {
+        let text = read_line("カレンダーの色（例: #FF2968、空なら既定の色）: ", "色")?;
+        if text.is_empty() {
+            break None;
+        }
+        match normalize_color(&text) {
+            Some(color) => break Some(color),
+            None => println!("#RRGGBB の形で入れて〜。"),
+        }
+    };
+    let description = read_line("カレンダーの説明（空なら無し）: ", "説明")?;
+
+    Ok(NewCalendar {
+        description: Some(description).filter(|d| !d.is_empty()),
+        color,
+        timezone: Some(DEFAULT_TZ),
+        ..NewCalendar::new(if name.is_empty() {
+            DEFAULT_CALENDAR_NAME
+        } else {
+            &name
+        })
+    })
+}
+
+/// "0"〜"max" の番号
+/// Go版: parseChoice
+fn parse_choice(text: &str, max: usize) -> Option<usize> {
+    text.trim().parse().ok().filter(|&i| i <= max)
+}
+
+/// 環境変数（ID は設定ファイルも）、なければ標準入力から ShiftWeb のログイン情報を読む
+fn shiftweb_credentials(opts: &Options) -> Result<(String, String), String> {
+    let id = env_or_prompt(
+        "ShiftWeb_ID",
+        &opts.config.shiftweb.id,
+        "ShiftWeb のログインID: ",
+        "ShiftWeb ID",
+    )?;
+    let password = env_or_prompt_password(
+        "ShiftWeb_PASSWORD",
+        "ShiftWeb のパスワード: ",
+        "ShiftWeb パスワード",
+    )?;
+    Ok((id, password))
+}
+
+/// 環境変数（Apple ID は設定ファイルも）、なければ標準入力から iCloud（CalDAV）のログイン情報を読む
+fn icloud_credentials(opts: &Options) -> Result<(String, String), String> {
+    let apple_id = env_or_prompt(
+        "ICLOUD_APPLE_ID",
+        &opts.config.icloud.apple_id,
+        "Apple ID（iCloud, メールアドレス）: ",
+        "Apple ID",
+    )?;
+    let password = env_or_prompt_password(
+        "ICLOUD_APP_PASSWORD",
+        "iCloud アプリ用パスワード: ",
+        "アプリ用パスワード",
+    )?;
+    Ok((apple_id, password))
+}
+
+/// 環境変数、なければ `saved`（設定ファイルの値）、それも空なら標準入力から読む
+fn env_or_prompt(var: &str, saved: &str, prompt: &str, label: &str) -> Result<String, String> {
+    let value = match std::env::var(var) {
+        Ok(value) if !value.is_empty() => value,
+        _ if !saved.is_empty() => saved.to_string(),
+        _ => read_line(prompt, label)?,
+    };
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(format!("{label} が空です"));
+    }
+    Ok(value.to_string())
+}
+
+/// `prompt` を出して標準入力から1行読む（前後の空白は落とす）
+fn read_line(prompt: &str, label: &str) -> Result<String, String> {
+    print!("{prompt}");
+    io::stdout().flush().ok();
+    let mut line = String::new();
+    let read = io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|err| format!("{label} の入力に失敗: {err}"))?;
+    if read == 0 {
+        return Err(format!("{label} の入力に失敗: 入力が終わりました"));
+    }
+    Ok(line.trim().to_string())
+}
+
+fn env_or_prompt_password(var: &str, prompt: &str, label: &str) -> Result<String, String> {
+    let value = match std::env::var(var) {
+        Ok(value) if !value.is_empty() => value,
+        _ => {
+            rpassword::prompt_password(prompt).map_err(|err| format!("{label}入力に失敗: {err}"))?
+        }
+    };
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(format!("{label}が空です"));
+    }
+    Ok(value.to_string())
+}
+
+fn print_shifts(shifts: &mut [Shift]) {
+    shifts.sort_by_key(|s| s.start());
+    for s in shifts.iter() {
+        println!(
+            "{}  {}-{}  {}",
+            s.start().format("%Y-%m-%d"),
+            s.start().format("%H:%M"),
+            s.end().format("%H:%M"),
+            s.location
+        );
+    }
+    println!("合計 {} 件", shifts.len());
+}
+
+fn print_report(opts: &Options, report: &ParseReport, month: Option<YearMonth>) {
+    let prefix = month.map(|m| format!("[{m}] ")).unwrap_or_default();
+    if opts.report {
+        for row in &report.skipped {
+            println!("{prefix}スキップ: {row}");
+        }
+    } else {
+        for row in report.problems() {
+            println!("{prefix}読み取れなかった行: {row}");
+        }
+    }
+}
+
+fn check_strict(opts: &Options, has_problems: bool) -> Result<(), String> {
+    if opts.strict && has_problems {
+        return Err("読み取れなかった行・予定があるため終了します（-strict）。".to_string());
+    }
+    Ok(())
 }
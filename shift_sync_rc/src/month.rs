@@ -0,0 +1,226 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+/// 年月（ShiftWeb の月ページ単位）
+/// Go版: yearMonth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct YearMonth {
+    pub year: i32,
+    pub month: u32,
+}
+
+impl YearMonth {
+    /// 1〜12 以外の月なら `None`
+    pub fn new(year: i32, month: u32) -> Option<Self> {
+        (1..=12)
+            .contains(&month)
+            .then_some(YearMonth { year, month })
+    }
+
+    /// `tz` での今月
+    pub fn now(tz: Tz) -> Self {
+        let today = Utc::now().with_timezone(&tz).date_naive();
+        YearMonth::of(today)
+    }
+
+    /// 日付が属する月
+    pub fn of(date: NaiveDate) -> Self {
+        YearMonth {
+            year: date.year(),
+            month: date.month(),
+        }
+    }
+
+    /// `n` か月後（負なら前）の月
+    /// Go版: addMonths
+    pub fn add_months(self, n: i32) -> Self {
+        let index = self.year * 12 + self.month as i32 - 1 + n;
+        YearMonth {
+            year: index.div_euclid(12),
+            month: index.rem_euclid(12) as u32 + 1,
+        }
+    }
+
+    /// 月初日
+    pub fn first_day(self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1).expect("month is always 1..=12")
+    }
+
+    /// この月ページに載っている `month` 月の行が何年のものかを推定する
+    ///
+    /// 月ページには前後の月の日付がはみ出して載ることがある（12月ページの "1/2" など）。
+    /// ページの月から半年以上離れた月は、前後の年のものとみなす。
+    pub fn infer_year(self, month: u32) -> i32 {
+        let diff = month as i32 - self.month as i32;
+        if diff > 6 {
+            self.year - 1
+        } else if diff < -6 {
+            self.year + 1
+        } else {
+            self.year
+        }
+    }
+}
+
+impl fmt::Display for YearMonth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+/// "YYYY-MM" を読めなかった
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseYearMonthError(String);
+
+impl fmt::Display for ParseYearMonthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} は YYYY-MM 形式ではありません", self.0)
+    }
+}
+
+impl std::error::Error for ParseYearMonthError {}
+
+impl FromStr for YearMonth {
+    type Err = ParseYearMonthError;
+
+    /// Go版: parseYYYYMM
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseYearMonthError(s.to_string());
+        let (year, month) = s.split_once('-').ok_or_else(invalid)?;
+        if year.len() != 4 || month.is_empty() || month.len() > 2 {
+            return Err(invalid());
+        }
+        let year = year.parse().map_err(|_| invalid())?;
+        let month = month.parse().map_err(|_| invalid())?;
+        YearMonth::new(year, month).ok_or_else(invalid)
+    }
+}
+
+/// 一度に取得できる最大の月数
+pub const MAX_MONTHS: usize = 12;
+
+/// 取得する月の範囲が不正
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonthRangeError {
+    FromAfterTo { from: YearMonth, to: YearMonth },
+    TooLong,
+}
+
+impl fmt::Display for MonthRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MonthRangeError::FromAfterTo { from, to } => {
+                write!(f, "from ({from}) が to ({to}) より未来になっています")
+            }
+            MonthRangeError::TooLong => {
+                write!(f, "期間が広すぎます（最大 {MAX_MONTHS} ヶ月まで）")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MonthRangeError {}
+
+/// 取得する月の一覧を作る
+///
+/// どちらも未指定なら今月と来月、`from` だけなら `from` とその翌月、`to` だけなら今月から `to` まで。
+/// Go版: buildMonthRange
+pub fn month_range(
+    from: Option<YearMonth>,
+    to: Option<YearMonth>,
+    current: YearMonth,
+) -> Result<Vec<YearMonth>, MonthRangeError> {
+    let (from, to) = match (from, to) {
+        (None, None) => (current, current.add_months(1)),
+        (Some(from), Some(to)) => (from, to),
+        (Some(from), None) => (from, from.add_months(1)),
+        (None, Some(to)) => (current, to),
+    };
+    if from > to {
+        return Err(MonthRangeError::FromAfterTo { from, to });
+    }
+
+    let mut months = vec![from];
+    while *months.last().unwrap() < to {
+        if months.len() == MAX_MONTHS {
+            return Err(MonthRangeError::TooLong);
+        }
+        months.push(months.last().unwrap().add_months(1));
+    }
+    Ok(months)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ym(year: i32, month: u32) -> YearMonth {
+        YearMonth::new(year, month).unwrap()
+    }
+
+    #[test]
+    fn adds_months_across_years() {
+        assert_eq!(ym(2025, 12).add_months(1), ym(2026, 1));
+        assert_eq!(ym(2026, 1).add_months(-1), ym(2025, 12));
+        assert_eq!(ym(2026, 1).add_months(25), ym(2028, 2));
+        assert_eq!(ym(2026, 1).add_months(-13), ym(2024, 12));
+        assert_eq!(YearMonth::new(2026, 13), None);
+    }
+
+    #[test]
+    fn parses_year_month() {
+        assert_eq!("2026-01".parse(), Ok(ym(2026, 1)));
+        assert_eq!("2026-1".parse(), Ok(ym(2026, 1)));
+        assert_eq!(ym(2026, 1).to_string(), "2026-01");
+        for text in [
+            "2026-13", "2026-00", "26-01", "2026-001", "2026-", "202601", "2026/01",
+        ] {
+            assert!(text.parse::<YearMonth>().is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn builds_month_ranges() {
+        let current = ym(2025, 12);
+        assert_eq!(
+            month_range(None, None, current),
+            Ok(vec![ym(2025, 12), ym(2026, 1)])
+        );
+        assert_eq!(
+            month_range(Some(ym(2026, 3)), None, current),
+            Ok(vec![ym(2026, 3), ym(2026, 4)])
+        );
+        assert_eq!(
+            month_range(None, Some(ym(2026, 2)), current),
+            Ok(vec![ym(2025, 12), ym(2026, 1), ym(2026, 2)])
+        );
+        assert_eq!(
+            month_range(Some(ym(2026, 2)), Some(ym(2026, 2)), current),
+            Ok(vec![ym(2026, 2)])
+        );
+        assert_eq!(
+            month_range(Some(ym(2026, 2)), Some(ym(2026, 1)), current),
+            Err(MonthRangeError::FromAfterTo {
+                from: ym(2026, 2),
+                to: ym(2026, 1),
+            })
+        );
+        // `to` だけで今月より前
+        assert!(month_range(None, Some(ym(2025, 11)), current).is_err());
+    }
+
+    #[test]
+    fn caps_ranges_at_twelve_months() {
+        let from = ym(2026, 1);
+        let months = month_range(Some(from), Some(ym(2026, 12)), from).unwrap();
+        assert_eq!(months.len(), MAX_MONTHS);
+        assert_eq!(months.last(), Some(&ym(2026, 12)));
+        assert_eq!(
+            month_range(Some(from), Some(ym(2027, 1)), from),
+            Err(MonthRangeError::TooLong)
+        );
+    }
+}
@@ -0,0 +1,377 @@
+use std::fmt;
+use std::sync::LazyLock;
+
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+
+use crate::month::YearMonth;
+use crate::shift::{DEFAULT_TITLE, Shift, ShiftError};
+
+static HEADER: LazyLock<Selector> = LazyLock::new(|| Selector::parse("h3.btn-block").unwrap());
+static TABLE: LazyLock<Selector> = LazyLock::new(|| Selector::parse("table#shiftTable").unwrap());
+static ROW: LazyLock<Selector> = LazyLock::new(|| Selector::parse("tr").unwrap());
+static DATE_CELL: LazyLock<Selector> = LazyLock::new(|| Selector::parse("td.shiftDate").unwrap());
+static SHOP_CELL: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("td.shiftMisName").unwrap());
+static TIME_CELL: LazyLock<Selector> = LazyLock::new(|| Selector::parse("td.shiftTime").unwrap());
+
+static YEAR_MONTH: LazyLock<[Regex; 2]> = LazyLock::new(|| {
+    [
+        Regex::new(r"(\d{4})年(\d{1,2})月").unwrap(),
+        Regex::new(r"(\d{4})-(\d{1,2})").unwrap(),
+    ]
+});
+
+/// シフトページを解析できなかった理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// `table#shiftTable` がない（ログイン切れや HTML の変更）
+    TableNotFound,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::TableNotFound => {
+                write!(f, "shiftTable が見つからんかった…HTML変わったかも")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 1ページ分の解析結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedPage {
+    pub shifts: Vec<Shift>,
+    pub report: ParseReport,
+}
+
+/// シフトにならなかった行の一覧
+///
+/// Go版・Swift版は読めない行を黙って飛ばすため、HTML が変わるとシフトが消えても気付けない。
+/// ここではスキップした行をすべて理由付きで残す。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseReport {
+    pub skipped: Vec<SkippedRow>,
+}
+
+impl ParseReport {
+    /// 休みの日などの想定内のスキップを除いた、要確認の行
+    pub fn problems(&self) -> impl Iterator<Item = &SkippedRow> {
+        self.skipped.iter().filter(|row| !row.reason.is_expected())
+    }
+
+    pub fn has_problems(&self) -> bool {
+        self.problems().next().is_some()
+    }
+}
+
+/// スキップした行（セルの生テキスト付き）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRow {
+    /// テーブル内の行番号（ヘッダ行が 0）
+    pub row: usize,
+    pub date: String,
+    pub shop: String,
+    pub time: String,
+    pub reason: SkipReason,
+}
+
+impl fmt::Display for SkippedRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}行目 {}: 日付={:?} 店舗={:?} 時間={:?}",
+            self.row, self.reason, self.date, self.shop, self.time
+        )
+    }
+}
+
+/// 行をスキップした理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// 時間欄が空、または "×" など勤務なしの印
+    NoShift,
+    /// 時間らしき文字はあるが ● がない
+    MissingMarker,
+    /// 店舗名が空
+    MissingShop,
+    /// 日付が "M/D" として読めない
+    BadDate,
+    /// 時刻が "HH:MM-HH:MM" として読めない
+    BadTime,
+    /// 開始と終了が同じ
+    ZeroLength,
+}
+
+impl SkipReason {
+    /// 正常なページでも出るスキップかどうか
+    pub fn is_expected(self) -> bool {
+        self == SkipReason::NoShift
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            SkipReason::NoShift => "勤務なし",
+            SkipReason::MissingMarker => "● がない",
+            SkipReason::MissingShop => "店舗名がない",
+            SkipReason::BadDate => "日付が読めない",
+            SkipReason::BadTime => "時刻が読めない",
+            SkipReason::ZeroLength => "開始と終了が同じ",
+        };
+        f.write_str(text)
+    }
+}
+
+/// ShiftWeb のシフトページ HTML からシフト一覧を取り出す
+///
+/// ページの年月は `h3.btn-block` の見出し（"2026年1月の確定シフト"）から取り、取れなければ今月とみなす。
+/// 日時は `tz` のローカル時刻として解釈する。シフトにならなかった行は `report` に残る。
+/// Go版: parseShifts
+pub fn parse_shifts(html: &str, tz: Tz) -> Result<ParsedPage, ParseError> {
+    parse_page(html, None, tz)
+}
+
+/// `requested` の月として取得したシフトページを解析する
+///
+/// 各行の年は、リクエストした年月と行の月から推定する（12月ページの "1/2" は翌年）。
+pub fn parse_shifts_for(
+    html: &str,
+    requested: YearMonth,
+    tz: Tz,
+) -> Result<ParsedPage, ParseError> {
+    parse_page(html, Some(requested), tz)
+}
+
+fn parse_page(html: &str, requested: Option<YearMonth>, tz: Tz) -> Result<ParsedPage, ParseError> {
+    let doc = Html::parse_document(html);
+
+    let page_month = requested
+        .or_else(|| {
+            let header = doc.select(&HEADER).next()?.text().collect::<String>();
+            let (year, month) = parse_year_month(&header)?;
+            YearMonth::new(year, month)
+        })
+        .unwrap_or_else(|| YearMonth::now(tz));
+
+    let table = doc.select(&TABLE).next().ok_or(ParseError::TableNotFound)?;
+
+    let mut page = ParsedPage {
+        shifts: Vec::new(),
+        report: ParseReport::default(),
+    };
+    // 先頭行はヘッダ
+    for (index, row) in table.select(&ROW).enumerate().skip(1) {
+        let date = cell_text(row, &DATE_CELL);
+        let shop = cell_text(row, &SHOP_CELL);
+        let time = cell_text(row, &TIME_CELL);
+
+        match parse_row(page_month, &date, &shop, &time, tz) {
+            Ok(shift) => page.shifts.push(shift),
+            Err(reason) => page.report.skipped.push(SkippedRow {
+                row: index,
+                date,
+                shop,
+                time,
+                reason,
+            }),
+        }
+    }
+
+    Ok(page)
+}
+
+/// 1行分のセルからシフトを組み立てる
+fn parse_row(
+    page_month: YearMonth,
+    date: &str,
+    shop: &str,
+    time: &str,
+    tz: Tz,
+) -> Result<Shift, SkipReason> {
+    // "●10:00-19:00" -> ("10:00", "19:00")。"×" の日などは勤務なし
+    let Some((_, time_part)) = time.split_once('●') else {
+        return Err(if time.chars().any(|c| c.is_ascii_digit()) {
+            SkipReason::MissingMarker
+        } else {
+            SkipReason::NoShift
+        });
+    };
+    if shop.is_empty() {
+        return Err(SkipReason::MissingShop);
+    }
+
+    // "1/15(木)\n未通知" -> (1, 15)
+    let date_main = date.split('\n').next().unwrap_or_default();
+    let date_main = date_main.split('(').next().unwrap_or_default();
+    let (month, day) = date_main.split_once('/').ok_or(SkipReason::BadDate)?;
+    let (Ok(month), Ok(day)) = (month.trim().parse::<u32>(), day.trim().parse::<u32>()) else {
+        return Err(SkipReason::BadDate);
+    };
+    let year = page_month.infer_year(month);
+    let date = NaiveDate::from_ymd_opt(year, month, day).ok_or(SkipReason::BadDate)?;
+
+    let (start, end) = time_part.split_once('-').ok_or(SkipReason::BadTime)?;
+    Shift::on_date(DEFAULT_TITLE, date, start, end, shop, tz).map_err(|err| match err {
+        ShiftError::EndNotAfterStart { .. } => SkipReason::ZeroLength,
+        ShiftError::InvalidClockTime(_) | ShiftError::InvalidLocalTime { .. } => {
+            SkipReason::BadTime
+        }
+    })
+}
+
+/// "2026年1月..." や "2026-01" から (年, 月) を取り出す
+/// Go版: parseYearMonth
+pub fn parse_year_month(text: &str) -> Option<(i32, u32)> {
+    YEAR_MONTH.iter().find_map(|re| {
+        let caps = re.captures(text)?;
+        Some((caps[1].parse().ok()?, caps[2].parse().ok()?))
+    })
+}
+
+/// 行内の該当セルのテキストを連結して前後の空白を落とす
+fn cell_text(row: ElementRef<'_>, selector: &Selector) -> String {
+    row.select(selector)
+        .flat_map(|cell| cell.text())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shift::DEFAULT_TZ;
+
+    const JANUARY: &str = include_str!("../tests/fixtures/shift_2026_01.html");
+    const NO_TABLE: &str = include_str!("../tests/fixtures/no_table.html");
+    const BROKEN: &str = include_str!("../tests/fixtures/shift_2026_02_broken.html");
+    const DECEMBER: &str = include_str!("../tests/fixtures/shift_2025_12.html");
+    const JANUARY_LEADING: &str = include_str!("../tests/fixtures/shift_2026_01_leading.html");
+
+    fn dates(page: &ParsedPage) -> Vec<String> {
+        page.shifts
+            .iter()
+            .map(|s| {
+                format!(
+                    "{} -> {}",
+                    s.start().format("%Y-%m-%d %H:%M"),
+                    s.end().format("%Y-%m-%d %H:%M")
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_confirmed_shifts() {
+        let shifts = parse_shifts(JANUARY, DEFAULT_TZ).unwrap().shifts;
+        let summary: Vec<String> = shifts
+            .iter()
+            .map(|s| format!("{} {} {}", s.date(), s.time_range_string(), s.location))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                "2026-01-05 10:00 - 19:00 渋谷店",
+                "2026-01-10 14:30 - 19:45 新宿店",
+                "2026-01-15 09:00 - 13:00 渋谷店",
+                "2026-01-24 22:00 - 06:00 新宿店",
+                "2026-01-31 18:00 - 01:30 新宿店",
+            ]
+        );
+        assert_eq!(shifts[4].end().to_string(), "2026-02-01 01:30:00 JST");
+        assert!(shifts.iter().all(|s| s.title == DEFAULT_TITLE));
+        assert!(shifts.iter().all(|s| s.timezone() == DEFAULT_TZ));
+    }
+
+    #[test]
+    fn reports_skipped_rows_with_reasons() {
+        let page = parse_shifts(BROKEN, DEFAULT_TZ).unwrap();
+        assert_eq!(page.shifts.len(), 1);
+        let reasons: Vec<(usize, SkipReason)> = page
+            .report
+            .skipped
+            .iter()
+            .map(|row| (row.row, row.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                (2, SkipReason::NoShift),
+                (3, SkipReason::MissingMarker),
+                (4, SkipReason::BadTime),
+                (5, SkipReason::BadDate),
+                (6, SkipReason::MissingShop),
+                (7, SkipReason::ZeroLength),
+            ]
+        );
+        assert_eq!(page.report.skipped[1].time, "10:00-19:00");
+        assert_eq!(page.report.problems().count(), 5);
+    }
+
+    #[test]
+    fn normal_page_has_no_problems_besides_zero_length() {
+        let report = parse_shifts(JANUARY, DEFAULT_TZ).unwrap().report;
+        let problems: Vec<SkipReason> = report.problems().map(|row| row.reason).collect();
+        assert_eq!(problems, [SkipReason::ZeroLength]);
+    }
+
+    #[test]
+    fn december_page_puts_january_rows_in_next_year() {
+        let requested = YearMonth::new(2025, 12).unwrap();
+        let page = parse_shifts_for(DECEMBER, requested, DEFAULT_TZ).unwrap();
+        assert_eq!(
+            dates(&page),
+            [
+                "2025-12-28 10:00 -> 2025-12-28 19:00",
+                "2025-12-31 22:00 -> 2026-01-01 06:00",
+                "2026-01-02 09:00 -> 2026-01-02 15:00",
+            ]
+        );
+        // 見出しからでも同じ結果になる
+        assert_eq!(parse_shifts(DECEMBER, DEFAULT_TZ).unwrap(), page);
+    }
+
+    #[test]
+    fn january_page_puts_december_rows_in_previous_year() {
+        let requested = YearMonth::new(2026, 1).unwrap();
+        let page = parse_shifts_for(JANUARY_LEADING, requested, DEFAULT_TZ).unwrap();
+        assert_eq!(
+            dates(&page),
+            [
+                "2025-12-29 10:00 -> 2025-12-29 19:00",
+                "2026-01-04 13:00 -> 2026-01-04 22:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn requested_month_wins_over_header() {
+        // 見出しが古いままでも、リクエストした年を使う
+        let requested = YearMonth::new(2027, 1).unwrap();
+        let page = parse_shifts_for(JANUARY_LEADING, requested, DEFAULT_TZ).unwrap();
+        assert_eq!(page.shifts[0].date().to_string(), "2026-12-29");
+        assert_eq!(page.shifts[1].date().to_string(), "2027-01-04");
+    }
+
+    #[test]
+    fn missing_table_is_an_error() {
+        assert_eq!(
+            parse_shifts(NO_TABLE, DEFAULT_TZ).unwrap_err(),
+            ParseError::TableNotFound
+        );
+    }
+
+    #[test]
+    fn year_month_from_header() {
+        assert_eq!(parse_year_month("2025年11月の確定シフト"), Some((2025, 11)));
+        assert_eq!(parse_year_month("2026-01"), Some((2026, 1)));
+        assert_eq!(parse_year_month("確定シフト"), None);
+    }
+}
@@ -0,0 +1,195 @@
+//! シフト開始前の通知
+//!
+//! Go版・Swift版は通知を付けず、カレンダーアプリの既定の通知に任せている。
+//! ここでは「60分前と15分前」のような通知を全体と勤務先（場所）ごとに決めておき、
+//! iCalendar では VALARM、Google カレンダーでは `reminders.overrides` として書き込む。
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::shift::Shift;
+
+/// 通知できるいちばん前の時刻（Google カレンダーの上限と同じ4週間）
+pub const MAX_MINUTES: u32 = 40320;
+
+/// シフトに付ける通知（開始の何分前か）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reminders {
+    /// 場所の指定がないシフトの通知
+    pub default: Vec<u32>,
+    /// 場所（LOCATION）ごとの通知。ここにある場所は `default` の代わりにこちらを使う
+    pub by_location: Vec<(String, Vec<u32>)>,
+}
+
+impl Reminders {
+    /// すべてのシフトに `minutes` 分前の通知を付ける
+    pub fn new(minutes: &[u32]) -> Self {
+        Reminders {
+            default: normalize(minutes),
+            by_location: Vec::new(),
+        }
+    }
+
+    /// 場所が `location` のシフトだけ通知を `minutes` にする（空なら通知なし）
+    pub fn at(mut self, location: impl Into<String>, minutes: &[u32]) -> Self {
+        self.set(ReminderSpec {
+            location: Some(location.into()),
+            minutes: normalize(minutes),
+        });
+        self
+    }
+
+    /// 指定を反映する（同じ場所・全体の指定は後のもので置き換える）
+    pub fn set(&mut self, spec: ReminderSpec) {
+        match spec.location {
+            None => self.default = spec.minutes,
+            Some(location) => match self.by_location.iter_mut().find(|(l, _)| *l == location) {
+                Some((_, minutes)) => *minutes = spec.minutes,
+                None => self.by_location.push((location, spec.minutes)),
+            },
+        }
+    }
+
+    /// 通知の指定が1つもない（カレンダーの既定の通知に任せる）
+    pub fn is_empty(&self) -> bool {
+        self.default.is_empty() && self.by_location.is_empty()
+    }
+
+    /// `shift` に付ける通知（早い順）
+    pub fn for_shift(&self, shift: &Shift) -> &[u32] {
+        self.overrides(shift).unwrap_or_default()
+    }
+
+    /// `shift` の通知が指定されていればその通知（空なら「通知なし」の指定）
+    ///
+    /// `None` ならカレンダーの既定の通知のままにする。
+    pub fn overrides(&self, shift: &Shift) -> Option<&[u32]> {
+        self.by_location
+            .iter()
+            .find(|(location, _)| *location == shift.location)
+            .map(|(_, minutes)| minutes.as_slice())
+            .or(Some(self.default.as_slice()).filter(|m| !m.is_empty()))
+    }
+}
+
+/// 通知の指定（`-remind` の値）
+///
+/// `60,15` なら全体、`渋谷店=60,15` なら場所が「渋谷店」のシフトだけ。
+/// `渋谷店=` のように分を書かなければ、その場所のシフトには通知を付けない。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReminderSpec {
+    pub location: Option<String>,
+    /// 開始の何分前か（早い順・重複なし）
+    pub minutes: Vec<u32>,
+}
+
+/// 通知の指定が読めない
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseReminderError(String);
+
+impl fmt::Display for ParseReminderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "通知 {:?} が読めない（例: 60,15 / 渋谷店=30、分は0〜{MAX_MINUTES}）",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseReminderError {}
+
+impl FromStr for ReminderSpec {
+    type Err = ParseReminderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseReminderError(s.to_string());
+        let (location, list) = match s.rsplit_once('=') {
+            Some((location, list)) if !location.trim().is_empty() => {
+                (Some(location.trim().to_string()), list)
+            }
+            Some(_) => return Err(invalid()),
+            None => (None, s),
+        };
+        let minutes = list
+            .split(',')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(|m| m.parse::<u32>().ok().filter(|m| *m <= MAX_MINUTES))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+        if location.is_none() && minutes.is_empty() {
+            return Err(invalid());
+        }
+        Ok(ReminderSpec {
+            location,
+            minutes: normalize(&minutes),
+        })
+    }
+}
+
+/// 早い順（分の大きい順）に並べ、重複を除く
+fn normalize(minutes: &[u32]) -> Vec<u32> {
+    let mut minutes = minutes.to_vec();
+    minutes.sort_unstable_by(|a, b| b.cmp(a));
+    minutes.dedup();
+    minutes
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::shift::{DEFAULT_TITLE, DEFAULT_TZ};
+
+    #[test]
+    fn parses_reminder_specs() {
+        assert_eq!(
+            "15, 60".parse(),
+            Ok(ReminderSpec {
+                location: None,
+                minutes: vec![60, 15],
+            })
+        );
+        assert_eq!(
+            "マック 渋谷=30".parse(),
+            Ok(ReminderSpec {
+                location: Some("マック 渋谷".to_string()),
+                minutes: vec![30],
+            })
+        );
+        assert_eq!(
+            "渋谷店=".parse(),
+            Ok(ReminderSpec {
+                location: Some("渋谷店".to_string()),
+                minutes: Vec::new(),
+            })
+        );
+        assert!("".parse::<ReminderSpec>().is_err());
+        assert!("=60".parse::<ReminderSpec>().is_err());
+        assert!("1時間".parse::<ReminderSpec>().is_err());
+        assert!("50000".parse::<ReminderSpec>().is_err());
+    }
+
+    #[test]
+    fn location_overrides_default() {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let shift = |location: &str| {
+            Shift::on_date(DEFAULT_TITLE, day, "10:00", "19:00", location, DEFAULT_TZ).unwrap()
+        };
+        let reminders = Reminders::new(&[15, 60])
+            .at("渋谷店", &[30])
+            .at("新宿店", &[]);
+        assert_eq!(reminders.for_shift(&shift("")), [60, 15]);
+        assert_eq!(reminders.for_shift(&shift("渋谷店")), [30]);
+        assert!(reminders.for_shift(&shift("新宿店")).is_empty());
+        assert_eq!(reminders.overrides(&shift("新宿店")), Some(&[][..]));
+        assert!(Reminders::default().is_empty());
+
+        // 場所だけの指定なら、ほかのシフトは既定の通知のまま
+        let only_shibuya = Reminders::default().at("渋谷店", &[30]);
+        assert_eq!(only_shibuya.overrides(&shift("")), None);
+        assert!(only_shibuya.for_shift(&shift("")).is_empty());
+    }
+}
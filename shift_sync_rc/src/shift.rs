@@ -1,6 +1,423 @@
+use std::fmt;
+
+use chrono::{
+    DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Weekday,
+};
+use chrono_tz::Tz;
+use sha1::{Digest, Sha1};
+
+/// タイムゾーン未指定時に使うゾーン（ShiftWeb は日本のサイトなので東京）
+pub const DEFAULT_TZ: Tz = chrono_tz::Asia::Tokyo;
+
+/// Go版・Swift版と同じデフォルトのイベント名
+pub const DEFAULT_TITLE: &str = "バイト";
+
+/// 1件分のシフト
+///
+/// `start` / `end` はタイムゾーン付きの日時で、常に `start < end` を満たす。
+/// 不変条件を守るため、日時はコンストラクタ経由でしか設定できない。
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Shift {
     pub title: String,
-    pub start: String,
-    pub end: String,
+    start: DateTime<Tz>,
+    end: DateTime<Tz>,
     pub location: String,
+    pub memo: String,
+}
+
+/// シフトを組み立てられなかった理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShiftError {
+    /// 終了が開始と同じか、開始より前
+    EndNotAfterStart {
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    },
+    /// 指定ゾーンで存在しない・一意に決まらないローカル時刻（夏時間の切り替わりなど）
+    InvalidLocalTime { datetime: NaiveDateTime, tz: Tz },
+    /// "HH:MM" として読めない時刻
+    InvalidClockTime(String),
+}
+
+impl fmt::Display for ShiftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShiftError::EndNotAfterStart { start, end } => {
+                write!(
+                    f,
+                    "終了時刻 {end} が開始時刻 {start} より後になっていません"
+                )
+            }
+            ShiftError::InvalidLocalTime { datetime, tz } => {
+                write!(f, "{datetime} は {tz} で一意に決まらない時刻です")
+            }
+            ShiftError::InvalidClockTime(text) => write!(f, "時刻 {text:?} が読めません"),
+        }
+    }
+}
+
+impl std::error::Error for ShiftError {}
+
+impl Shift {
+    /// タイムゾーン付きの開始・終了からシフトを作る
+    ///
+    /// 終了は `start` のゾーンに揃えて保持する。
+    pub fn new(
+        title: impl Into<String>,
+        start: DateTime<Tz>,
+        end: DateTime<Tz>,
+        location: impl Into<String>,
+    ) -> Result<Self, ShiftError> {
+        if end <= start {
+            return Err(ShiftError::EndNotAfterStart {
+                start: start.naive_local(),
+                end: end.with_timezone(&start.timezone()).naive_local(),
+            });
+        }
+        let end = end.with_timezone(&start.timezone());
+        Ok(Shift {
+            title: title.into(),
+            start,
+            end,
+            location: location.into(),
+            memo: String::new(),
+        })
+    }
+
+    /// ゾーンなしのローカル日時を `tz` で解釈してシフトを作る
+    pub fn from_local(
+        title: impl Into<String>,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        location: impl Into<String>,
+        tz: Tz,
+    ) -> Result<Self, ShiftError> {
+        let start = localize(start, tz)?;
+        let end = localize(end, tz)?;
+        Shift::new(title, start, end, location)
+    }
+
+    /// 勤務日と "HH:MM" の開始・終了からシフトを作る
+    ///
+    /// "25:30" のような 24:00 以降の表記は翌日の時刻として扱う。
+    /// 終了が開始より前（"22:00-06:00" など）なら、終了を翌日に繰り越す。
+    pub fn on_date(
+        title: impl Into<String>,
+        date: NaiveDate,
+        start: &str,
+        end: &str,
+        location: impl Into<String>,
+        tz: Tz,
+    ) -> Result<Self, ShiftError> {
+        let start = date.and_time(NaiveTime::MIN) + parse_clock_time(start)?;
+        let mut end = date.and_time(NaiveTime::MIN) + parse_clock_time(end)?;
+        if end < start {
+            end += TimeDelta::days(1);
+        }
+        Shift::from_local(title, start, end, location, tz)
+    }
+
+    /// メモ（DESCRIPTION）を付ける
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = memo.into();
+        self
+    }
+
+    pub fn start(&self) -> DateTime<Tz> {
+        self.start
+    }
+
+    pub fn end(&self) -> DateTime<Tz> {
+        self.end
+    }
+
+    pub fn timezone(&self) -> Tz {
+        self.start.timezone()
+    }
+
+    /// 勤務時間の長さ
+    pub fn duration(&self) -> TimeDelta {
+        self.end - self.start
+    }
+
+    /// 開始日（シフトのゾーンでの日付）
+    pub fn date(&self) -> NaiveDate {
+        self.start.date_naive()
+    }
+
+    /// 開始日の曜日
+    pub fn weekday(&self) -> Weekday {
+        self.start.weekday()
+    }
+
+    /// "1/15" 形式の日付
+    /// Swift版: Shift.dateString
+    pub fn date_string(&self) -> String {
+        format!("{}/{}", self.start.month(), self.start.day())
+    }
+
+    /// "水" のような日本語の曜日
+    /// Swift版: Shift.dayOfWeek
+    pub fn day_of_week(&self) -> &'static str {
+        weekday_ja(self.weekday())
+    }
+
+    /// "10:00 - 19:00" 形式の時間帯
+    /// Swift版: Shift.timeRangeString
+    pub fn time_range_string(&self) -> String {
+        format!(
+            "{} - {}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+
+    /// 開始・終了・場所から決まる UID（再同期で上書きできるよう決定的）
+    ///
+    /// `shift-YYYYMMDD-HHMM-HHMM-<SHA1先頭8桁>` の形式で、Go版・Swift版と同じ値になる。
+    /// Go版: makeShiftUID / Swift版: Shift.makeUID
+    pub fn uid(&self) -> String {
+        let key = format!(
+            "{}-{}-{}",
+            self.start.format("%Y%m%dT%H%M"),
+            self.end.format("%Y%m%dT%H%M"),
+            self.location
+        );
+        let digest = Sha1::digest(key.as_bytes());
+        let hash: String = digest[..4].iter().map(|b| format!("{b:02x}")).collect();
+        format!(
+            "shift-{}-{}-{}-{}",
+            self.start.format("%Y%m%d"),
+            self.start.format("%H%M"),
+            self.end.format("%H%M"),
+            hash
+        )
+    }
+}
+
+/// UID（`shift-YYYYMMDD-...`）からシフトの開始日を取り出す
+///
+/// このツールが作った UID でなければ `None`。
+pub fn uid_date(uid: &str) -> Option<NaiveDate> {
+    let date = uid.strip_prefix("shift-")?.get(..8)?;
+    NaiveDate::parse_from_str(date, "%Y%m%d").ok()
+}
+
+/// 曜日の日本語1文字表記
+pub fn weekday_ja(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "月",
+        Weekday::Tue => "火",
+        Weekday::Wed => "水",
+        Weekday::Thu => "木",
+        Weekday::Fri => "金",
+        Weekday::Sat => "土",
+        Weekday::Sun => "日",
+    }
+}
+
+/// "HH:MM" を勤務日の 0:00 からの経過時間に変換する（"25:30" のような表記も可）
+///
+/// 時は1〜2桁、分は2桁の数字だけ受け付ける（"+5:00" や "-0:30" は不可）。
+pub fn parse_clock_time(text: &str) -> Result<TimeDelta, ShiftError> {
+    let invalid = || ShiftError::InvalidClockTime(text.to_string());
+    let (hour, minute) = text.trim().split_once(':').ok_or_else(invalid)?;
+    let digits = |s: &str, len: std::ops::RangeInclusive<usize>| {
+        (len.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| s.parse::<i64>().ok())
+            .flatten()
+    };
+    let hour = digits(hour, 1..=2).ok_or_else(invalid)?;
+    let minute = digits(minute, 2..=2).ok_or_else(invalid)?;
+    if !(0..48).contains(&hour) || !(0..60).contains(&minute) {
+        return Err(invalid());
+    }
+    Ok(TimeDelta::hours(hour) + TimeDelta::minutes(minute))
+}
+
+fn localize(datetime: NaiveDateTime, tz: Tz) -> Result<DateTime<Tz>, ShiftError> {
+    tz.from_local_datetime(&datetime)
+        .single()
+        .ok_or(ShiftError::InvalidLocalTime { datetime, tz })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn shift(start: &str, end: &str, location: &str) -> Shift {
+        Shift::from_local(
+            DEFAULT_TITLE,
+            local(start),
+            local(end),
+            location,
+            DEFAULT_TZ,
+        )
+        .unwrap()
+    }
+
+    // Go版 makeShiftUID の出力と突き合わせた値
+    #[test]
+    fn uid_matches_go_and_swift() {
+        assert_eq!(
+            shift("2026-01-15 10:00", "2026-01-15 19:00", "渋谷店").uid(),
+            "shift-20260115-1000-1900-66605f51"
+        );
+        assert_eq!(
+            shift("2025-12-31 22:00", "2026-01-01 06:00", "新宿店").uid(),
+            "shift-20251231-2200-0600-8882e753"
+        );
+        assert_eq!(
+            shift("2026-02-01 09:00", "2026-02-01 18:00", "").uid(),
+            "shift-20260201-0900-1800-a7e4d64e"
+        );
+    }
+
+    #[test]
+    fn uid_ignores_title_and_memo() {
+        let a = shift("2026-01-15 10:00", "2026-01-15 19:00", "渋谷店");
+        let mut b = a.clone().with_memo("早番");
+        b.title = "シフト".to_string();
+        assert_eq!(a.uid(), b.uid());
+    }
+
+    #[test]
+    fn uid_date_reads_start_date() {
+        let s = shift("2025-12-31 22:00", "2026-01-01 06:00", "新宿店");
+        assert_eq!(uid_date(&s.uid()), NaiveDate::from_ymd_opt(2025, 12, 31));
+        assert_eq!(uid_date("shift-2026"), None);
+        assert_eq!(uid_date("event-20260115-1000-1900-66605f51"), None);
+    }
+
+    #[test]
+    fn rejects_end_not_after_start() {
+        let err = Shift::from_local(
+            DEFAULT_TITLE,
+            local("2026-01-15 10:00"),
+            local("2026-01-15 10:00"),
+            "渋谷店",
+            DEFAULT_TZ,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ShiftError::EndNotAfterStart { .. }));
+
+        let start = DEFAULT_TZ
+            .from_local_datetime(&local("2026-01-15 19:00"))
+            .unwrap();
+        let end = DEFAULT_TZ
+            .from_local_datetime(&local("2026-01-15 10:00"))
+            .unwrap();
+        assert_eq!(
+            Shift::new(DEFAULT_TITLE, start, end, "渋谷店"),
+            Err(ShiftError::EndNotAfterStart {
+                start: local("2026-01-15 19:00"),
+                end: local("2026-01-15 10:00"),
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_end_in_start_timezone() {
+        let start = DEFAULT_TZ
+            .from_local_datetime(&local("2026-01-15 10:00"))
+            .unwrap();
+        let end = chrono::Utc
+            .from_local_datetime(&local("2026-01-15 10:00"))
+            .unwrap()
+            .with_timezone(&chrono_tz::UTC);
+        let shift = Shift::new(DEFAULT_TITLE, start, end, "渋谷店").unwrap();
+        assert_eq!(shift.timezone(), DEFAULT_TZ);
+        assert_eq!(shift.end().naive_local(), local("2026-01-15 19:00"));
+        assert_eq!(shift.duration(), TimeDelta::hours(9));
+    }
+
+    // Swift版 Shift の dateString / dayOfWeek / timeRangeString と同じ出力
+    #[test]
+    fn helpers_match_swift() {
+        let shift = Shift::from_local(
+            DEFAULT_TITLE,
+            local("2026-01-07 09:05"),
+            local("2026-01-07 18:30"),
+            "渋谷店",
+            DEFAULT_TZ,
+        )
+        .unwrap()
+        .with_memo("早番");
+        assert_eq!(shift.date_string(), "1/7");
+        assert_eq!(shift.day_of_week(), "水");
+        assert_eq!(shift.time_range_string(), "09:05 - 18:30");
+        assert_eq!(shift.duration(), TimeDelta::minutes(565));
+        assert_eq!(shift.date(), NaiveDate::from_ymd_opt(2026, 1, 7).unwrap());
+        assert_eq!(shift.memo, "早番");
+
+        let days: Vec<&str> = (0..7)
+            .map(|i| weekday_ja(Weekday::try_from(i).unwrap()))
+            .collect();
+        assert_eq!(days, ["月", "火", "水", "木", "金", "土", "日"]);
+    }
+
+    #[test]
+    fn rejects_nonexistent_local_time() {
+        let gap = local("2026-03-08 02:30");
+        let err = Shift::from_local(
+            DEFAULT_TITLE,
+            gap,
+            local("2026-03-08 10:00"),
+            "",
+            chrono_tz::America::New_York,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ShiftError::InvalidLocalTime { datetime, .. } if datetime == gap));
+    }
+
+    #[test]
+    fn overnight_end_rolls_into_next_day() {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+        let s = Shift::on_date(DEFAULT_TITLE, day, "22:00", "06:00", "渋谷店", DEFAULT_TZ).unwrap();
+        assert_eq!(s.end().naive_local(), local("2026-02-01 06:00"));
+        assert_eq!(s.duration(), TimeDelta::hours(8));
+        assert_eq!(s.date(), day);
+    }
+
+    #[test]
+    fn accepts_past_midnight_notation() {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let s = Shift::on_date(DEFAULT_TITLE, day, "18:00", "25:30", "渋谷店", DEFAULT_TZ).unwrap();
+        assert_eq!(s.end().naive_local(), local("2026-01-11 01:30"));
+        assert_eq!(s.time_range_string(), "18:00 - 01:30");
+
+        let late =
+            Shift::on_date(DEFAULT_TITLE, day, "24:30", "27:00", "渋谷店", DEFAULT_TZ).unwrap();
+        assert_eq!(late.start().naive_local(), local("2026-01-11 00:30"));
+    }
+
+    #[test]
+    fn same_clock_time_is_zero_length() {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let err = Shift::on_date(DEFAULT_TITLE, day, "12:00", "12:00", "渋谷店", DEFAULT_TZ);
+        assert!(matches!(err, Err(ShiftError::EndNotAfterStart { .. })));
+        assert!(matches!(
+            parse_clock_time("10時"),
+            Err(ShiftError::InvalidClockTime(_))
+        ));
+    }
+
+    #[test]
+    fn clock_time_is_digits_only() {
+        assert_eq!(parse_clock_time("9:05"), Ok(TimeDelta::minutes(545)));
+        assert_eq!(parse_clock_time(" 25:30 "), Ok(TimeDelta::minutes(1530)));
+        for text in [
+            "+5:00", "-0:30", "5:+0", "5:0", "005:00", "5:000", "５:00", "5 :00",
+        ] {
+            assert_eq!(
+                parse_clock_time(text),
+                Err(ShiftError::InvalidClockTime(text.to_string())),
+                "{text}"
+            );
+        }
+    }
 }
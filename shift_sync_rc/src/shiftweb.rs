@@ -0,0 +1,159 @@
+use std::fmt;
+use std::time::Duration;
+
+use ureq::Agent;
+use url::form_urlencoded;
+
+use crate::month::YearMonth;
+
+/// 本番の ShiftWeb
+pub const DEFAULT_BASE_URL: &str = "https://example-shift.com";
+
+const LOGIN_PAGE_PATH: &str = "/login.php";
+const LOGIN_API_PATH: &str = "/cont/login/check_login.php";
+const SHIFT_PATH: &str = "/shift.php";
+
+/// ShiftWeb とのやりとりで起きたエラー
+#[derive(Debug)]
+pub enum ShiftWebError {
+    /// 接続できない・応答が読めないなど
+    Http {
+        context: &'static str,
+        source: ureq::Error,
+    },
+    /// 4xx / 5xx が返ってきた
+    Status {
+        context: String,
+        status: u16,
+        body: String,
+    },
+}
+
+impl fmt::Display for ShiftWebError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShiftWebError::Http { context, source } => write!(f, "{context}: {source}"),
+            ShiftWebError::Status {
+                context,
+                status,
+                body,
+            } => write!(f, "{context} status={status} body={body}"),
+        }
+    }
+}
+
+impl std::error::Error for ShiftWebError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShiftWebError::Http { source, .. } => Some(source),
+            ShiftWebError::Status { .. } => None,
+        }
+    }
+}
+
+/// ShiftWeb のクライアント（Cookie でセッションを保持する）
+pub struct ShiftWebClient {
+    agent: Agent,
+    base_url: String,
+}
+
+impl ShiftWebClient {
+    /// `base_url` の ShiftWeb に接続するクライアントを作る（末尾の `/` は不要）
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let agent = Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(30)))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        ShiftWebClient {
+            agent,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// ログインしてセッション Cookie を受け取る
+    ///
+    /// ログインページを一度開いて Cookie を用意してから、ブラウザと同じヘッダでログイン API を叩く。
+    /// Go版: loginShiftWeb
+    pub fn login(&self, id: &str, password: &str) -> Result<(), ShiftWebError> {
+        let login_page = format!("{}{LOGIN_PAGE_PATH}?err=1", self.base_url);
+        self.agent
+            .get(&login_page)
+            .call()
+            .map_err(|source| ShiftWebError::Http {
+                context: "ログインページ取得失敗",
+                source,
+            })?;
+
+        let body: String = form_urlencoded::Serializer::new(String::new())
+            .append_pair("id", id)
+            .append_pair("password", password)
+            .append_pair("savelogin", "1")
+            .finish();
+        let login_api = format!(
+            "{}{LOGIN_API_PATH}?{}",
+            self.base_url,
+            form_urlencoded::byte_serialize(id.as_bytes()).collect::<String>()
+        );
+        let mut resp = self
+            .agent
+            .post(&login_api)
+            .header(
+                "Content-Type",
+                "application/x-www-form-urlencoded; charset=UTF-8",
+            )
+            .header("X-Requested-With", "XMLHttpRequest")
+            .header("Origin", &self.base_url)
+            .header("Referer", &login_page)
+            .send(body)
+            .map_err(|source| ShiftWebError::Http {
+                context: "ログインAPI失敗",
+                source,
+            })?;
+
+        let status = resp.status().as_u16();
+        if status >= 400 {
+            return Err(ShiftWebError::Status {
+                context: "ログインAPI".to_string(),
+                status,
+                body: resp.body_mut().read_to_string().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+
+    /// 指定した月のシフトページ HTML を取得する
+    /// Go版: fetchShiftPageForMonth
+    pub fn fetch_month(&self, month: YearMonth) -> Result<String, ShiftWebError> {
+        let shift_url = format!("{}{SHIFT_PATH}", self.base_url);
+        let mut resp = self
+            .agent
+            .get(&shift_url)
+            .query("mod", "look")
+            .query("date2", month.to_string())
+            .header("Referer", &shift_url)
+            .call()
+            .map_err(|source| ShiftWebError::Http {
+                context: "シフトページ取得失敗",
+                source,
+            })?;
+
+        let status = resp.status().as_u16();
+        let body = resp.body_mut().read_to_string();
+        if status >= 400 {
+            return Err(ShiftWebError::Status {
+                context: format!("shift ({month})"),
+                status,
+                body: body.unwrap_or_default(),
+            });
+        }
+        body.map_err(|source| ShiftWebError::Http {
+            context: "シフトページ読み込み失敗",
+            source,
+        })
+    }
+}
@@ -0,0 +1,235 @@
+//! ローカルの CSV ファイル
+//!
+//! ShiftWeb を使わない職場のシフトを手で書いておくためのもの。1行目は見出しで、列の順番は自由。
+//!
+//! ```text
+//! date,start,end,location,title,memo
+//! 2026-01-15,17:00,22:00,マック渋谷,,
+//! 2026/1/16,22:00,06:00,マック渋谷,,夜勤
+//! ```
+//!
+//! `date`・`start`・`end` は必須、ほかは省略できる（日本語の見出し「日付」「開始」なども可）。
+//! 時刻は ShiftWeb と同じく "25:30" のような表記や、日をまたぐ "22:00"〜"06:00" を受け付ける。
+//! `#` で始まる行は読み飛ばす。
+//! 読めない行は取得した月のもの（日付も読めないものを含む）だけを報告し、残りの行は読み続ける。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+
+use crate::month::YearMonth;
+use crate::shift::{DEFAULT_TITLE, Shift};
+use crate::source::{ShiftSource, SourceError, SourceReport, SourceShifts};
+
+/// 列の見出し（英語・日本語）
+const DATE_HEADERS: [&str; 2] = ["date", "日付"];
+const START_HEADERS: [&str; 2] = ["start", "開始"];
+const END_HEADERS: [&str; 2] = ["end", "終了"];
+const LOCATION_HEADERS: [&str; 3] = ["location", "場所", "店舗"];
+const TITLE_HEADERS: [&str; 2] = ["title", "タイトル"];
+const MEMO_HEADERS: [&str; 2] = ["memo", "メモ"];
+
+/// CSV ファイルの取得元
+pub struct CsvSource {
+    path: PathBuf,
+    tz: Tz,
+}
+
+impl CsvSource {
+    /// `path` の CSV を読み、時刻は `tz` のローカル時刻として解釈する
+    pub fn new(path: impl Into<PathBuf>, tz: Tz) -> Self {
+        CsvSource {
+            path: path.into(),
+            tz,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl ShiftSource for CsvSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn fetch(&mut self, months: &[YearMonth]) -> Result<SourceShifts, SourceError> {
+        let text = fs::read_to_string(&self.path).map_err(|source| SourceError::Io {
+            path: self.path.clone(),
+            source,
+        })?;
+        let parsed = parse_csv(&text, self.tz).map_err(|(line, message)| SourceError::Csv {
+            path: self.path.clone(),
+            line,
+            message,
+        })?;
+        let in_months = |date| months.contains(&YearMonth::of(date));
+        let mut fetched = SourceShifts {
+            shifts: parsed
+                .shifts
+                .into_iter()
+                .filter(|s| in_months(s.date()))
+                .collect(),
+            reports: Vec::new(),
+        };
+        let skipped: Vec<_> = parsed
+            .skipped
+            .into_iter()
+            .filter(|row| row.date.is_none_or(in_months))
+            .map(|row| (row.line, row.message))
+            .collect();
+        if !skipped.is_empty() {
+            fetched.reports.push(SourceReport::Rows {
+                path: self.path.clone(),
+                skipped,
+            });
+        }
+        Ok(fetched)
+    }
+}
+
+/// CSV を読んだ結果
+#[derive(Debug, Default)]
+struct ParsedCsv {
+    shifts: Vec<Shift>,
+    skipped: Vec<BadRow>,
+}
+
+/// シフトにできなかった行
+#[derive(Debug)]
+struct BadRow {
+    /// 1始まり
+    line: u64,
+    /// 日付の列だけは読めたときの日付
+    date: Option<NaiveDate>,
+    message: String,
+}
+
+/// CSV の本文をシフトにする
+///
+/// 見出しが読めないときだけ失敗する（行番号と理由）。読めない行は `skipped` に入れて読み続ける。
+fn parse_csv(text: &str, tz: Tz) -> Result<ParsedCsv, (u64, String)> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(text.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|err| (1, err.to_string()))?
+        .clone();
+    let column = |names: &[&str]| {
+        headers
+            .iter()
+            .position(|h| names.iter().any(|n| h.eq_ignore_ascii_case(n)))
+    };
+    let required =
+        |names: &[&str]| column(names).ok_or_else(|| (1, format!("{} 列がない", names[0])));
+    let date_col = required(&DATE_HEADERS)?;
+    let start_col = required(&START_HEADERS)?;
+    let end_col = required(&END_HEADERS)?;
+    let location_col = column(&LOCATION_HEADERS);
+    let title_col = column(&TITLE_HEADERS);
+    let memo_col = column(&MEMO_HEADERS);
+
+    let mut parsed = ParsedCsv::default();
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => {
+                parsed.skipped.push(BadRow {
+                    line: err.position().map_or(0, |p| p.line()),
+                    date: None,
+                    message: err.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |p| p.line());
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).unwrap_or_default();
+
+        let date_text = field(Some(date_col));
+        let Some(date) = parse_date(date_text) else {
+            parsed.skipped.push(BadRow {
+                line,
+                date: None,
+                message: format!("日付 {date_text:?}"),
+            });
+            continue;
+        };
+        let title = Some(field(title_col))
+            .filter(|t| !t.is_empty())
+            .unwrap_or(DEFAULT_TITLE);
+        match Shift::on_date(
+            title,
+            date,
+            field(Some(start_col)),
+            field(Some(end_col)),
+            field(location_col),
+            tz,
+        ) {
+            Ok(shift) => parsed.shifts.push(shift.with_memo(field(memo_col))),
+            Err(err) => parsed.skipped.push(BadRow {
+                line,
+                date: Some(date),
+                message: err.to_string(),
+            }),
+        }
+    }
+    Ok(parsed)
+}
+
+/// "2026-01-15" / "2026/1/15"
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(text, "%Y/%m/%d"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shift::DEFAULT_TZ;
+
+    #[test]
+    fn reads_columns_in_any_order() {
+        let text = "# 2つ目のバイト\n\
+                    開始,終了,日付,メモ,店舗\n\
+                    17:00,22:00,2026-01-15,,\"マック, 渋谷\"\n\
+                    22:00,06:00,2026/1/31,夜勤,マック渋谷\n";
+        let shifts = parse_csv(text, DEFAULT_TZ).unwrap().shifts;
+        assert_eq!(shifts.len(), 2);
+        assert_eq!(shifts[0].title, DEFAULT_TITLE);
+        assert_eq!(shifts[0].location, "マック, 渋谷");
+        assert_eq!(shifts[0].time_range_string(), "17:00 - 22:00");
+        assert_eq!(shifts[1].memo, "夜勤");
+        assert_eq!(
+            shifts[1].end().date_naive(),
+            NaiveDate::from_ymd_opt(2026, 2, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn reports_line_of_bad_rows() {
+        let text = "date,start,end\n2026-01-15,10:00,19:00\n2026-01-16,10時,19:00\n\
+                    1/17,10:00,19:00\n2026-01-18,10:00,19:00\n";
+        let parsed = parse_csv(text, DEFAULT_TZ).unwrap();
+        assert_eq!(parsed.shifts.len(), 2);
+        let [bad_time, bad_date] = parsed.skipped.as_slice() else {
+            panic!("{:?}", parsed.skipped);
+        };
+        assert_eq!(
+            (bad_time.line, bad_time.date),
+            (3, NaiveDate::from_ymd_opt(2026, 1, 16))
+        );
+        assert!(bad_time.message.contains("10時"), "{}", bad_time.message);
+        assert_eq!((bad_date.line, bad_date.date), (4, None));
+
+        let (line, _) = parse_csv("date,end\n", DEFAULT_TZ).unwrap_err();
+        assert_eq!(line, 1);
+    }
+}
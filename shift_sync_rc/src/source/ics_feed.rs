@@ -0,0 +1,108 @@
+//! iCalendar の URL（公開カレンダー・シフト管理アプリの購読 URL など）
+//!
+//! VEVENT の読み方は `ics::read_shifts` と同じで、終日の予定や取り消された予定は飛ばす。
+//! 読めない予定は取得した月のものだけを報告し、残りの予定は読み続ける
+//! （勤務先のカレンダーには何年も前の目印やメモの予定が混ざっていることがある）。
+//! 繰り返しの予定（RRULE）は回ごとに展開しないので読まず、取得した月に回がありそうなものを報告する
+//! （取得した月より後に始まるもの、UNTIL・RDATE の最後の回が取得した月より前のものは報告しない）。
+//! UID は元の予定のものではなく、ほかの取得元と同じく日時と場所から決まる。
+
+use std::time::Duration;
+
+use chrono_tz::Tz;
+use ureq::Agent;
+
+use crate::ics::{IcsError, IcsEvent, read_events};
+use crate::month::YearMonth;
+use crate::source::{ShiftSource, SourceError, SourceReport, SourceShifts};
+
+/// iCalendar の URL の取得元
+pub struct IcsFeedSource {
+    agent: Agent,
+    url: String,
+    tz: Tz,
+}
+
+impl IcsFeedSource {
+    /// `url` のカレンダーを読み、ゾーンのない時刻は `tz` として解釈する
+    ///
+    /// `webcal://` は `https://` に読み替える。
+    pub fn new(url: &str, tz: Tz) -> Self {
+        let agent = Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(30)))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        let url = match url.strip_prefix("webcal://") {
+            Some(rest) => format!("https://{rest}"),
+            None => url.to_string(),
+        };
+        IcsFeedSource { agent, url, tz }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn download(&self) -> Result<String, SourceError> {
+        let http = |source| SourceError::Http {
+            url: self.url.clone(),
+            source,
+        };
+        let mut resp = self.agent.get(&self.url).call().map_err(http)?;
+        let status = resp.status().as_u16();
+        if !(200..300).contains(&status) {
+            return Err(SourceError::Status {
+                url: self.url.clone(),
+                status,
+            });
+        }
+        resp.body_mut().read_to_string().map_err(http)
+    }
+}
+
+impl ShiftSource for IcsFeedSource {
+    fn name(&self) -> String {
+        self.url.clone()
+    }
+
+    fn fetch(&mut self, months: &[YearMonth]) -> Result<SourceShifts, SourceError> {
+        let body = self.download()?;
+        let in_months = |date| months.contains(&YearMonth::of(date));
+        let first = months.iter().min().map(|m| m.first_day());
+        let after_last = months.iter().max().map(|m| m.add_months(1).first_day());
+        let may_recur_in_months = |event: &IcsEvent| {
+            event
+                .start_date()
+                .is_none_or(|start| after_last.is_none_or(|end| start < end))
+                && event
+                    .recurrence_end()
+                    .is_none_or(|until| first.is_none_or(|first| until >= first))
+        };
+        let mut fetched = SourceShifts::default();
+        let mut skipped = Vec::new();
+        for event in read_events(&body) {
+            match event.to_shift(self.tz) {
+                Ok(Some(shift)) if in_months(shift.date()) => fetched.shifts.push(shift),
+                Ok(_) => {}
+                Err(err @ IcsError::Recurring { .. }) => {
+                    if may_recur_in_months(&event) {
+                        skipped.push(err);
+                    }
+                }
+                Err(err) => {
+                    if event.start_date().is_none_or(in_months) {
+                        skipped.push(err);
+                    }
+                }
+            }
+        }
+        if !skipped.is_empty() {
+            fetched.reports.push(SourceReport::Events {
+                url: self.url.clone(),
+                skipped,
+            });
+        }
+        Ok(fetched)
+    }
+}
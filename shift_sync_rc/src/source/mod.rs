@@ -0,0 +1,317 @@
+//! シフトの取得元
+//!
+//! Go版・Swift版は ShiftWeb だけを読む。掛け持ちで ShiftWeb を使わない職場もあるので、
+//! 取得元を `ShiftSource` にまとめ、いくつかの取得元のシフトを1つのカレンダーに同期できるようにする。
+
+pub mod csv_file;
+pub mod ics_feed;
+pub mod shiftweb;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::ics::IcsError;
+use crate::month::YearMonth;
+use crate::parser::{ParseError, ParseReport};
+use crate::shift::Shift;
+use crate::shiftweb::ShiftWebError;
+
+/// シフトを取得できなかった理由
+#[derive(Debug)]
+pub enum SourceError {
+    /// ShiftWeb にログインできない
+    Login(ShiftWebError),
+    /// ShiftWeb の月のページが取れない
+    Fetch {
+        month: YearMonth,
+        source: ShiftWebError,
+    },
+    /// ShiftWeb の月のページが読めない
+    Parse {
+        month: YearMonth,
+        source: ParseError,
+    },
+    /// ファイルが読めない
+    Io { path: PathBuf, source: io::Error },
+    /// CSV の見出しが読めない（`line` は1始まり）
+    Csv {
+        path: PathBuf,
+        line: u64,
+        message: String,
+    },
+    /// カレンダーの URL に接続できない・応答が読めない
+    Http { url: String, source: ureq::Error },
+    /// カレンダーの URL が想定外のステータスを返した
+    Status { url: String, status: u16 },
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceError::Login(err) => write!(f, "ShiftWeb ログインに失敗: {err}"),
+            SourceError::Fetch { month, source } => {
+                write!(f, "{month} のシフト取得に失敗: {source}")
+            }
+            SourceError::Parse { month, source } => {
+                write!(f, "{month} のシフト解析に失敗: {source}")
+            }
+            SourceError::Io { path, source } => {
+                write!(f, "{} の読み込みに失敗: {source}", path.display())
+            }
+            SourceError::Csv {
+                path,
+                line,
+                message,
+            } => write!(f, "{} の{line}行目が読めない: {message}", path.display()),
+            SourceError::Http { url, source } => write!(f, "{url}: {source}"),
+            SourceError::Status { url, status } => write!(f, "{url} status={status}"),
+        }
+    }
+}
+
+impl std::error::Error for SourceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SourceError::Login(err) => Some(err),
+            SourceError::Fetch { source, .. } => Some(source),
+            SourceError::Parse { source, .. } => Some(source),
+            SourceError::Io { source, .. } => Some(source),
+            SourceError::Http { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// 取得したシフト
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceShifts {
+    pub shifts: Vec<Shift>,
+    /// シフトにしなかった行・予定
+    pub reports: Vec<SourceReport>,
+}
+
+/// 取得元がシフトにしなかったもの
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceReport {
+    /// ShiftWeb の月のページでシフトにならなかった行
+    Page {
+        month: YearMonth,
+        report: ParseReport,
+    },
+    /// iCalendar の URL でシフトにできなかった予定（どれも要確認）
+    Events { url: String, skipped: Vec<IcsError> },
+    /// CSV ファイルでシフトにできなかった行（行番号は1始まり、どれも要確認）
+    Rows {
+        path: PathBuf,
+        skipped: Vec<(u64, String)>,
+    },
+}
+
+impl SourceReport {
+    /// 要確認のものがあるか
+    pub fn has_problems(&self) -> bool {
+        match self {
+            SourceReport::Page { report, .. } => report.has_problems(),
+            SourceReport::Events { skipped, .. } => !skipped.is_empty(),
+            SourceReport::Rows { skipped, .. } => !skipped.is_empty(),
+        }
+    }
+}
+
+/// シフトの取得元
+pub trait ShiftSource {
+    /// 表示用の名前（"ShiftWeb"、ファイル名など）
+    fn name(&self) -> String;
+
+    /// `months` の月のシフトを取得する
+    ///
+    /// 月の境目をまたぐシフトなど、`months` の外のシフトが混ざってもよい。
+    fn fetch(&mut self, months: &[YearMonth]) -> Result<SourceShifts, SourceError>;
+}
+
+/// イベント名・場所の上書き付きの取得元
+pub struct LabeledSource {
+    pub source: Box<dyn ShiftSource>,
+    /// イベント名（SUMMARY）を置き換える
+    pub title: Option<String>,
+    /// 場所（LOCATION）を置き換える。UID も場所から決まるので変わる
+    pub location: Option<String>,
+}
+
+impl LabeledSource {
+    pub fn new(source: impl ShiftSource + 'static) -> Self {
+        LabeledSource {
+            source: Box::new(source),
+            title: None,
+            location: None,
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    fn label(&self, mut shift: Shift) -> Shift {
+        if let Some(title) = &self.title {
+            shift.title = title.clone();
+        }
+        if let Some(location) = &self.location {
+            shift.location = location.clone();
+        }
+        shift
+    }
+}
+
+/// すべての取得元から `months` のシフトを取得してまとめる
+///
+/// 同じ UID のシフトは最初のものだけを残し、開始時刻順に並べる。
+/// （ShiftWeb は前後の月のページに同じ日がはみ出して載ることがある）
+pub fn fetch_all(
+    sources: &mut [LabeledSource],
+    months: &[YearMonth],
+) -> Result<SourceShifts, SourceError> {
+    let mut merged = SourceShifts::default();
+    for labeled in sources.iter_mut() {
+        let fetched = labeled.source.fetch(months)?;
+        merged
+            .shifts
+            .extend(fetched.shifts.into_iter().map(|s| labeled.label(s)));
+        merged.reports.extend(fetched.reports);
+    }
+    let mut seen = HashSet::new();
+    merged.shifts.retain(|s| seen.insert(s.uid()));
+    merged.shifts.sort_by_key(|s| s.start());
+    Ok(merged)
+}
+
+/// 取得元の種類
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceKind {
+    ShiftWeb,
+    /// ローカルの CSV ファイル
+    Csv(PathBuf),
+    /// iCalendar の URL（`webcal://` も可）
+    IcsFeed(String),
+}
+
+/// 取得元の指定（`-source` の値）
+///
+/// `shiftweb`、`csv:PATH`、`ics:URL` のあとに `;title=...`、`;location=...` を付けられる。
+/// 例: `csv:second_job.csv;title=🍔 マック;location=渋谷店`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSpec {
+    pub kind: SourceKind,
+    pub title: Option<String>,
+    pub location: Option<String>,
+}
+
+/// 取得元の指定が読めない
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSourceSpecError(String);
+
+impl fmt::Display for ParseSourceSpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "取得元 {:?} が読めない（shiftweb / csv:PATH / ics:URL のどれか）",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseSourceSpecError {}
+
+impl FromStr for SourceSpec {
+    type Err = ParseSourceSpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseSourceSpecError(s.to_string());
+        let mut parts = s.split(';');
+        let head = parts.next().unwrap_or_default().trim();
+        let kind = match head.split_once(':') {
+            _ if head.eq_ignore_ascii_case("shiftweb") => SourceKind::ShiftWeb,
+            Some((kind, path)) if kind.eq_ignore_ascii_case("csv") && !path.is_empty() => {
+                SourceKind::Csv(PathBuf::from(path))
+            }
+            Some((kind, url)) if kind.eq_ignore_ascii_case("ics") && !url.is_empty() => {
+                SourceKind::IcsFeed(url.to_string())
+            }
+            _ => return Err(invalid()),
+        };
+        let mut spec = SourceSpec {
+            kind,
+            title: None,
+            location: None,
+        };
+        for part in parts {
+            match part.split_once('=') {
+                Some(("title", title)) => spec.title = Some(title.to_string()),
+                Some(("location", location)) => spec.location = Some(location.to_string()),
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(spec)
+    }
+}
+
+impl fmt::Display for SourceSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            SourceKind::ShiftWeb => write!(f, "shiftweb")?,
+            SourceKind::Csv(path) => write!(f, "csv:{}", path.display())?,
+            SourceKind::IcsFeed(url) => write!(f, "ics:{url}")?,
+        }
+        if let Some(title) = &self.title {
+            write!(f, ";title={title}")?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, ";location={location}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_source_specs() {
+        assert_eq!(
+            "shiftweb".parse(),
+            Ok(SourceSpec {
+                kind: SourceKind::ShiftWeb,
+                title: None,
+                location: None,
+            })
+        );
+        assert_eq!(
+            "csv:shifts/second.csv;title=🍔 マック;location=渋谷店".parse(),
+            Ok(SourceSpec {
+                kind: SourceKind::Csv(PathBuf::from("shifts/second.csv")),
+                title: Some("🍔 マック".to_string()),
+                location: Some("渋谷店".to_string()),
+            })
+        );
+        let spec: SourceSpec = "ics:https://example.com/a.ics?x=1".parse().unwrap();
+        assert_eq!(
+            spec.kind,
+            SourceKind::IcsFeed("https://example.com/a.ics?x=1".to_string())
+        );
+        assert_eq!(spec.to_string(), "ics:https://example.com/a.ics?x=1");
+        let text = "csv:shifts/second.csv;title=🍔 マック;location=渋谷店";
+        assert_eq!(text.parse::<SourceSpec>().unwrap().to_string(), text);
+        assert!("csv:".parse::<SourceSpec>().is_err());
+        assert!("ftp:x".parse::<SourceSpec>().is_err());
+        assert!("shiftweb;color=red".parse::<SourceSpec>().is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! ShiftWeb のシフトページ
+//! Go版: fetchShiftHTML, parseShifts
+
+use chrono_tz::Tz;
+
+use crate::month::YearMonth;
+use crate::parser::parse_shifts_for;
+use crate::shiftweb::ShiftWebClient;
+use crate::source::{ShiftSource, SourceError, SourceReport, SourceShifts};
+
+/// ShiftWeb にログインして月ごとのシフトページを読む取得元
+pub struct ShiftWebSource {
+    client: ShiftWebClient,
+    id: String,
+    password: String,
+    tz: Tz,
+    logged_in: bool,
+}
+
+impl ShiftWebSource {
+    pub fn new(client: ShiftWebClient, id: &str, password: &str, tz: Tz) -> Self {
+        ShiftWebSource {
+            client,
+            id: id.to_string(),
+            password: password.to_string(),
+            tz,
+            logged_in: false,
+        }
+    }
+}
+
+impl ShiftSource for ShiftWebSource {
+    fn name(&self) -> String {
+        "ShiftWeb".to_string()
+    }
+
+    fn fetch(&mut self, months: &[YearMonth]) -> Result<SourceShifts, SourceError> {
+        if !self.logged_in {
+            self.client
+                .login(&self.id, &self.password)
+                .map_err(SourceError::Login)?;
+            self.logged_in = true;
+        }
+
+        let mut fetched = SourceShifts::default();
+        for &month in months {
+            let html = self
+                .client
+                .fetch_month(month)
+                .map_err(|source| SourceError::Fetch { month, source })?;
+            let page = parse_shifts_for(&html, month, self.tz)
+                .map_err(|source| SourceError::Parse { month, source })?;
+            fetched.shifts.extend(page.shifts);
+            fetched.reports.push(SourceReport::Page {
+                month,
+                report: page.report,
+            });
+        }
+        Ok(fetched)
+    }
+}
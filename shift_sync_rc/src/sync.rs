@@ -0,0 +1,265 @@
+//! 同期の手順・範囲・結果
+//!
+//! 書き込み先（CalDAV など）によらない部分。
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use crate::backend::{BackendError, CalendarBackend, RemoteEvent};
+use crate::month::YearMonth;
+use crate::shift::Shift;
+
+/// `shifts` を `backend` のカレンダーに反映する
+///
+/// - カレンダーにないシフトは追加
+/// - 内容が変わったシフトだけ更新
+/// - `window` 内のシフトのイベントで `shifts` にないものは削除
+///
+/// 書き込んだ後に他で編集されたイベント（書き込み先が見分けられる場合）と、
+/// 条件付きの書き込みが競合したイベントは上書きせず `conflicts` に入れる。
+/// `window` の外にある既存イベントには触らない。1件ごとの失敗は結果の `failures` に入れて続行する。
+/// Go版: syncShiftsToCalDAV / Swift版: GoogleCalendarService.syncShifts
+pub fn sync_shifts<B: CalendarBackend>(
+    backend: &mut B,
+    shifts: &[Shift],
+    window: SyncWindow,
+) -> Result<SyncSummary, B::Error> {
+    let mut seen = HashSet::new();
+    let desired: Vec<(String, &Shift)> = shifts
+        .iter()
+        .map(|s| (s.uid(), s))
+        .filter(|(uid, _)| seen.insert(uid.clone()))
+        .collect();
+
+    let mut existing: HashMap<String, RemoteEvent> = HashMap::new();
+    for event in backend.list_events(window.including(shifts))? {
+        existing.entry(event.uid.clone()).or_insert(event);
+    }
+    let detects_edits = backend.capabilities().detects_edits;
+
+    let mut summary = SyncSummary::default();
+
+    let mut stale: Vec<&RemoteEvent> = existing
+        .values()
+        .filter(|e| !seen.contains(&e.uid))
+        .filter(|e| e.date.is_some_and(|d| window.contains(d)))
+        .collect();
+    stale.sort_by(|a, b| a.uid.cmp(&b.uid));
+    for event in stale {
+        let result = backend.delete_event(event);
+        summary.apply(&event.uid, SyncAction::Delete, result);
+    }
+
+    for (uid, shift) in desired {
+        let (action, result) = match existing.get(&uid) {
+            None => (SyncAction::Create, backend.create_event(shift)),
+            Some(current) => {
+                if current.hash == backend.content_hash(shift) {
+                    continue;
+                }
+                if detects_edits && current.is_edited() {
+                    // 前回の書き込みの後にスマホなどで編集されている
+                    summary.conflict(&uid, SyncAction::Update);
+                    continue;
+                }
+                (SyncAction::Update, backend.update_event(current, shift))
+            }
+        };
+        summary.apply(&uid, action, result);
+    }
+
+    backend.finish()?;
+    Ok(summary)
+}
+
+/// 削除してよい範囲（`start` 以上 `end` 未満の日付）
+///
+/// ShiftWeb から取得した月の外にある既存イベントは、取得していないだけで消えたわけではない。
+/// Go版はこの区別をせず、取得した2か月以外のシフトをすべて消していた。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncWindow {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl SyncWindow {
+    /// 取得した月の最初の月初から、最後の月の翌月初まで
+    pub fn from_months(months: &[YearMonth]) -> Option<Self> {
+        let first = months.iter().min()?;
+        let last = months.iter().max()?;
+        Some(SyncWindow {
+            start: first.first_day(),
+            end: last.add_months(1).first_day(),
+        })
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date < self.end
+    }
+
+    /// 既存イベントを探す範囲（前後の月のページにはみ出して載っていたシフトの日も含める）
+    pub fn including(self, shifts: &[Shift]) -> SyncWindow {
+        shifts.iter().fold(self, |range, s| SyncWindow {
+            start: range.start.min(s.date()),
+            end: range
+                .end
+                .max(s.end().date_naive().succ_opt().unwrap_or(range.end)),
+        })
+    }
+}
+
+/// 同期で行う操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl fmt::Display for SyncAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SyncAction::Create => "追加",
+            SyncAction::Update => "更新",
+            SyncAction::Delete => "削除",
+        })
+    }
+}
+
+/// 1件分の失敗（他のイベントの同期は続ける）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncFailure {
+    pub uid: String,
+    pub action: SyncAction,
+    pub message: String,
+}
+
+impl fmt::Display for SyncFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} の{}に失敗: {}", self.uid, self.action, self.message)
+    }
+}
+
+/// 他で変更されていたため書き込まなかったイベント
+///
+/// スマホで編集されたイベントや、同期中に別の端末が書き換えたイベント（412）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncConflict {
+    pub uid: String,
+    pub action: SyncAction,
+}
+
+impl fmt::Display for SyncConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} は他で変更されていたため{}しませんでした",
+            self.uid, self.action
+        )
+    }
+}
+
+/// 同期結果
+/// Swift版: SyncResultSummary
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub conflicts: Vec<SyncConflict>,
+    pub failures: Vec<SyncFailure>,
+}
+
+impl SyncSummary {
+    pub fn has_changes(&self) -> bool {
+        self.added > 0 || self.updated > 0 || self.deleted > 0
+    }
+
+    /// "+2 ↻1 -1" / "変更なし"
+    /// Swift版: SyncResultSummary.shortDescription
+    pub fn short_description(&self) -> String {
+        if !self.has_changes() {
+            return "変更なし".to_string();
+        }
+        let mut parts = Vec::new();
+        if self.added > 0 {
+            parts.push(format!("+{}", self.added));
+        }
+        if self.updated > 0 {
+            parts.push(format!("↻{}", self.updated));
+        }
+        if self.deleted > 0 {
+            parts.push(format!("-{}", self.deleted));
+        }
+        parts.join(" ")
+    }
+
+    /// 1件分の書き込みの結果を数える
+    fn apply<E: BackendError>(&mut self, uid: &str, action: SyncAction, result: Result<(), E>) {
+        match result {
+            Ok(()) => self.record(action),
+            Err(err) if err.is_conflict() => self.conflict(uid, action),
+            Err(err) => self.fail(uid, action, err),
+        }
+    }
+
+    pub(crate) fn record(&mut self, action: SyncAction) {
+        match action {
+            SyncAction::Create => self.added += 1,
+            SyncAction::Update => self.updated += 1,
+            SyncAction::Delete => self.deleted += 1,
+        }
+    }
+
+    pub(crate) fn conflict(&mut self, uid: &str, action: SyncAction) {
+        self.conflicts.push(SyncConflict {
+            uid: uid.to_string(),
+            action,
+        });
+    }
+
+    pub(crate) fn fail(&mut self, uid: &str, action: SyncAction, message: impl ToString) {
+        self.failures.push(SyncFailure {
+            uid: uid.to_string(),
+            action,
+            message: message.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn window_covers_fetched_months_only() {
+        let months = [
+            YearMonth::new(2025, 12).unwrap(),
+            YearMonth::new(2026, 1).unwrap(),
+        ];
+        let window = SyncWindow::from_months(&months).unwrap();
+        assert_eq!(window.start, date(2025, 12, 1));
+        assert_eq!(window.end, date(2026, 2, 1));
+        assert!(window.contains(date(2026, 1, 31)));
+        assert!(!window.contains(date(2026, 2, 1)));
+        assert!(!window.contains(date(2025, 11, 30)));
+        assert_eq!(SyncWindow::from_months(&[]), None);
+    }
+
+    #[test]
+    fn short_description_matches_swift() {
+        let mut summary = SyncSummary::default();
+        assert_eq!(summary.short_description(), "変更なし");
+        summary.added = 2;
+        summary.deleted = 1;
+        assert_eq!(summary.short_description(), "+2 -1");
+        summary.updated = 3;
+        assert_eq!(summary.short_description(), "+2 ↻3 -1");
+    }
+}
@@ -0,0 +1,391 @@
+//! イベントの SUMMARY・LOCATION・DESCRIPTION のテンプレート
+//!
+//! Go版・Swift版はイベント名を「バイト」に固定し、メモも書かない。
+//! ここでは `{shop} ({hours}h)` のようなテンプレートをシフトごとに埋めて、イベントの中身を決められるようにする。
+//! `{` `}` そのものは `{{` `}}` と書く。
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::TimeDelta;
+
+use crate::shift::{DEFAULT_TITLE, Shift};
+
+/// テンプレートで使える変数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variable {
+    /// 元のイベント名
+    Title,
+    /// 場所（勤務先）
+    Shop,
+    /// 元のメモ
+    Memo,
+    /// "1/15"
+    Date,
+    /// "木"
+    Weekday,
+    /// "10:00"
+    Start,
+    /// "19:00"
+    End,
+    /// 勤務時間（"8"、"7.5"）
+    Hours,
+    /// 時給 × 勤務時間の見込み（"9,600"）。時給の設定が要る
+    Wage,
+}
+
+impl Variable {
+    pub const ALL: [Variable; 9] = [
+        Variable::Title,
+        Variable::Shop,
+        Variable::Memo,
+        Variable::Date,
+        Variable::Weekday,
+        Variable::Start,
+        Variable::End,
+        Variable::Hours,
+        Variable::Wage,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Variable::Title => "title",
+            Variable::Shop => "shop",
+            Variable::Memo => "memo",
+            Variable::Date => "date",
+            Variable::Weekday => "weekday",
+            Variable::Start => "start",
+            Variable::End => "end",
+            Variable::Hours => "hours",
+            Variable::Wage => "wage",
+        }
+    }
+}
+
+impl FromStr for Variable {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Variable::ALL
+            .into_iter()
+            .find(|v| v.name() == s)
+            .ok_or_else(|| TemplateError::UnknownVariable(s.to_string()))
+    }
+}
+
+/// テンプレートが読めない理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// 知らない変数
+    UnknownVariable(String),
+    /// `{` が閉じていない
+    Unclosed,
+    /// 対応する `{` のない `}`
+    UnmatchedClose,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnknownVariable(name) => {
+                let names: Vec<String> = Variable::ALL
+                    .iter()
+                    .map(|v| format!("{{{}}}", v.name()))
+                    .collect();
+                write!(
+                    f,
+                    "変数 {{{name}}} は使えません（使えるのは {}）",
+                    names.join(" ")
+                )
+            }
+            TemplateError::Unclosed => write!(f, "`{{` が閉じていません"),
+            TemplateError::UnmatchedClose => {
+                write!(
+                    f,
+                    "対応する `{{` のない `}}` があります（文字として書くなら `}}}}`）"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Variable(Variable),
+}
+
+/// 読み込んだテンプレート
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = source.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => return Err(TemplateError::UnmatchedClose),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(TemplateError::Unclosed),
+                        }
+                    }
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Variable(name.trim().parse()?));
+                }
+                _ => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Ok(Template {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    /// 書かれたままのテンプレート
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn uses(&self, variable: Variable) -> bool {
+        self.segments.contains(&Segment::Variable(variable))
+    }
+
+    /// `shift` の値で埋める（`hourly_wage` がなければ `{wage}` は空）
+    pub fn render(&self, shift: &Shift, hourly_wage: Option<u32>) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => out.push_str(text),
+                Segment::Variable(variable) => {
+                    out.push_str(&value(*variable, shift, hourly_wage));
+                }
+            }
+        }
+        out
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Template::parse(s)
+    }
+}
+
+fn value(variable: Variable, shift: &Shift, hourly_wage: Option<u32>) -> String {
+    match variable {
+        Variable::Title => shift.title.clone(),
+        Variable::Shop => shift.location.clone(),
+        Variable::Memo => shift.memo.clone(),
+        Variable::Date => shift.date_string(),
+        Variable::Weekday => shift.day_of_week().to_string(),
+        Variable::Start => shift.start().format("%H:%M").to_string(),
+        Variable::End => shift.end().format("%H:%M").to_string(),
+        Variable::Hours => format_hours(shift.duration()),
+        Variable::Wage => hourly_wage
+            .map(|wage| format_yen(wage_for(shift.duration(), wage)))
+            .unwrap_or_default(),
+    }
+}
+
+/// "8"、"7.5"、"7.25"（小数は2桁まで）
+fn format_hours(duration: TimeDelta) -> String {
+    let hundredths = duration.num_minutes() * 100 / 60;
+    let text = format!("{}.{:02}", hundredths / 100, hundredths % 100);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// 時給 × 勤務時間（1円未満は切り捨て）
+fn wage_for(duration: TimeDelta, hourly_wage: u32) -> u64 {
+    duration.num_minutes().max(0) as u64 * u64::from(hourly_wage) / 60
+}
+
+/// "9,600"
+fn format_yen(yen: u64) -> String {
+    let digits = yen.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// SUMMARY・LOCATION・DESCRIPTION のテンプレートと時給
+///
+/// テンプレートのない項目は元のシフトのまま。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventTemplates {
+    pub summary: Option<Template>,
+    pub location: Option<Template>,
+    pub description: Option<Template>,
+    /// `{wage}` に使う時給（円）
+    pub hourly_wage: Option<u32>,
+    /// 場所ごとの時給。ここにある場所は `hourly_wage` の代わりにこちらを使う
+    pub hourly_wages: Vec<(String, u32)>,
+}
+
+impl EventTemplates {
+    pub fn templates(&self) -> impl Iterator<Item = &Template> {
+        [&self.summary, &self.location, &self.description]
+            .into_iter()
+            .flatten()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates().next().is_none()
+    }
+
+    /// `shift` の場所の時給
+    pub fn hourly_wage_for(&self, shift: &Shift) -> Option<u32> {
+        self.hourly_wages
+            .iter()
+            .find(|(location, _)| *location == shift.location)
+            .map(|(_, wage)| *wage)
+            .or(self.hourly_wage)
+    }
+
+    /// テンプレートでイベント名・場所・メモを書き換える
+    ///
+    /// 変数はすべて元のシフトの値で埋める。イベント名が空になったら DEFAULT_TITLE にする。
+    /// 場所を書き換えると UID も変わる（`-source` の `;location=` と同じ）。
+    pub fn apply(&self, shift: Shift) -> Shift {
+        if self.is_empty() {
+            return shift;
+        }
+        let wage = self.hourly_wage_for(&shift);
+        let render = |template: &Option<Template>| {
+            template
+                .as_ref()
+                .map(|t| t.render(&shift, wage).trim().to_string())
+        };
+        let (summary, location, description) = (
+            render(&self.summary),
+            render(&self.location),
+            render(&self.description),
+        );
+
+        let mut shift = shift;
+        if let Some(summary) = summary {
+            shift.title = Some(summary)
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| DEFAULT_TITLE.to_string());
+        }
+        if let Some(location) = location {
+            shift.location = location;
+        }
+        if let Some(description) = description {
+            shift.memo = description;
+        }
+        shift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::shift::DEFAULT_TZ;
+
+    fn shift(start: &str, end: &str) -> Shift {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        Shift::on_date(DEFAULT_TITLE, day, start, end, "マック 渋谷", DEFAULT_TZ).unwrap()
+    }
+
+    #[test]
+    fn renders_variables() {
+        let t: Template = "🍔 {shop} ({hours}h)".parse().unwrap();
+        assert_eq!(
+            t.render(&shift("10:00", "18:00"), None),
+            "🍔 マック 渋谷 (8h)"
+        );
+        assert_eq!(
+            t.render(&shift("22:00", "05:30"), None),
+            "🍔 マック 渋谷 (7.5h)"
+        );
+
+        let t: Template = "{date}({weekday}) {start}〜{end} {{{title}}} 見込み{wage}円"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            t.render(&shift("10:00", "19:45"), Some(1200)),
+            "1/15(木) 10:00〜19:45 {バイト} 見込み11,700円"
+        );
+        assert_eq!(
+            t.render(&shift("10:00", "19:45"), None),
+            "1/15(木) 10:00〜19:45 {バイト} 見込み円"
+        );
+        assert!(t.uses(Variable::Wage));
+        assert_eq!(format_hours(TimeDelta::minutes(435)), "7.25");
+    }
+
+    #[test]
+    fn rejects_broken_templates() {
+        assert_eq!(Template::parse("{shop"), Err(TemplateError::Unclosed));
+        assert_eq!(Template::parse("shop}"), Err(TemplateError::UnmatchedClose));
+        assert_eq!(
+            Template::parse("{store}"),
+            Err(TemplateError::UnknownVariable("store".to_string()))
+        );
+        let message = TemplateError::UnknownVariable("store".to_string()).to_string();
+        assert!(
+            message.contains("{store}") && message.contains("{shop}"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn applies_templates_to_shift() {
+        let templates = EventTemplates {
+            summary: Some("🍔 {shop} ({hours}h)".parse().unwrap()),
+            description: Some("{memo} 見込み{wage}円".parse().unwrap()),
+            hourly_wage: Some(1000),
+            hourly_wages: vec![("マック 渋谷".to_string(), 1200)],
+            ..EventTemplates::default()
+        };
+        let applied = templates.apply(shift("10:00", "18:00").with_memo("早番"));
+        assert_eq!(applied.title, "🍔 マック 渋谷 (8h)");
+        assert_eq!(applied.location, "マック 渋谷");
+        assert_eq!(applied.memo, "早番 見込み9,600円");
+        assert_eq!(applied.uid(), shift("10:00", "18:00").uid());
+
+        let blank = EventTemplates {
+            summary: Some("{memo}".parse().unwrap()),
+            ..EventTemplates::default()
+        };
+        assert_eq!(blank.apply(shift("10:00", "18:00")).title, DEFAULT_TITLE);
+        assert_eq!(
+            EventTemplates::default().apply(shift("10:00", "18:00")),
+            shift("10:00", "18:00")
+        );
+    }
+}
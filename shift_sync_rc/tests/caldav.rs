@@ -0,0 +1,505 @@
+mod support;
+
+use shift_sync_rc::caldav::state::SyncState;
+use shift_sync_rc::caldav::sync::CalDavBackend;
+use shift_sync_rc::caldav::{CalDavClient, ICLOUD_URL, NewCalendar, Precondition, normalize_color};
+use shift_sync_rc::ics::generate_event_ics;
+use shift_sync_rc::month::YearMonth;
+use shift_sync_rc::shift::{DEFAULT_TZ, Shift};
+use shift_sync_rc::sync::{SyncWindow, sync_shifts};
+use support::caldav::{HOME, MockCalDav, MockCalendar, PRINCIPAL};
+use support::{january, shift};
+
+fn mock() -> MockCalDav {
+    MockCalDav::start("user01", "app-pass")
+        .with_calendar(
+            "work",
+            MockCalendar::new("バイト")
+                .color("#FF2968FF")
+                .description("シフト用"),
+        )
+        .with_calendar(
+            "reminders",
+            MockCalendar::new("リマインダー").components(&["VTODO"]),
+        )
+}
+
+#[test]
+fn discovers_principal_home_and_calendars() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let found = client.discover(server.base_url()).unwrap();
+
+    assert_eq!(
+        found.principal_url,
+        format!("{}{PRINCIPAL}", server.base_url())
+    );
+    assert_eq!(found.home_url, format!("{}{HOME}", server.base_url()));
+    assert_eq!(found.calendars.len(), 2);
+
+    let reminders = &found.calendars[0];
+    assert_eq!(reminders.name(), "リマインダー");
+    assert!(!reminders.supports_events());
+
+    let work = &found.calendars[1];
+    assert_eq!(work.url, server.calendar_url("work"));
+    assert_eq!(work.name(), "バイト");
+    assert_eq!(work.color.as_deref(), Some("#FF2968FF"));
+    assert_eq!(work.description.as_deref(), Some("シフト用"));
+    assert_eq!(work.components, ["VEVENT"]);
+    assert!(work.supports_events());
+}
+
+#[test]
+fn falls_back_to_well_known() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let start = format!("{}/somewhere/else/", server.base_url());
+    let found = client.discover(&start).unwrap();
+    assert_eq!(found.calendars.len(), 2);
+    assert!(
+        server
+            .requests()
+            .contains(&"PROPFIND /.well-known/caldav".to_string())
+    );
+}
+
+#[test]
+fn wrong_password_is_unauthorized() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "wrong");
+    let err = client.discover(server.base_url()).unwrap_err();
+    assert!(err.is_unauthorized(), "{err}");
+}
+
+#[test]
+fn reads_single_calendar() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let calendar = client.calendar(&server.calendar_url("work")).unwrap();
+    assert_eq!(calendar.name(), "バイト");
+}
+
+#[test]
+fn creates_calendar_with_properties() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let home = format!("{}{HOME}", server.base_url());
+    let new = NewCalendar {
+        description: Some("ShiftWeb のシフト & メモ".to_string()),
+        color: normalize_color("1badf8"),
+        timezone: Some(DEFAULT_TZ),
+        ..NewCalendar::new("バイト <新>")
+    };
+    let created = client.create_calendar(&home, &new).unwrap();
+
+    // パスは Go版と同じくホーム直下の UUID
+    let id = created.url.strip_prefix(&home).unwrap();
+    assert_eq!(id.len(), 37, "{id}");
+    assert!(id.ends_with('/'));
+    assert_eq!(created.name(), "バイト <新>");
+    assert_eq!(created.color.as_deref(), Some("#1BADF8FF"));
+    assert_eq!(
+        created.description.as_deref(),
+        Some("ShiftWeb のシフト & メモ")
+    );
+    assert_eq!(created.components, ["VEVENT"]);
+
+    let stored = server.calendar_at(&created.url).unwrap();
+    let timezone = stored.timezone.unwrap();
+    assert!(timezone.contains("\r\nTZID:Asia/Tokyo\r\n"), "{timezone}");
+
+    let found = client.discover(server.base_url()).unwrap();
+    assert!(found.calendars.contains(&created));
+
+    // 同じ URL にはもう作れない
+    let err = client.make_calendar(&created.url, &new).unwrap_err();
+    assert!(err.to_string().contains("status=405"), "{err}");
+}
+
+#[test]
+fn normalizes_calendar_colors() {
+    assert_eq!(normalize_color("#ff2968").as_deref(), Some("#FF2968FF"));
+    assert_eq!(normalize_color(" FF296880 ").as_deref(), Some("#FF296880"));
+    assert_eq!(normalize_color("red"), None);
+    assert_eq!(normalize_color("#FF29"), None);
+}
+
+fn resource(s: &Shift) -> String {
+    format!("{}.ics", s.uid())
+}
+
+#[test]
+fn sync_adds_then_reports_no_changes() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let mut state = SyncState::new(&server.calendar_url("work"));
+    let shifts = [
+        shift(2026, 1, 5, "10:00", "19:00"),
+        shift(2026, 1, 31, "22:00", "06:00"),
+    ];
+
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    assert_eq!((summary.added, summary.updated, summary.deleted), (2, 0, 0));
+    assert!(summary.failures.is_empty());
+    assert_eq!(
+        server.event_names("work"),
+        [resource(&shifts[0]), resource(&shifts[1])]
+    );
+    let stored = server.event("work", &resource(&shifts[1])).unwrap().body;
+    assert!(stored.contains("\r\nDTEND;TZID=Asia/Tokyo:20260201T060000\r\n"));
+    assert!(stored.contains("\r\nBEGIN:VTIMEZONE\r\nTZID:Asia/Tokyo\r\n"));
+
+    server.clear_requests();
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    assert_eq!(summary.short_description(), "変更なし");
+    // 前回の ETag のままなので中身も取りに行かない
+    assert_eq!(server.count("GET"), 0);
+    assert_eq!(server.count("PUT"), 0);
+}
+
+#[test]
+fn sync_does_not_overwrite_phone_edits() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let mut state = SyncState::new(&server.calendar_url("work"));
+    let shifts = [shift(2026, 1, 5, "10:00", "19:00")];
+    sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+
+    let name = resource(&shifts[0]);
+    let edited = server
+        .event("work", &name)
+        .unwrap()
+        .body
+        .replace("SUMMARY:バイト", "SUMMARY:バイト（代打あり）");
+    server.put_event("work", &name, &edited);
+
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    assert_eq!(summary.short_description(), "変更なし");
+    assert_eq!(summary.conflicts.len(), 1);
+    assert_eq!(summary.conflicts[0].uid, shifts[0].uid());
+    assert_eq!(server.event("work", &name).unwrap().body, edited);
+}
+
+#[test]
+fn sync_keeps_phone_edit_protection_across_months() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let mut state = SyncState::new(&server.calendar_url("work"));
+    let january_shifts = [shift(2026, 1, 5, "10:00", "19:00")];
+    let march_shifts = [shift(2026, 3, 2, "10:00", "19:00")];
+    let march = SyncWindow::from_months(&[YearMonth::new(2026, 3).unwrap()]).unwrap();
+    sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &january_shifts,
+        january(),
+    )
+    .unwrap();
+    sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &march_shifts,
+        march,
+    )
+    .unwrap();
+
+    let name = resource(&january_shifts[0]);
+    let edited = server
+        .event("work", &name)
+        .unwrap()
+        .body
+        .replace("SUMMARY:バイト", "SUMMARY:バイト（代打あり）");
+    server.put_event("work", &name, &edited);
+
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &january_shifts,
+        january(),
+    )
+    .unwrap();
+    assert_eq!(summary.conflicts.len(), 1);
+    assert_eq!(server.event("work", &name).unwrap().body, edited);
+    assert!(state.get(&march_shifts[0].uid()).is_some());
+}
+
+#[test]
+fn sync_adopts_existing_events_without_state() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let s = shift(2026, 1, 5, "10:00", "19:00");
+    // 記録を持たない別の実装（Go版など）が書いたイベント
+    server.put_event(
+        "work",
+        &resource(&s),
+        &generate_event_ics(&s).replace("SUMMARY:バイト", "SUMMARY:シフト"),
+    );
+
+    let mut state = SyncState::new(&server.calendar_url("work"));
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        std::slice::from_ref(&s),
+        january(),
+    )
+    .unwrap();
+    assert_eq!((summary.added, summary.updated, summary.deleted), (0, 1, 0));
+    assert!(summary.conflicts.is_empty());
+    assert_eq!(
+        state.get(&s.uid()).map(|r| r.etag.clone()),
+        Some(server.event("work", &resource(&s)).unwrap().etag)
+    );
+}
+
+#[test]
+fn sync_finds_events_by_content_not_name() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let mut state = SyncState::new(&server.calendar_url("work"));
+
+    // 他のクライアントがリソース名を変えたイベント
+    let renamed = shift(2026, 1, 5, "10:00", "19:00");
+    server.put_event("work", "8F2C-RENAMED.ics", &generate_event_ics(&renamed));
+    // UID まで書き換えられたが X-SHIFT-SYNC は残っているイベント（もう ShiftWeb にない）
+    let removed = shift(2026, 1, 6, "10:00", "19:00");
+    let rewritten = generate_event_ics(&removed).replace(
+        &format!("UID:{}", removed.uid()),
+        "UID:0F1E2D3C@example.com",
+    );
+    server.put_event("work", "0F1E2D3C.ics", &rewritten);
+    // シフトと関係ない予定
+    let birthday = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:birthday@example.com\r\n\
+DTSTART:20260110T000000\r\nDTEND:20260111T000000\r\nSUMMARY:誕生日\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+    server.put_event("work", "birthday.ics", birthday);
+
+    let shifts = [renamed.clone().with_memo("早番")];
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    assert_eq!((summary.added, summary.updated, summary.deleted), (0, 1, 1));
+    assert_eq!(
+        server.event_names("work"),
+        ["8F2C-RENAMED.ics", "birthday.ics"]
+    );
+    assert!(
+        server
+            .event("work", "8F2C-RENAMED.ics")
+            .unwrap()
+            .body
+            .contains("DESCRIPTION:早番")
+    );
+    assert_eq!(server.count_report("calendar-query"), 1);
+    assert_eq!(server.count_report("calendar-multiget"), 1);
+}
+
+#[test]
+fn sync_uses_sync_token_for_later_runs() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let mut state = SyncState::new(&server.calendar_url("work"));
+    let shifts = [
+        shift(2026, 1, 5, "10:00", "19:00"),
+        shift(2026, 1, 6, "10:00", "19:00"),
+    ];
+    sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    assert!(state.sync_token.is_some());
+
+    // スマホで1件消され、別の shift-* が1件足された
+    server.delete_event("work", &resource(&shifts[0]));
+    let extra = shift(2026, 1, 7, "10:00", "19:00");
+    server.put_event("work", &resource(&extra), &generate_event_ics(&extra));
+
+    server.clear_requests();
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    assert_eq!((summary.added, summary.updated, summary.deleted), (1, 0, 1));
+    assert_eq!(server.count_report("sync-collection"), 1);
+    assert_eq!(server.count_report("calendar-query"), 0);
+    assert_eq!(server.count("PROPFIND"), 0);
+    assert_eq!(
+        server.event_names("work"),
+        [resource(&shifts[0]), resource(&shifts[1])]
+    );
+}
+
+#[test]
+fn sync_falls_back_to_full_listing_on_invalid_token() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let mut state = SyncState::new(&server.calendar_url("work"));
+    let shifts = [shift(2026, 1, 5, "10:00", "19:00")];
+    sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    let old_token = state.sync_token.clone();
+
+    server.invalidate_sync_tokens();
+    server.delete_event("work", &resource(&shifts[0]));
+    server.clear_requests();
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    assert_eq!(summary.added, 1);
+    assert_eq!(server.count_report("sync-collection"), 1);
+    assert_eq!(server.count_report("calendar-query"), 1);
+    assert_ne!(state.sync_token, old_token);
+
+    // 取り直したトークンは次回から使える
+    server.clear_requests();
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    assert!(!summary.has_changes());
+    assert_eq!(server.count_report("calendar-query"), 0);
+}
+
+#[test]
+fn conditional_writes_fail_with_conflict() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let url = format!("{}shift-x.ics", server.calendar_url("work"));
+    let ics = "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n";
+
+    let etag = client
+        .put_event(&url, ics, Precondition::Absent)
+        .unwrap()
+        .unwrap();
+    let err = client
+        .put_event(&url, ics, Precondition::Absent)
+        .unwrap_err();
+    assert!(err.is_conflict(), "{err}");
+    let err = client
+        .put_event(&url, ics, Precondition::Matches("\"stale\""))
+        .unwrap_err();
+    assert!(err.is_conflict(), "{err}");
+    let err = client
+        .delete_event(&url, Precondition::Matches("\"stale\""))
+        .unwrap_err();
+    assert!(err.is_conflict(), "{err}");
+
+    assert_eq!(client.get_event(&url).unwrap().etag.as_ref(), Some(&etag));
+    client
+        .delete_event(&url, Precondition::Matches(&etag))
+        .unwrap();
+    assert!(server.event_names("work").is_empty());
+}
+
+#[test]
+fn sync_updates_changed_events_only() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let mut state = SyncState::new(&server.calendar_url("work"));
+    let mut shifts = vec![
+        shift(2026, 1, 5, "10:00", "19:00"),
+        shift(2026, 1, 6, "10:00", "19:00"),
+    ];
+    sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+
+    // UID は時刻と場所だけで決まるので、メモの変更は同じリソースの更新になる
+    shifts[1] = shifts[1].clone().with_memo("早番");
+    server.clear_requests();
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    assert_eq!((summary.added, summary.updated, summary.deleted), (0, 1, 0));
+    assert_eq!(server.count("PUT"), 1);
+    let stored = server.event("work", &resource(&shifts[1])).unwrap().body;
+    assert!(stored.contains("DESCRIPTION:早番"));
+}
+
+#[test]
+fn sync_deletes_only_inside_window() {
+    let server = mock();
+    let client = CalDavClient::new("user01", "app-pass");
+    let mut state = SyncState::new(&server.calendar_url("work"));
+
+    let kept = shift(2026, 1, 5, "10:00", "19:00");
+    let removed = shift(2026, 1, 20, "10:00", "19:00");
+    let history = shift(2025, 12, 20, "10:00", "19:00");
+    let next_month = shift(2026, 2, 3, "10:00", "19:00");
+    for s in [&kept, &removed, &history, &next_month] {
+        server.put_event("work", &resource(s), &generate_event_ics(s));
+    }
+    server.put_event(
+        "work",
+        "birthday.ics",
+        "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n",
+    );
+
+    let summary = sync_shifts(
+        &mut CalDavBackend::new(&client, &mut state),
+        std::slice::from_ref(&kept),
+        january(),
+    )
+    .unwrap();
+    assert_eq!((summary.added, summary.updated, summary.deleted), (0, 0, 1));
+    assert_eq!(
+        server.event_names("work"),
+        [
+            "birthday.ics".to_string(),
+            resource(&history),
+            resource(&kept),
+            resource(&next_month),
+        ]
+    );
+}
+
+/// 実サーバー（Radicale など）に対する確認
+///
+/// `SHIFT_SYNC_CALDAV_URL` / `SHIFT_SYNC_CALDAV_USER` / `SHIFT_SYNC_CALDAV_PASSWORD` を設定して
+/// `cargo test -- --ignored` で動かす。
+#[test]
+#[ignore]
+fn discovers_on_real_server() {
+    let url = std::env::var("SHIFT_SYNC_CALDAV_URL").unwrap_or_else(|_| ICLOUD_URL.to_string());
+    let user = std::env::var("SHIFT_SYNC_CALDAV_USER").expect("SHIFT_SYNC_CALDAV_USER");
+    let password = std::env::var("SHIFT_SYNC_CALDAV_PASSWORD").expect("SHIFT_SYNC_CALDAV_PASSWORD");
+    let found = CalDavClient::new(&user, &password).discover(&url).unwrap();
+    assert!(!found.home_url.is_empty());
+}
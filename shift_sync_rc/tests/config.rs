@@ -0,0 +1,57 @@
+mod support;
+
+use std::path::{Path, PathBuf};
+
+use shift_sync_rc::config::{CONFIG_FILE_NAME, CONFIG_VERSION, Config};
+use shift_sync_rc::reminder::Reminders;
+
+/// Go版の saveConfig が書いたファイル
+const GO_CONFIG: &str = "[shiftweb]\n  id = \"12345\"\n\n\
+[icloud]\n  apple_id = \"user@example.com\"\n  calendar_url = \"https://caldav.icloud.com/1/calendars/work/\"\n";
+
+fn config_path(name: &str) -> PathBuf {
+    support::temp_path(
+        &format!("config_{name}"),
+        Path::new(".shift_sync").join(CONFIG_FILE_NAME),
+    )
+}
+
+#[test]
+fn missing_file_is_default_config() {
+    let path = config_path("missing");
+    assert_eq!(Config::load(&path).unwrap(), Config::default());
+    assert!(!path.exists());
+}
+
+#[test]
+fn saves_extension_next_to_go_settings() {
+    let path = config_path("save");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, GO_CONFIG).unwrap();
+
+    let mut config = Config::load(&path).unwrap();
+    assert_eq!(config.migrated_from, None);
+    assert_eq!(config.shiftweb.id, "12345");
+    config.templates.summary = Some("🍔 {shop} ({hours}h)".parse().unwrap());
+    config.reminders = Reminders::new(&[60, 15]);
+    config.save(&path).unwrap();
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        written.contains(&format!("version = {CONFIG_VERSION}")),
+        "{written}"
+    );
+    assert!(written.contains("[shift_sync_rc.templates]"), "{written}");
+    assert_eq!(
+        std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+        1,
+        "一時ファイルが残っている"
+    );
+
+    let reloaded = Config::load(&path).unwrap();
+    assert_eq!(reloaded.migrated_from, None);
+    assert_eq!(reloaded.icloud, config.icloud);
+    assert_eq!(reloaded.templates, config.templates);
+    assert_eq!(reloaded.reminders, Reminders::new(&[60, 15]));
+    std::fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).ok();
+}
@@ -0,0 +1,219 @@
+mod support;
+
+use serde_json::json;
+use shift_sync_rc::google::sync::GoogleBackend;
+use shift_sync_rc::google::{GoogleClient, GoogleEvent, HASH_PROPERTY, UID_PROPERTY};
+use shift_sync_rc::shift::Shift;
+use shift_sync_rc::sync::sync_shifts;
+use support::google::MockGoogle;
+use support::{january, shift};
+
+const CALENDAR: &str = "shifts@group.calendar.google.com";
+
+fn mock() -> MockGoogle {
+    MockGoogle::start("token-1")
+        .with_calendar("user01@gmail.com", "user01@gmail.com", "owner")
+        .with_calendar(CALENDAR, "バイト", "writer")
+        .with_calendar(
+            "ja.japanese#holiday@group.v.calendar.google.com",
+            "日本の祝日",
+            "reader",
+        )
+}
+
+fn client(server: &MockGoogle) -> GoogleClient {
+    GoogleClient::with_base_url(&server.base_url(), "token-1").unwrap()
+}
+
+/// サーバー上のシフトのイベントの UID（並び順はイベントID順）
+fn stored_uids(server: &MockGoogle) -> Vec<String> {
+    server
+        .events(CALENDAR)
+        .iter()
+        .filter_map(|e| e["extendedProperties"]["private"][UID_PROPERTY].as_str())
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn lists_calendars_across_pages() {
+    let server = mock();
+    let calendars = client(&server).list_calendars().unwrap();
+    let ids: Vec<&str> = calendars.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            "user01@gmail.com",
+            CALENDAR,
+            "ja.japanese#holiday@group.v.calendar.google.com"
+        ]
+    );
+    assert!(calendars[1].is_writable());
+    assert!(!calendars[2].is_writable());
+    assert_eq!(server.count("GET"), 2);
+}
+
+#[test]
+fn wrong_token_is_unauthorized() {
+    let server = mock();
+    let client = GoogleClient::with_base_url(&server.base_url(), "expired").unwrap();
+    let err = client.list_calendars().unwrap_err();
+    assert!(err.is_unauthorized(), "{err}");
+}
+
+#[test]
+fn sync_writes_marked_events_and_pages_through_listing() {
+    let server = mock();
+    let client = client(&server);
+    let early = shift(2026, 1, 5, "10:00", "19:00");
+    let shifts = [
+        early.clone(),
+        shift(2026, 1, 20, "10:00", "19:00"),
+        shift(2026, 1, 31, "22:00", "06:00"),
+    ];
+    sync_shifts(
+        &mut GoogleBackend::new(&client, CALENDAR),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+    assert_eq!(
+        stored_uids(&server),
+        shifts.iter().map(Shift::uid).collect::<Vec<_>>()
+    );
+    let last = &server.events(CALENDAR)[2];
+    assert_eq!(last["end"]["dateTime"], "2026-02-01T06:00:00+09:00");
+    assert_eq!(last["location"], "渋谷店");
+    assert!(
+        last["extendedProperties"]["private"][HASH_PROPERTY]
+            .as_str()
+            .is_some()
+    );
+
+    // 他のアプリの予定は印がないので消さない
+    server.insert_event(
+        CALENDAR,
+        json!({
+            "summary": "歯医者",
+            "start": {"dateTime": "2026-01-20T15:00:00+09:00"},
+            "end": {"dateTime": "2026-01-20T16:00:00+09:00"},
+        }),
+    );
+
+    // 4件あるので一覧は2ページに分かれる。変わった1件は PUT、消えた2件は DELETE
+    server.clear_requests();
+    let early = early.with_memo("早番");
+    let summary = sync_shifts(
+        &mut GoogleBackend::new(&client, CALENDAR),
+        std::slice::from_ref(&early),
+        january(),
+    )
+    .unwrap();
+    assert_eq!((summary.updated, summary.deleted), (1, 2));
+    assert_eq!(server.count("GET"), 2);
+    assert_eq!((server.count("PUT"), server.count("DELETE")), (1, 2));
+    assert_eq!(stored_uids(&server), [early.uid()]);
+    let events = server.events(CALENDAR);
+    assert_eq!(
+        events[0]["description"],
+        format!("shift-uid:{}\n早番", early.uid())
+    );
+    assert_eq!(events[1]["summary"], "歯医者");
+}
+
+#[test]
+fn sync_detects_edits_by_stored_hash() {
+    let server = mock();
+    let client = client(&server);
+    let shifts = [shift(2026, 1, 5, "10:00", "19:00")];
+    sync_shifts(
+        &mut GoogleBackend::new(&client, CALENDAR),
+        &shifts,
+        january(),
+    )
+    .unwrap();
+
+    // 電話のカレンダーで直すと、書いたときの HASH_PROPERTY と中身が合わなくなる
+    let id = server.events(CALENDAR)[0]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    server.edit_event(CALENDAR, &id, |event| {
+        event["summary"] = json!("バイト（代打あり）");
+    });
+
+    let moved = [shifts[0].clone().with_memo("シフト変更")];
+    let summary = sync_shifts(
+        &mut GoogleBackend::new(&client, CALENDAR),
+        &moved,
+        january(),
+    )
+    .unwrap();
+    assert_eq!(summary.conflicts.len(), 1);
+    assert_eq!(server.count("PUT"), 0);
+    assert_eq!(server.events(CALENDAR)[0]["summary"], "バイト（代打あり）");
+}
+
+#[test]
+fn sync_adopts_events_from_swift_app() {
+    let server = mock();
+    let client = client(&server);
+    let s = shift(2026, 1, 5, "10:00", "19:00");
+    // Swift版は説明に shift-uid: を書き、extendedProperties は使わない
+    server.insert_event(
+        CALENDAR,
+        json!({
+            "summary": "バイト",
+            "location": "渋谷店",
+            "description": format!("shift-uid:{}", s.uid()),
+            "start": {"dateTime": "2026-01-05T01:00:00Z", "timeZone": "Asia/Tokyo"},
+            "end": {"dateTime": "2026-01-05T10:00:00Z", "timeZone": "Asia/Tokyo"},
+        }),
+    );
+
+    let summary = sync_shifts(
+        &mut GoogleBackend::new(&client, CALENDAR),
+        std::slice::from_ref(&s),
+        january(),
+    )
+    .unwrap();
+    assert_eq!(summary.short_description(), "変更なし");
+    assert_eq!(server.count("POST"), 0);
+
+    // 内容が変われば印を付けて上書きする（記録がないので編集扱いにはしない）
+    let summary = sync_shifts(
+        &mut GoogleBackend::new(&client, CALENDAR),
+        &[s.with_memo("レジ")],
+        january(),
+    )
+    .unwrap();
+    assert_eq!(summary.updated, 1);
+    assert_eq!(stored_uids(&server).len(), 1);
+}
+
+#[test]
+fn stale_etag_is_a_conflict() {
+    let server = mock();
+    let client = client(&server);
+    let event = GoogleEvent::from_shift(&shift(2026, 1, 5, "10:00", "19:00"));
+    let created = client.insert_event(CALENDAR, &event).unwrap();
+    let etag = created.etag.clone().unwrap();
+
+    let updated = client
+        .update_event(CALENDAR, &created.id, &event, Some(&etag))
+        .unwrap();
+    let err = client
+        .update_event(CALENDAR, &created.id, &event, Some(&etag))
+        .unwrap_err();
+    assert!(err.is_conflict(), "{err}");
+    let err = client
+        .delete_event(CALENDAR, &created.id, Some(&etag))
+        .unwrap_err();
+    assert!(err.is_conflict(), "{err}");
+
+    client
+        .delete_event(CALENDAR, &created.id, updated.etag.as_deref())
+        .unwrap();
+    // すでに消えていれば成功扱い
+    client.delete_event(CALENDAR, &created.id, None).unwrap();
+}
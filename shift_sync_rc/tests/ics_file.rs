@@ -0,0 +1,167 @@
+mod support;
+
+use shift_sync_rc::backend::ics_file::IcsFileBackend;
+use shift_sync_rc::ics::{
+    IcsOptions, TimeFormat, event_properties, generate_ics, generate_ics_with,
+};
+use shift_sync_rc::sync::sync_shifts;
+use support::{january, shift};
+
+/// 他のアプリの予定・タイムゾーン・カレンダー名（折り返し入り）
+const FOREIGN: &str = "BEGIN:VTIMEZONE\r\nTZID:Asia/Tokyo\r\nBEGIN:STANDARD\r\n\
+DTSTART:19700101T000000\r\nTZOFFSETFROM:+0900\r\nTZOFFSETTO:+0900\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\n\
+BEGIN:VEVENT\r\nUID:dentist@example.com\r\nDTSTAMP:20251201T000000Z\r\n\
+DTSTART;TZID=Asia/Tokyo:20260115T090000\r\nDTEND;TZID=Asia/Tokyo:20260115T093000\r\n\
+SUMMARY:歯医者（シフトの前に\r\n 寄る）\r\nBEGIN:VALARM\r\nACTION:DISPLAY\r\nTRIGGER:-PT10M\r\nEND:VALARM\r\nEND:VEVENT\r\n";
+
+/// ファイルにある VEVENT の UID
+fn uids(ics: &str) -> Vec<String> {
+    ics.split("BEGIN:VEVENT")
+        .skip(1)
+        .filter_map(|event| {
+            event_properties(&format!("BEGIN:VEVENT{event}"))
+                .into_iter()
+                .find(|(name, _)| name == "UID")
+                .map(|(_, uid)| uid)
+        })
+        .collect()
+}
+
+#[test]
+fn merges_into_existing_file_and_keeps_foreign_events() {
+    let path = support::temp_path("ics_merge", "shifts.ics");
+    let kept = shift(2026, 1, 5, "10:00", "19:00");
+    let changed = shift(2026, 1, 6, "10:00", "19:00");
+    let removed = shift(2026, 1, 20, "10:00", "19:00");
+    let history = shift(2025, 12, 20, "10:00", "19:00");
+
+    // Go版 / generate_ics で書き出したファイルに、他のアプリの予定を足したもの
+    let exported = generate_ics(&[
+        history.clone(),
+        kept.clone(),
+        changed.clone(),
+        removed.clone(),
+    ]);
+    let existing = exported.replace(
+        "METHOD:PUBLISH\r\n",
+        &format!("METHOD:PUBLISH\r\nX-WR-CALNAME:バイト\r\n{FOREIGN}"),
+    );
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, &existing).unwrap();
+
+    let added = shift(2026, 1, 31, "22:00", "06:00");
+    let shifts = [
+        kept.clone(),
+        changed.clone().with_memo("早番"),
+        added.clone(),
+    ];
+    let mut backend = IcsFileBackend::open(&path).unwrap();
+    let summary = sync_shifts(&mut backend, &shifts, january()).unwrap();
+    assert_eq!(summary.short_description(), "+1 ↻1 -1");
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.contains(FOREIGN), "{written}");
+    assert!(written.contains("\r\nX-WR-CALNAME:バイト\r\n"));
+    assert_eq!(
+        uids(&written),
+        [
+            "dentist@example.com".to_string(),
+            history.uid(),
+            kept.uid(),
+            changed.uid(),
+            added.uid(),
+        ]
+    );
+    // 変わっていないシフトは書き出したときの行のまま
+    let kept_event = exported
+        .split("BEGIN:VEVENT")
+        .find(|e| e.contains(&kept.uid()))
+        .unwrap();
+    assert!(written.contains(kept_event));
+    assert!(written.contains("DESCRIPTION:早番"));
+    let files: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(files, ["shifts.ics"], "一時ファイルが残っている");
+
+    // もう一度同期しても何も変わらない
+    let mut backend = IcsFileBackend::open(&path).unwrap();
+    let summary = sync_shifts(&mut backend, &shifts, january()).unwrap();
+    assert_eq!(summary.short_description(), "変更なし");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), written);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[test]
+fn rewrites_floating_times_with_timezone() {
+    let path = support::temp_path("ics_zones", "shifts.ics");
+    let s = shift(2026, 1, 5, "10:00", "19:00");
+    // Go版と同じフローティングの時刻で書かれたファイル
+    let floating = IcsOptions {
+        times: TimeFormat::Floating,
+        ..IcsOptions::default()
+    };
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(
+        &path,
+        generate_ics_with(std::slice::from_ref(&s), chrono::Utc::now(), &floating),
+    )
+    .unwrap();
+
+    let mut backend = IcsFileBackend::open(&path).unwrap();
+    let summary = sync_shifts(&mut backend, std::slice::from_ref(&s), january()).unwrap();
+    assert_eq!(summary.updated, 1);
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(written.matches("BEGIN:VTIMEZONE").count(), 1);
+    assert!(written.contains("\r\nTZID:Asia/Tokyo\r\nX-SHIFT-SYNC:Asia/Tokyo\r\n"));
+    assert!(written.contains("\r\nDTSTART;TZID=Asia/Tokyo:20260105T100000\r\n"));
+    assert!(written.find("BEGIN:VTIMEZONE") < written.find("BEGIN:VEVENT"));
+
+    let mut backend = IcsFileBackend::open(&path).unwrap();
+    let summary = sync_shifts(&mut backend, std::slice::from_ref(&s), january()).unwrap();
+    assert_eq!(summary.short_description(), "変更なし");
+
+    // UTC で書き直すと、このツールが置いた VTIMEZONE は要らなくなる
+    let utc = IcsOptions {
+        times: TimeFormat::Utc,
+        ..IcsOptions::default()
+    };
+    let mut backend = IcsFileBackend::open(&path).unwrap().with_options(utc);
+    let summary = sync_shifts(&mut backend, std::slice::from_ref(&s), january()).unwrap();
+    assert_eq!(summary.updated, 1);
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(!written.contains("VTIMEZONE"));
+    assert!(written.contains("\r\nDTSTART:20260105T010000Z\r\n"));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[test]
+fn creates_missing_file() {
+    let path = support::temp_path("ics_create", "shifts.ics");
+    let s = shift(2026, 1, 5, "10:00", "19:00");
+    let mut backend = IcsFileBackend::open(&path).unwrap();
+    sync_shifts(&mut backend, std::slice::from_ref(&s), january()).unwrap();
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:"));
+    assert!(written.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    assert_eq!(uids(&written), [s.uid()]);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[test]
+fn refuses_files_that_are_not_icalendar() {
+    let path = support::temp_path("ics_invalid", "shifts.ics");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "date,start,end\n").unwrap();
+    let err = IcsFileBackend::open(&path).err().unwrap();
+    assert!(
+        err.to_string().contains("iCalendar として読めない"),
+        "{err}"
+    );
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
@@ -0,0 +1,186 @@
+mod support;
+
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use shift_sync_rc::google::oauth::{OAuthClient, OAuthError, Token, TokenStore};
+use support::oauth::{MockAuthServer, REFRESH_TOKEN, USER_CODE};
+
+fn client(server: &MockAuthServer) -> OAuthClient {
+    OAuthClient::new(server.config())
+}
+
+/// ブラウザの代わりに認可 URL を開き、リダイレクトをたどって表示されたページを返す
+fn open_browser(url: &str) -> thread::JoinHandle<String> {
+    let url = url.to_string();
+    thread::spawn(move || {
+        ureq::get(&url)
+            .call()
+            .and_then(|mut resp| resp.body_mut().read_to_string())
+            .unwrap_or_default()
+    })
+}
+
+fn temp_store(name: &str) -> TokenStore {
+    let dir = std::env::temp_dir().join(format!("shift_sync_oauth_{}", std::process::id()));
+    TokenStore::new(dir.join(name))
+}
+
+#[test]
+fn device_flow_polls_until_authorized() {
+    let server = MockAuthServer::start("cli-app")
+        .with_pending(2)
+        .with_slow_down();
+    let client = client(&server);
+    let device = client.start_device().unwrap();
+    assert_eq!(device.user_code, USER_CODE);
+    assert_eq!(device.verification_uri, "https://www.google.com/device");
+
+    let mut waits = Vec::new();
+    let token = client
+        .poll_device(&device, |d| waits.push(d.as_secs()))
+        .unwrap();
+    assert_eq!(token.access_token, "access-1");
+    assert_eq!(token.refresh_token.as_deref(), Some(REFRESH_TOKEN));
+    assert!(token.expires_at.is_some());
+    // slow_down の後は間隔を 5 秒延ばす
+    assert_eq!(waits, [5, 10, 10, 10]);
+}
+
+#[test]
+fn device_flow_reports_denial() {
+    let server = MockAuthServer::start("cli-app").denying();
+    let client = client(&server);
+    let device = client.start_device().unwrap();
+    let err = client.poll_device(&device, |_| {}).unwrap_err();
+    assert!(matches!(err, OAuthError::Denied), "{err}");
+}
+
+#[test]
+fn unknown_client_is_a_server_error() {
+    let server = MockAuthServer::start("cli-app");
+    let mut config = server.config();
+    config.client_id = "other".to_string();
+    let err = OAuthClient::new(config).start_device().unwrap_err();
+    assert!(
+        matches!(&err, OAuthError::Server { error, .. } if error == "invalid_client"),
+        "{err}"
+    );
+}
+
+#[test]
+fn loopback_flow_exchanges_code_with_pkce() {
+    let server = MockAuthServer::start("cli-app");
+    let client = client(&server);
+    let login = client.start_loopback().unwrap();
+    assert!(login.redirect_uri().starts_with("http://127.0.0.1:"));
+
+    let browser = open_browser(login.authorize_url());
+    let token = login.finish(&client).unwrap();
+    assert_eq!(token.access_token, "access-1");
+    assert_eq!(token.refresh_token.as_deref(), Some(REFRESH_TOKEN));
+    assert_eq!(server.grants(), ["authorization_code"]);
+    assert!(browser.join().unwrap().contains("ログインできました"));
+}
+
+#[test]
+fn loopback_flow_rejects_forged_state() {
+    let server = MockAuthServer::start("cli-app").tampering_state();
+    let client = client(&server);
+    let login = client.start_loopback().unwrap();
+    let browser = open_browser(login.authorize_url());
+    let err = login.finish(&client).unwrap_err();
+    assert!(matches!(err, OAuthError::StateMismatch), "{err}");
+    assert!(browser.join().unwrap().contains("ログインできませんでした"));
+    // コードの交換まで進まない
+    assert!(server.grants().is_empty());
+}
+
+#[test]
+fn loopback_flow_reports_denial() {
+    let server = MockAuthServer::start("cli-app").denying();
+    let client = client(&server);
+    let login = client.start_loopback().unwrap();
+    let browser = open_browser(login.authorize_url());
+    let err = login.finish(&client).unwrap_err();
+    assert!(matches!(err, OAuthError::Denied), "{err}");
+    browser.join().unwrap();
+}
+
+#[test]
+fn loopback_flow_times_out_without_redirect() {
+    let server = MockAuthServer::start("cli-app");
+    let client = client(&server);
+    let login = client
+        .start_loopback()
+        .unwrap()
+        .with_timeout(Duration::from_millis(300));
+    // 接続だけして何も送らないブラウザ
+    let addr = login
+        .redirect_uri()
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    let _idle = TcpStream::connect(addr).unwrap();
+    let started = Instant::now();
+    let err = login.finish(&client).unwrap_err();
+    assert!(matches!(err, OAuthError::TimedOut(_)), "{err}");
+    assert!(started.elapsed() < Duration::from_secs(3));
+    assert!(server.grants().is_empty());
+}
+
+#[test]
+fn stored_token_is_refreshed_when_expired() {
+    let server = MockAuthServer::start("cli-app");
+    let client = client(&server);
+    let store = temp_store("refresh.json");
+    assert_eq!(client.stored_token(&store).unwrap(), None);
+
+    let now = chrono::Utc::now().timestamp();
+    let fresh = Token {
+        access_token: "still-valid".to_string(),
+        refresh_token: Some(REFRESH_TOKEN.to_string()),
+        expires_at: Some(now + 3600),
+        scope: None,
+    };
+    store.save(&fresh).unwrap();
+    assert_eq!(client.stored_token(&store).unwrap(), Some(fresh.clone()));
+    assert!(server.grants().is_empty());
+
+    let expired = Token {
+        expires_at: Some(now - 10),
+        ..fresh
+    };
+    store.save(&expired).unwrap();
+    let refreshed = client.stored_token(&store).unwrap().unwrap();
+    assert_eq!(refreshed.access_token, "access-1");
+    // リフレッシュトークンは引き継いで保存し直す
+    assert_eq!(refreshed.refresh_token.as_deref(), Some(REFRESH_TOKEN));
+    assert_eq!(store.load().unwrap(), Some(refreshed));
+    assert_eq!(server.grants(), ["refresh_token"]);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(store.path())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    store.clear().unwrap();
+    assert_eq!(store.load().unwrap(), None);
+}
+
+#[test]
+fn refresh_without_refresh_token_fails() {
+    let server = MockAuthServer::start("cli-app");
+    let token = Token {
+        access_token: "a".to_string(),
+        refresh_token: None,
+        expires_at: Some(0),
+        scope: None,
+    };
+    let err = client(&server).refresh(&token).unwrap_err();
+    assert!(matches!(err, OAuthError::NoRefreshToken), "{err}");
+}
@@ -0,0 +1,61 @@
+mod support;
+
+use shift_sync_rc::month::YearMonth;
+use shift_sync_rc::parser::{ParseError, parse_shifts_for};
+use shift_sync_rc::shift::DEFAULT_TZ;
+use shift_sync_rc::shiftweb::{ShiftWebClient, ShiftWebError};
+use support::shiftweb::MockShiftWeb;
+
+const JANUARY: &str = include_str!("fixtures/shift_2026_01.html");
+
+fn january() -> YearMonth {
+    YearMonth::new(2026, 1).unwrap()
+}
+
+#[test]
+fn login_fetch_and_parse_end_to_end() {
+    let site = MockShiftWeb::start("user01", "p@ss word").with_page("2026-01", JANUARY);
+    let client = ShiftWebClient::new(site.base_url());
+    client.login("user01", "p@ss word").unwrap();
+
+    let html = client.fetch_month(january()).unwrap();
+    let page = parse_shifts_for(&html, january(), DEFAULT_TZ).unwrap();
+    assert_eq!(page.shifts.len(), 5);
+    assert_eq!(page.shifts[0].uid(), "shift-20260105-1000-1900-16011f8e");
+
+    let empty = client.fetch_month(january().add_months(1)).unwrap();
+    let page = parse_shifts_for(&empty, january().add_months(1), DEFAULT_TZ).unwrap();
+    assert!(page.shifts.is_empty());
+
+    assert_eq!(
+        site.requests(),
+        [
+            "GET /login.php?err=1",
+            "POST /cont/login/check_login.php?user01",
+            "GET /shift.php?mod=look&date2=2026-01",
+            "GET /shift.php?mod=look&date2=2026-02",
+        ]
+    );
+}
+
+#[test]
+fn wrong_password_is_rejected() {
+    let site = MockShiftWeb::start("user01", "secret");
+    let client = ShiftWebClient::new(site.base_url());
+    let err = client.login("user01", "wrong").unwrap_err();
+    assert!(
+        matches!(err, ShiftWebError::Status { status: 401, .. }),
+        "{err}"
+    );
+}
+
+#[test]
+fn fetching_without_login_gets_the_login_page() {
+    let site = MockShiftWeb::start("user01", "secret").with_page("2026-01", JANUARY);
+    let client = ShiftWebClient::new(site.base_url());
+    let html = client.fetch_month(january()).unwrap();
+    assert_eq!(
+        parse_shifts_for(&html, january(), DEFAULT_TZ).unwrap_err(),
+        ParseError::TableNotFound
+    );
+}
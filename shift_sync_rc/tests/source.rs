@@ -0,0 +1,192 @@
+mod support;
+
+use shift_sync_rc::ics::IcsError;
+use shift_sync_rc::month::YearMonth;
+use shift_sync_rc::shift::DEFAULT_TZ;
+use shift_sync_rc::shiftweb::ShiftWebClient;
+use shift_sync_rc::source::csv_file::CsvSource;
+use shift_sync_rc::source::ics_feed::IcsFeedSource;
+use shift_sync_rc::source::shiftweb::ShiftWebSource;
+use shift_sync_rc::source::{LabeledSource, ShiftSource, SourceError, SourceReport, fetch_all};
+use support::shiftweb::MockShiftWeb;
+use support::{MockResponse, MockServer};
+
+const JANUARY: &str = include_str!("fixtures/shift_2026_01.html");
+
+const FEED: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Example//Shifts//JA\r\n\
+BEGIN:VEVENT\r\nUID:ev-1@example.com\r\nDTSTART:20260110T000000Z\r\nDTEND:20260110T050000Z\r\n\
+SUMMARY:カフェ\r\nLOCATION:駅前店\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:ev-2@example.com\r\nDTSTART;TZID=Asia/Tokyo:20260301T090000\r\n\
+DTEND;TZID=Asia/Tokyo:20260301T150000\r\nSUMMARY:カフェ\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+fn january() -> Vec<YearMonth> {
+    vec![YearMonth::new(2026, 1).unwrap()]
+}
+
+fn temp_csv(name: &str, text: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("shift_sync_{name}_{}.csv", std::process::id()));
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn merges_sources_with_their_labels() {
+    let site = MockShiftWeb::start("user01", "pw").with_page("2026-01", JANUARY);
+    let csv = temp_csv(
+        "merge",
+        "date,start,end,location\n\
+         2026-01-05,20:00,23:00,本店\n\
+         2026-01-31,22:00,06:00,本店\n\
+         2026-02-01,10:00,15:00,本店\n",
+    );
+    let shiftweb = ShiftWebSource::new(
+        ShiftWebClient::new(site.base_url()),
+        "user01",
+        "pw",
+        DEFAULT_TZ,
+    );
+    let mut sources = [
+        LabeledSource::new(shiftweb),
+        LabeledSource::new(CsvSource::new(&csv, DEFAULT_TZ))
+            .title("🍔 マック")
+            .location("渋谷店"),
+    ];
+
+    let fetched = fetch_all(&mut sources, &january()).unwrap();
+    std::fs::remove_file(&csv).ok();
+
+    // 2月のシフトは取得範囲の外なので入らない
+    assert_eq!(fetched.shifts.len(), 5 + 2);
+    assert!(
+        fetched
+            .shifts
+            .windows(2)
+            .all(|w| w[0].start() <= w[1].start())
+    );
+    assert_eq!(fetched.reports.len(), 1);
+    assert!(
+        matches!(&fetched.reports[0], SourceReport::Page { month, .. } if *month == january()[0])
+    );
+
+    let second_job: Vec<_> = fetched
+        .shifts
+        .iter()
+        .filter(|s| s.title == "🍔 マック")
+        .collect();
+    assert_eq!(second_job.len(), 2);
+    assert!(second_job.iter().all(|s| s.location == "渋谷店"));
+    assert_eq!(second_job[0].time_range_string(), "20:00 - 23:00");
+    // 同じ日の ShiftWeb のシフトも残る
+    assert!(
+        fetched
+            .shifts
+            .iter()
+            .any(|s| s.uid() == "shift-20260105-1000-1900-16011f8e")
+    );
+}
+
+#[test]
+fn reads_ics_feed_over_http() {
+    let server = MockServer::start(|req| match req.path() {
+        "/feed.ics" => MockResponse::new(200, FEED).header("Content-Type", "text/calendar"),
+        _ => MockResponse::new(404, ""),
+    });
+    let mut feed = IcsFeedSource::new(&format!("{}/feed.ics", server.base_url()), DEFAULT_TZ);
+    let fetched = feed.fetch(&january()).unwrap();
+    assert_eq!(fetched.shifts.len(), 1);
+    let shift = &fetched.shifts[0];
+    assert_eq!(
+        (shift.title.as_str(), shift.location.as_str()),
+        ("カフェ", "駅前店")
+    );
+    assert_eq!(shift.date_string(), "1/10");
+    assert_eq!(shift.time_range_string(), "09:00 - 14:00");
+
+    let mut missing = IcsFeedSource::new(&format!("{}/gone.ics", server.base_url()), DEFAULT_TZ);
+    assert!(matches!(
+        missing.fetch(&january()),
+        Err(SourceError::Status { status: 404, .. })
+    ));
+}
+
+#[test]
+fn ics_feed_reports_bad_events_only_in_requested_months() {
+    let feed = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\nUID:old@example.com\r\nDTSTART:20190401T090000\r\nDTEND:20190401T0900xx\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:mark@example.com\r\nDTSTART:20260105T090000\r\nDTEND:20260105T090000\r\n\
+SUMMARY:締め日\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:bad@example.com\r\nDTSTART:20260112T190000\r\nDTEND:20260112T100000\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:weekly@example.com\r\nDTSTART:20251201T170000\r\nDTEND:20251201T220000\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:ended@example.com\r\nDTSTART:20190101T170000\r\nDTEND:20190101T220000\r\n\
+RRULE:FREQ=WEEKLY;UNTIL=20191231T235959Z\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:later@example.com\r\nDTSTART:20260401T170000\r\nDTEND:20260401T220000\r\n\
+RRULE:FREQ=DAILY\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:extra@example.com\r\nDTSTART:20251101T170000\r\nDTEND:20251101T220000\r\n\
+RDATE:20251108T170000,20251115T170000\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:ok@example.com\r\nDTSTART:20260120T100000\r\nDTEND:20260120T150000\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+    let server = MockServer::start(move |_| MockResponse::new(200, feed));
+    let url = format!("{}/feed.ics", server.base_url());
+    let fetched = IcsFeedSource::new(&url, DEFAULT_TZ)
+        .fetch(&january())
+        .unwrap();
+
+    assert_eq!(fetched.shifts.len(), 1);
+    assert_eq!(fetched.shifts[0].date_string(), "1/20");
+    let [
+        SourceReport::Events {
+            url: reported,
+            skipped,
+        },
+    ] = fetched.reports.as_slice()
+    else {
+        panic!("{:?}", fetched.reports);
+    };
+    assert_eq!(*reported, url);
+    let uids: Vec<&str> = skipped
+        .iter()
+        .map(|err| match err {
+            IcsError::Shift { uid, .. } | IcsError::Recurring { uid } => uid.as_str(),
+            other => panic!("{other}"),
+        })
+        .collect();
+    assert_eq!(uids, ["bad@example.com", "weekly@example.com"]);
+    assert!(fetched.reports[0].has_problems());
+}
+
+#[test]
+fn csv_reports_bad_rows_only_in_requested_months() {
+    let csv = temp_csv(
+        "bad_rows",
+        "date,start,end\n\
+         2019-04-01,10時,19:00\n\
+         2026-01-05,10:00,19:00\n\
+         2026-01-06,10:00,\n\
+         1月7日,10:00,19:00\n\
+         2026-02-01,25:00:00,26:00\n",
+    );
+    let fetched = CsvSource::new(&csv, DEFAULT_TZ).fetch(&january());
+    std::fs::remove_file(&csv).ok();
+
+    let fetched = fetched.unwrap();
+    assert_eq!(fetched.shifts.len(), 1);
+    let [SourceReport::Rows { path, skipped }] = fetched.reports.as_slice() else {
+        panic!("{:?}", fetched.reports);
+    };
+    assert_eq!(*path, csv);
+    let lines: Vec<u64> = skipped.iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, [4, 5]);
+    assert!(fetched.reports[0].has_problems());
+}
+
+#[test]
+fn missing_csv_is_an_io_error() {
+    let path = std::env::temp_dir().join("shift_sync_no_such_file.csv");
+    let err = CsvSource::new(&path, DEFAULT_TZ)
+        .fetch(&january())
+        .unwrap_err();
+    assert!(matches!(err, SourceError::Io { .. }));
+    assert!(err.to_string().contains("shift_sync_no_such_file.csv"));
+}
@@ -0,0 +1,561 @@
+//! CalDAV サーバーのモック
+//!
+//! Basic 認証、`/.well-known/caldav` のリダイレクト、principal → calendar-home-set →
+//! カレンダー一覧の PROPFIND と、イベントの GET / PUT / DELETE に答える。
+//! PUT / DELETE は If-Match / If-None-Match を見て、合わなければ 412 を返す。
+//! REPORT は sync-collection（RFC 6578）、VEVENT の time-range だけの calendar-query、
+//! calendar-multiget に答える。
+//! MKCALENDAR はカレンダーホーム直下にだけカレンダーを作る（既にあれば 405）。
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{NaiveDateTime, TimeDelta};
+
+use super::{MockRequest, MockResponse, MockServer};
+
+pub const PRINCIPAL: &str = "/principals/user01/";
+pub const HOME: &str = "/calendars/user01/";
+
+/// モック上のカレンダー
+#[derive(Debug, Clone, Default)]
+pub struct MockCalendar {
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    /// `calendar-timezone` の中身（VTIMEZONE 入りのカレンダーオブジェクト）
+    pub timezone: Option<String>,
+    pub components: Vec<String>,
+    /// リソース名（"shift-....ics"）-> イベント
+    pub events: BTreeMap<String, MockEvent>,
+}
+
+/// モック上のイベントリソース
+#[derive(Debug, Clone)]
+pub struct MockEvent {
+    pub body: String,
+    pub etag: String,
+}
+
+impl MockCalendar {
+    pub fn new(display_name: &str) -> Self {
+        MockCalendar {
+            display_name: Some(display_name.to_string()),
+            components: vec!["VEVENT".to_string()],
+            ..MockCalendar::default()
+        }
+    }
+
+    pub fn color(mut self, color: &str) -> Self {
+        self.color = Some(color.to_string());
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn components(mut self, components: &[&str]) -> Self {
+        self.components = components.iter().map(|c| c.to_string()).collect();
+        self
+    }
+}
+
+#[derive(Default)]
+struct State {
+    authorization: String,
+    /// パス（"/calendars/user01/work/"）-> カレンダー
+    calendars: BTreeMap<String, MockCalendar>,
+    /// ETag の連番
+    next_etag: u64,
+    /// 変更の連番（sync-token の中身）と、変更されたイベントのパス
+    changes: Vec<(u64, String)>,
+    /// これより古い sync-token は無効
+    oldest_token: u64,
+    /// 受け付けたリクエスト（"PROPFIND /calendars/user01/" の形式。REPORT は末尾に種類が付く）
+    log: Vec<String>,
+}
+
+pub struct MockCalDav {
+    server: MockServer,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockCalDav {
+    pub fn start(username: &str, password: &str) -> Self {
+        let state = Arc::new(Mutex::new(State {
+            authorization: format!("Basic {}", BASE64.encode(format!("{username}:{password}"))),
+            ..State::default()
+        }));
+        let handler_state = Arc::clone(&state);
+        let server = MockServer::start(move |req| handle(&mut handler_state.lock().unwrap(), req));
+        MockCalDav { server, state }
+    }
+
+    /// カレンダーホーム直下に `slug` のカレンダーを置く
+    pub fn with_calendar(self, slug: &str, calendar: MockCalendar) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .calendars
+            .insert(format!("{HOME}{slug}/"), calendar);
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.server.base_url()
+    }
+
+    /// カレンダーの絶対 URL
+    pub fn calendar_url(&self, slug: &str) -> String {
+        format!("{}{HOME}{slug}/", self.base_url())
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().log.clone()
+    }
+
+    /// 受け付けたリクエストのうち `method` のものの件数
+    pub fn count(&self, method: &str) -> usize {
+        let prefix = format!("{method} ");
+        self.requests()
+            .iter()
+            .filter(|r| r.starts_with(&prefix))
+            .count()
+    }
+
+    /// 受け付けた `kind`（"calendar-query" など）の REPORT の件数
+    pub fn count_report(&self, kind: &str) -> usize {
+        self.requests()
+            .iter()
+            .filter(|r| r.starts_with("REPORT ") && r.ends_with(kind))
+            .count()
+    }
+
+    pub fn clear_requests(&self) {
+        self.state.lock().unwrap().log.clear();
+    }
+
+    /// イベントをサーバー側に直接置く（スマホで作った・編集した想定）
+    pub fn put_event(&self, slug: &str, name: &str, body: &str) {
+        let mut state = self.state.lock().unwrap();
+        let calendar = format!("{HOME}{slug}/");
+        assert!(state.calendars.contains_key(&calendar), "calendar");
+        state.store(&calendar, name, body);
+    }
+
+    /// イベントをサーバー側で直接消す
+    pub fn delete_event(&self, slug: &str, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.remove(&format!("{HOME}{slug}/"), name);
+    }
+
+    /// これまでに発行した sync-token をすべて無効にする
+    pub fn invalidate_sync_tokens(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.record_change("", "");
+        state.oldest_token = state.sync_seq();
+    }
+
+    /// `url`（絶対 URL）のカレンダー
+    pub fn calendar_at(&self, url: &str) -> Option<MockCalendar> {
+        let path = url.strip_prefix(self.base_url())?;
+        self.state.lock().unwrap().calendars.get(path).cloned()
+    }
+
+    pub fn event(&self, slug: &str, name: &str) -> Option<MockEvent> {
+        self.state.lock().unwrap().calendars[&format!("{HOME}{slug}/")]
+            .events
+            .get(name)
+            .cloned()
+    }
+
+    pub fn event_names(&self, slug: &str) -> Vec<String> {
+        self.state.lock().unwrap().calendars[&format!("{HOME}{slug}/")]
+            .events
+            .keys()
+            .cloned()
+            .collect()
+    }
+}
+
+impl State {
+    /// イベントを置いて、新しい ETag を返す。作成なら true も返す
+    fn store(&mut self, calendar: &str, name: &str, body: &str) -> (String, bool) {
+        self.next_etag += 1;
+        let etag = format!("\"etag-{}\"", self.next_etag);
+        let events = &mut self.calendars.get_mut(calendar).unwrap().events;
+        let created = events
+            .insert(
+                name.to_string(),
+                MockEvent {
+                    body: body.to_string(),
+                    etag: etag.clone(),
+                },
+            )
+            .is_none();
+        self.record_change(calendar, name);
+        (etag, created)
+    }
+
+    fn remove(&mut self, calendar: &str, name: &str) -> bool {
+        let removed = self
+            .calendars
+            .get_mut(calendar)
+            .unwrap()
+            .events
+            .remove(name)
+            .is_some();
+        if removed {
+            self.record_change(calendar, name);
+        }
+        removed
+    }
+
+    fn record_change(&mut self, calendar: &str, name: &str) {
+        let seq = self.sync_seq() + 1;
+        self.changes.push((seq, format!("{calendar}{name}")));
+    }
+
+    fn sync_seq(&self) -> u64 {
+        self.changes.last().map_or(0, |(seq, _)| *seq)
+    }
+
+    fn sync_token(&self) -> String {
+        format!("http://mock.example/sync/{}", self.sync_seq())
+    }
+
+    /// "/calendars/user01/work/shift-x.ics" -> ("/calendars/user01/work/", "shift-x.ics")
+    fn split_event_path<'a>(&self, path: &'a str) -> Option<(String, &'a str)> {
+        let (dir, name) = path.rsplit_once('/')?;
+        let dir = format!("{dir}/");
+        (!name.is_empty() && self.calendars.contains_key(&dir)).then_some((dir, name))
+    }
+}
+
+fn handle(state: &mut State, req: &MockRequest) -> MockResponse {
+    let mut entry = format!("{} {}", req.method, req.url);
+    if req.method == "REPORT" {
+        for kind in ["sync-collection", "calendar-query", "calendar-multiget"] {
+            if req.body.contains(kind) {
+                entry = format!("{entry} {kind}");
+            }
+        }
+    }
+    state.log.push(entry);
+    if req.header("Authorization") != Some(state.authorization.as_str()) {
+        return MockResponse::new(401, "Unauthorized")
+            .header("WWW-Authenticate", "Basic realm=\"mock\"");
+    }
+
+    let path = req.path();
+    if req.method == "MKCALENDAR" {
+        return mkcalendar(state, path, &req.body);
+    }
+    if req.method == "PROPFIND" {
+        return propfind(state, path, req.header("Depth").unwrap_or("0"));
+    }
+    if req.method == "REPORT" && state.calendars.contains_key(path) {
+        return if req.body.contains("sync-collection") {
+            sync_collection(state, path, &req.body)
+        } else if req.body.contains("calendar-query") {
+            calendar_query(state, path, &req.body)
+        } else if req.body.contains("calendar-multiget") {
+            calendar_multiget(state, path, &req.body)
+        } else {
+            MockResponse::new(400, "unknown report")
+        };
+    }
+    let Some((calendar, name)) = state.split_event_path(path) else {
+        return MockResponse::new(404, "not found");
+    };
+    if matches!(req.method.as_str(), "PUT" | "DELETE") {
+        let current = state.calendars[&calendar]
+            .events
+            .get(name)
+            .map(|e| e.etag.as_str());
+        let failed = match (req.header("If-None-Match"), req.header("If-Match")) {
+            (Some("*"), _) => current.is_some(),
+            (_, Some(etag)) => current != Some(etag),
+            _ => false,
+        };
+        if failed {
+            return MockResponse::new(412, "precondition failed");
+        }
+    }
+    match req.method.as_str() {
+        "GET" => match state.calendars[&calendar].events.get(name) {
+            Some(event) => MockResponse::new(200, event.body.clone())
+                .header("Content-Type", "text/calendar; charset=utf-8")
+                .header("ETag", event.etag.clone()),
+            None => MockResponse::new(404, "not found"),
+        },
+        "PUT" => {
+            let (etag, created) = state.store(&calendar, name, &req.body);
+            MockResponse::new(if created { 201 } else { 204 }, "").header("ETag", etag)
+        }
+        "DELETE" => match state.remove(&calendar, name) {
+            true => MockResponse::new(204, ""),
+            false => MockResponse::new(404, "not found"),
+        },
+        _ => MockResponse::new(405, "method not allowed"),
+    }
+}
+
+fn propfind(state: &State, path: &str, depth: &str) -> MockResponse {
+    if path == "/.well-known/caldav" {
+        return MockResponse::new(301, "").header("Location", "/");
+    }
+    if path == "/" {
+        return multistatus(&[response(
+            "/",
+            &format!(
+                "<d:current-user-principal><d:href>{PRINCIPAL}</d:href></d:current-user-principal>"
+            ),
+        )]);
+    }
+    if path == PRINCIPAL {
+        return multistatus(&[response(
+            PRINCIPAL,
+            &format!("<c:calendar-home-set><d:href>{HOME}</d:href></c:calendar-home-set>"),
+        )]);
+    }
+    if path == HOME {
+        let mut responses = vec![response(
+            HOME,
+            "<d:resourcetype><d:collection/></d:resourcetype>",
+        )];
+        if depth != "0" {
+            for (href, calendar) in &state.calendars {
+                responses.push(calendar_response(state, href, calendar));
+            }
+        }
+        return multistatus(&responses);
+    }
+    if let Some(calendar) = state.calendars.get(path) {
+        let mut responses = vec![calendar_response(state, path, calendar)];
+        if depth != "0" {
+            for (name, event) in &calendar.events {
+                responses.push(event_response(&format!("{path}{name}"), event, false));
+            }
+        }
+        return multistatus(&responses);
+    }
+    MockResponse::new(404, "not found")
+}
+
+/// MKCALENDAR（set されたプロパティでカレンダーを作る）
+fn mkcalendar(state: &mut State, path: &str, body: &str) -> MockResponse {
+    let slug = path
+        .strip_prefix(HOME)
+        .and_then(|rest| rest.strip_suffix('/'))
+        .filter(|slug| !slug.is_empty() && !slug.contains('/'));
+    if slug.is_none() {
+        return MockResponse::new(403, "forbidden");
+    }
+    if state.calendars.contains_key(path) {
+        return MockResponse::new(405, "already exists");
+    }
+    let Ok(doc) = roxmltree::Document::parse(body) else {
+        return MockResponse::new(400, "bad xml");
+    };
+    let text = |name: &str| {
+        doc.descendants()
+            .find(|n| n.tag_name().name() == name)
+            .and_then(|n| n.text())
+            .map(str::to_string)
+    };
+    let calendar = MockCalendar {
+        display_name: text("displayname"),
+        description: text("calendar-description"),
+        color: text("calendar-color"),
+        timezone: text("calendar-timezone"),
+        components: doc
+            .descendants()
+            .filter(|n| n.tag_name().name() == "comp")
+            .filter_map(|n| n.attribute("name"))
+            .map(str::to_string)
+            .collect(),
+        events: BTreeMap::new(),
+    };
+    state.calendars.insert(path.to_string(), calendar);
+    MockResponse::new(201, "")
+}
+
+/// calendar-query REPORT（time-range にかかる VEVENT の ETag）
+fn calendar_query(state: &State, calendar: &str, body: &str) -> MockResponse {
+    let attr = |name: &str| {
+        let value = body.split_once(&format!("{name}=\""))?.1.split_once('"')?.0;
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ").ok()
+    };
+    let (Some(start), Some(end)) = (attr("start"), attr("end")) else {
+        return MockResponse::new(400, "time-range required");
+    };
+    let responses: Vec<String> = state.calendars[calendar]
+        .events
+        .iter()
+        .filter(|(_, event)| {
+            let (Some(dtstart), Some(dtend)) = (
+                utc_property(&event.body, "DTSTART"),
+                utc_property(&event.body, "DTEND"),
+            ) else {
+                return false;
+            };
+            dtstart < end && dtend > start
+        })
+        .map(|(name, event)| event_response(&format!("{calendar}{name}"), event, false))
+        .collect();
+    multistatus(&responses)
+}
+
+/// calendar-multiget REPORT（href ごとの ETag と中身）
+fn calendar_multiget(state: &State, calendar: &str, body: &str) -> MockResponse {
+    let events = &state.calendars[calendar].events;
+    let responses: Vec<String> = body
+        .split("<d:href>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</d:href>"))
+        .map(
+            |(href, _)| match events.get(&href[calendar.len().min(href.len())..]) {
+                Some(event) if href.starts_with(calendar) => event_response(href, event, true),
+                _ => not_found(href),
+            },
+        )
+        .collect();
+    multistatus(&responses)
+}
+
+/// 日時のプロパティを UTC にする（フローティングは日本時間とみなす）
+fn utc_property(ics: &str, name: &str) -> Option<NaiveDateTime> {
+    // VTIMEZONE の DTSTART は飛ばす
+    let line = ics
+        .lines()
+        .skip_while(|l| !l.starts_with("BEGIN:VEVENT"))
+        .find(|l| l.starts_with(&format!("{name}:")) || l.starts_with(&format!("{name};")))?;
+    let value = line.split_once(':')?.1.trim();
+    match value.strip_suffix('Z') {
+        Some(utc) => NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok(),
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|local| local - TimeDelta::hours(9)),
+    }
+}
+
+fn event_response(href: &str, event: &MockEvent, with_data: bool) -> String {
+    let mut props = format!(
+        "<d:getetag>{}</d:getetag>",
+        event.etag.replace('"', "&quot;")
+    );
+    if with_data {
+        props.push_str(&format!(
+            "<c:calendar-data>{}</c:calendar-data>",
+            escape(&event.body)
+        ));
+    }
+    response(href, &props)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn not_found(href: &str) -> String {
+    format!(
+        "<d:response><d:href>{href}</d:href>\
+<d:status>HTTP/1.1 404 Not Found</d:status></d:response>"
+    )
+}
+
+/// sync-collection REPORT
+fn sync_collection(state: &State, calendar: &str, body: &str) -> MockResponse {
+    let token = body
+        .split_once("<d:sync-token>")
+        .and_then(|(_, rest)| rest.split_once("</d:sync-token>"))
+        .map(|(token, _)| token)
+        .unwrap_or_default();
+    let since = token
+        .strip_prefix("http://mock.example/sync/")
+        .and_then(|seq| seq.parse::<u64>().ok())
+        .filter(|seq| *seq >= state.oldest_token && *seq <= state.sync_seq());
+    let Some(since) = since else {
+        return MockResponse::new(
+            403,
+            "<?xml version=\"1.0\"?><d:error xmlns:d=\"DAV:\"><d:valid-sync-token/></d:error>",
+        )
+        .header("Content-Type", "application/xml; charset=utf-8");
+    };
+
+    let mut changed: Vec<&str> = state
+        .changes
+        .iter()
+        .filter(|(seq, path)| *seq > since && path.starts_with(calendar))
+        .map(|(_, path)| path.as_str())
+        .collect();
+    changed.sort();
+    changed.dedup();
+
+    let events = &state.calendars[calendar].events;
+    let responses: Vec<String> = changed
+        .into_iter()
+        .map(|path| match events.get(&path[calendar.len()..]) {
+            Some(event) => event_response(path, event, false),
+            None => not_found(path),
+        })
+        .collect();
+    multistatus_with_token(&responses, Some(&state.sync_token()))
+}
+
+fn calendar_response(state: &State, href: &str, calendar: &MockCalendar) -> String {
+    let mut props = format!(
+        "<d:resourcetype><d:collection/><c:calendar/></d:resourcetype>\
+<d:sync-token>{}</d:sync-token>",
+        state.sync_token()
+    );
+    if let Some(name) = &calendar.display_name {
+        props.push_str(&format!("<d:displayname>{}</d:displayname>", escape(name)));
+    }
+    if let Some(description) = &calendar.description {
+        props.push_str(&format!(
+            "<c:calendar-description>{}</c:calendar-description>",
+            escape(description)
+        ));
+    }
+    if let Some(color) = &calendar.color {
+        props.push_str(&format!("<a:calendar-color>{color}</a:calendar-color>"));
+    }
+    props.push_str("<c:supported-calendar-component-set>");
+    for comp in &calendar.components {
+        props.push_str(&format!("<c:comp name=\"{comp}\"/>"));
+    }
+    props.push_str("</c:supported-calendar-component-set>");
+    response(href, &props)
+}
+
+fn response(href: &str, props: &str) -> String {
+    format!(
+        "<d:response><d:href>{href}</d:href><d:propstat><d:prop>{props}</d:prop>\
+<d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>"
+    )
+}
+
+fn multistatus(responses: &[String]) -> MockResponse {
+    multistatus_with_token(responses, None)
+}
+
+fn multistatus_with_token(responses: &[String], sync_token: Option<&str>) -> MockResponse {
+    let token = sync_token
+        .map(|t| format!("<d:sync-token>{t}</d:sync-token>"))
+        .unwrap_or_default();
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+<d:multistatus xmlns:d=\"DAV:\" xmlns:c=\"urn:ietf:params:xml:ns:caldav\" \
+xmlns:a=\"http://apple.com/ns/ical/\">{}{token}</d:multistatus>",
+        responses.concat()
+    );
+    MockResponse::new(207, body).header("Content-Type", "application/xml; charset=utf-8")
+}
@@ -0,0 +1,296 @@
+//! Google Calendar API (calendar/v3) のモック
+//!
+//! Bearer トークンを確かめ、`users/me/calendarList` と `calendars/{id}/events` の
+//! 一覧（ページ分割あり）・POST・PUT・DELETE に答える。
+//! PUT / DELETE は If-Match を見て、合わなければ 412 を返す。
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::DateTime;
+use serde_json::{Value, json};
+
+use super::{MockRequest, MockResponse, MockServer};
+
+/// API の入口のパス
+pub const API_PATH: &str = "/calendar/v3/";
+/// 一覧の1ページの件数（ページ分割を確かめるため小さくしておく）
+const PAGE_SIZE: usize = 2;
+
+#[derive(Debug, Clone)]
+struct MockCalendar {
+    summary: String,
+    access_role: String,
+    /// イベントID -> イベント（id と etag を含む JSON）
+    events: BTreeMap<String, Value>,
+}
+
+#[derive(Default)]
+struct State {
+    token: String,
+    /// カレンダーID -> カレンダー（追加順を保つため Vec）
+    calendars: Vec<(String, MockCalendar)>,
+    /// イベントID と ETag の連番
+    next_id: u64,
+    /// 受け付けたリクエスト（"GET /calendar/v3/users/me/calendarList" の形式、クエリは除く）
+    log: Vec<String>,
+}
+
+pub struct MockGoogle {
+    server: MockServer,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockGoogle {
+    /// `token` のアクセストークンだけを受け付ける API を立てる
+    pub fn start(token: &str) -> Self {
+        let state = Arc::new(Mutex::new(State {
+            token: token.to_string(),
+            ..State::default()
+        }));
+        let handler_state = Arc::clone(&state);
+        let server = MockServer::start(move |req| handle(&mut handler_state.lock().unwrap(), req));
+        MockGoogle { server, state }
+    }
+
+    pub fn with_calendar(self, id: &str, summary: &str, access_role: &str) -> Self {
+        self.state.lock().unwrap().calendars.push((
+            id.to_string(),
+            MockCalendar {
+                summary: summary.to_string(),
+                access_role: access_role.to_string(),
+                events: BTreeMap::new(),
+            },
+        ));
+        self
+    }
+
+    /// `GoogleClient::with_base_url` に渡す入口
+    pub fn base_url(&self) -> String {
+        format!("{}{API_PATH}", self.server.base_url())
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().log.clone()
+    }
+
+    /// 受け付けたリクエストのうち `method` のものの件数
+    pub fn count(&self, method: &str) -> usize {
+        let prefix = format!("{method} ");
+        self.requests()
+            .iter()
+            .filter(|r| r.starts_with(&prefix))
+            .count()
+    }
+
+    pub fn clear_requests(&self) {
+        self.state.lock().unwrap().log.clear();
+    }
+
+    /// イベントをサーバー側に直接置いて ID を返す（スマホや Swift版で作った想定）
+    pub fn insert_event(&self, calendar: &str, event: Value) -> String {
+        self.state.lock().unwrap().insert(calendar, event)
+    }
+
+    /// イベントをサーバー側で直接書き換える（スマホで編集した想定）
+    pub fn edit_event(&self, calendar: &str, id: &str, edit: impl FnOnce(&mut Value)) {
+        let mut state = self.state.lock().unwrap();
+        let etag = state.next_etag();
+        let event = state.calendar_mut(calendar).events.get_mut(id).unwrap();
+        edit(event);
+        event["etag"] = json!(etag);
+    }
+
+    pub fn events(&self, calendar: &str) -> Vec<Value> {
+        let mut state = self.state.lock().unwrap();
+        state
+            .calendar_mut(calendar)
+            .events
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+impl State {
+    fn calendar_mut(&mut self, id: &str) -> &mut MockCalendar {
+        self.calendars
+            .iter_mut()
+            .find(|(cal_id, _)| cal_id == id)
+            .map(|(_, cal)| cal)
+            .expect("calendar")
+    }
+
+    fn next_etag(&mut self) -> String {
+        self.next_id += 1;
+        format!("\"{}\"", self.next_id)
+    }
+
+    fn insert(&mut self, calendar: &str, mut event: Value) -> String {
+        let etag = self.next_etag();
+        let id = format!("event{}", self.next_id);
+        event["id"] = json!(id);
+        event["etag"] = json!(etag);
+        event["status"] = json!("confirmed");
+        self.calendar_mut(calendar).events.insert(id.clone(), event);
+        id
+    }
+}
+
+fn handle(state: &mut State, req: &MockRequest) -> MockResponse {
+    state.log.push(format!("{} {}", req.method, req.path()));
+    if req.header("Authorization") != Some(format!("Bearer {}", state.token).as_str()) {
+        return error(401, "Invalid Credentials");
+    }
+    let Some(rest) = req.path().strip_prefix(API_PATH) else {
+        return error(404, "Not Found");
+    };
+    let segments: Vec<String> = rest.split('/').map(percent_decode).collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    match (req.method.as_str(), segments.as_slice()) {
+        ("GET", ["users", "me", "calendarList"]) => {
+            let items: Vec<Value> = state
+                .calendars
+                .iter()
+                .map(|(id, cal)| {
+                    json!({"id": id, "summary": cal.summary, "accessRole": cal.access_role})
+                })
+                .collect();
+            page(req, items)
+        }
+        (method, ["calendars", calendar, "events", rest @ ..]) => {
+            if !state.calendars.iter().any(|(id, _)| id == calendar) {
+                return error(404, "Not Found");
+            }
+            events(state, req, method, calendar, rest.first().copied())
+        }
+        _ => error(404, "Not Found"),
+    }
+}
+
+fn events(
+    state: &mut State,
+    req: &MockRequest,
+    method: &str,
+    calendar: &str,
+    event_id: Option<&str>,
+) -> MockResponse {
+    let Some(event_id) = event_id else {
+        return match method {
+            "GET" => list_events(state, req, calendar),
+            "POST" => {
+                let Ok(event) = serde_json::from_str::<Value>(&req.body) else {
+                    return error(400, "Bad Request");
+                };
+                let id = state.insert(calendar, event);
+                ok(&state.calendar_mut(calendar).events[&id])
+            }
+            _ => error(405, "Method Not Allowed"),
+        };
+    };
+
+    let current = state.calendar_mut(calendar).events.get(event_id).cloned();
+    let Some(current) = current else {
+        return error(if method == "DELETE" { 410 } else { 404 }, "Not Found");
+    };
+    if let Some(etag) = req.header("If-Match")
+        && current["etag"].as_str() != Some(etag)
+    {
+        return error(412, "Precondition Failed");
+    }
+    match method {
+        "PUT" => {
+            let Ok(mut event) = serde_json::from_str::<Value>(&req.body) else {
+                return error(400, "Bad Request");
+            };
+            let etag = state.next_etag();
+            event["id"] = json!(event_id);
+            event["etag"] = json!(etag);
+            event["status"] = json!("confirmed");
+            state
+                .calendar_mut(calendar)
+                .events
+                .insert(event_id.to_string(), event.clone());
+            ok(&event)
+        }
+        "DELETE" => {
+            state.calendar_mut(calendar).events.remove(event_id);
+            MockResponse::new(204, "")
+        }
+        _ => error(405, "Method Not Allowed"),
+    }
+}
+
+/// events.list（timeMin 〜 timeMax にかかるイベント）
+fn list_events(state: &mut State, req: &MockRequest, calendar: &str) -> MockResponse {
+    let time = |key: &str| {
+        req.query(key)
+            .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+    };
+    let event_time = |event: &Value, key: &str| {
+        DateTime::parse_from_rfc3339(event[key]["dateTime"].as_str()?).ok()
+    };
+    let (time_min, time_max) = (time("timeMin"), time("timeMax"));
+    let items: Vec<Value> = state
+        .calendar_mut(calendar)
+        .events
+        .values()
+        .filter(|event| {
+            let (Some(start), Some(end)) = (event_time(event, "start"), event_time(event, "end"))
+            else {
+                return false;
+            };
+            time_min.is_none_or(|min| end > min) && time_max.is_none_or(|max| start < max)
+        })
+        .cloned()
+        .collect();
+    page(req, items)
+}
+
+/// `pageToken`（先頭からの件数）から PAGE_SIZE 件を返す
+fn page(req: &MockRequest, items: Vec<Value>) -> MockResponse {
+    let offset: usize = req
+        .query("pageToken")
+        .and_then(|t| t.parse().ok())
+        .unwrap_or(0);
+    let end = (offset + PAGE_SIZE).min(items.len());
+    let mut body = json!({"kind": "calendar#list", "items": items[offset.min(end)..end]});
+    if end < items.len() {
+        body["nextPageToken"] = json!(end.to_string());
+    }
+    ok(&body)
+}
+
+fn ok(body: &Value) -> MockResponse {
+    MockResponse::new(200, body.to_string()).header("Content-Type", "application/json")
+}
+
+fn error(status: u16, message: &str) -> MockResponse {
+    let body = json!({"error": {"code": status, "message": message}});
+    MockResponse::new(status, body.to_string()).header("Content-Type", "application/json")
+}
+
+/// "a%40b" -> "a@b"
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,169 @@
+//! 結合テスト用のローカルサーバー・一時ファイル・シフト
+//!
+//! 本物のサイトの代わりに `127.0.0.1` の空きポートで待ち受け、`cargo test` をネットワークなしで回す。
+#![allow(dead_code)]
+
+pub mod caldav;
+pub mod google;
+pub mod oauth;
+pub mod shiftweb;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use chrono::NaiveDate;
+use shift_sync_rc::month::YearMonth;
+use shift_sync_rc::shift::{DEFAULT_TITLE, DEFAULT_TZ, Shift};
+use shift_sync_rc::sync::SyncWindow;
+use tiny_http::{Header, Response, Server};
+use url::form_urlencoded;
+
+/// テストごとの一時ディレクトリに置く `file` のパス（前回の残りは消しておく）
+pub fn temp_path(name: &str, file: impl AsRef<Path>) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("shift_sync_{name}_{}", std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    dir.join(file)
+}
+
+/// 渋谷店の `y`/`m`/`d` のシフト（時刻は "22:00"〜"06:00" のような日またぎも可）
+pub fn shift(y: i32, m: u32, d: u32, start: &str, end: &str) -> Shift {
+    let day = NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    Shift::on_date(DEFAULT_TITLE, day, start, end, "渋谷店", DEFAULT_TZ).unwrap()
+}
+
+/// 2026年1月だけを同期する範囲
+pub fn january() -> SyncWindow {
+    SyncWindow::from_months(&[YearMonth::new(2026, 1).unwrap()]).unwrap()
+}
+
+/// モックが受け取ったリクエスト
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    /// パスとクエリ（`/shift.php?mod=look`）
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockRequest {
+    pub fn path(&self) -> &str {
+        self.url.split('?').next().unwrap_or_default()
+    }
+
+    pub fn query_string(&self) -> &str {
+        self.url.split_once('?').map(|(_, q)| q).unwrap_or_default()
+    }
+
+    pub fn query(&self, key: &str) -> Option<String> {
+        form_urlencoded::parse(self.query_string().as_bytes())
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn form(&self, key: &str) -> Option<String> {
+        form_urlencoded::parse(self.body.as_bytes())
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+    }
+
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.header("Cookie")?
+            .split(';')
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v)
+    }
+}
+
+/// モックが返すレスポンス
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        MockResponse {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+}
+
+/// リクエストごとに `handler` を呼ぶ HTTP サーバー（drop で停止）
+pub struct MockServer {
+    server: Arc<Server>,
+    thread: Option<JoinHandle<()>>,
+    base_url: String,
+}
+
+impl MockServer {
+    pub fn start(handler: impl Fn(&MockRequest) -> MockResponse + Send + 'static) -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("bind mock server"));
+        let port = server.server_addr().to_ip().expect("tcp listener").port();
+        let base_url = format!("http://127.0.0.1:{port}");
+
+        let incoming = Arc::clone(&server);
+        let thread = std::thread::spawn(move || {
+            for mut request in incoming.incoming_requests() {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).ok();
+                let mock_request = MockRequest {
+                    method: request.method().as_str().to_string(),
+                    url: request.url().to_string(),
+                    headers: request
+                        .headers()
+                        .iter()
+                        .map(|h| (h.field.as_str().to_string(), h.value.as_str().to_string()))
+                        .collect(),
+                    body,
+                };
+                let mock_response = handler(&mock_request);
+                let mut response = Response::from_string(mock_response.body)
+                    .with_status_code(mock_response.status);
+                for (name, value) in &mock_response.headers {
+                    response.add_header(
+                        Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("header"),
+                    );
+                }
+                request.respond(response).ok();
+            }
+        });
+
+        MockServer {
+            server,
+            thread: Some(thread),
+            base_url,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
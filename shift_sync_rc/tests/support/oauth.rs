@@ -0,0 +1,217 @@
+//! OAuth 2.0 の認可サーバーのモック
+//!
+//! `/auth`（認可エンドポイント、すぐにリダイレクトで認可コードを返す）、
+//! `/device/code`（デバイス認可）、`/token`（トークン）に答える。
+//! 認可コードの交換では PKCE の code_verifier と redirect_uri を確かめる。
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Value, json};
+use shift_sync_rc::google::oauth::{OAuthConfig, pkce_challenge};
+
+use super::{MockRequest, MockResponse, MockServer};
+
+pub const DEVICE_CODE: &str = "device-code-1";
+pub const USER_CODE: &str = "WDJB-MJHT";
+pub const AUTH_CODE: &str = "auth-code-1";
+pub const REFRESH_TOKEN: &str = "refresh-1";
+
+#[derive(Default)]
+struct State {
+    client_id: String,
+    /// デバイスフローで authorization_pending を返す残り回数
+    pending: usize,
+    /// 最初のポーリングに slow_down を返すか
+    slow_down: bool,
+    /// ユーザーがログインを拒否した想定にするか
+    deny: bool,
+    /// リダイレクトで返す state を書き換えるか（なりすましの想定）
+    tamper_state: bool,
+    /// `/auth` で受け取った code_challenge と redirect_uri
+    challenge: Option<(String, String)>,
+    /// 発行したアクセストークンの連番
+    issued: u64,
+    /// 受け付けた grant_type
+    grants: Vec<String>,
+}
+
+pub struct MockAuthServer {
+    server: MockServer,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockAuthServer {
+    /// `client_id` のクライアントだけを受け付ける認可サーバーを立てる
+    pub fn start(client_id: &str) -> Self {
+        let state = Arc::new(Mutex::new(State {
+            client_id: client_id.to_string(),
+            ..State::default()
+        }));
+        let handler_state = Arc::clone(&state);
+        let server = MockServer::start(move |req| handle(&mut handler_state.lock().unwrap(), req));
+        MockAuthServer { server, state }
+    }
+
+    /// デバイスフローで `count` 回 authorization_pending を返す
+    pub fn with_pending(self, count: usize) -> Self {
+        self.state.lock().unwrap().pending = count;
+        self
+    }
+
+    pub fn with_slow_down(self) -> Self {
+        self.state.lock().unwrap().slow_down = true;
+        self
+    }
+
+    pub fn denying(self) -> Self {
+        self.state.lock().unwrap().deny = true;
+        self
+    }
+
+    pub fn tampering_state(self) -> Self {
+        self.state.lock().unwrap().tamper_state = true;
+        self
+    }
+
+    /// このサーバーを向いた設定
+    pub fn config(&self) -> OAuthConfig {
+        let base = self.server.base_url();
+        OAuthConfig {
+            auth_url: format!("{base}/auth"),
+            token_url: format!("{base}/token"),
+            device_url: format!("{base}/device/code"),
+            ..OAuthConfig::google(&self.state.lock().unwrap().client_id, Some("secret"))
+        }
+    }
+
+    /// トークンエンドポイントが受け付けた grant_type（順番どおり）
+    pub fn grants(&self) -> Vec<String> {
+        self.state.lock().unwrap().grants.clone()
+    }
+}
+
+fn handle(state: &mut State, req: &MockRequest) -> MockResponse {
+    match (req.method.as_str(), req.path()) {
+        ("GET", "/auth") => authorize(state, req),
+        ("POST", "/device/code") => {
+            if req.form("client_id").as_deref() != Some(state.client_id.as_str()) {
+                return error(401, "invalid_client");
+            }
+            ok(&json!({
+                "device_code": DEVICE_CODE,
+                "user_code": USER_CODE,
+                "verification_url": "https://www.google.com/device",
+                "expires_in": 1800,
+                "interval": 5,
+            }))
+        }
+        ("POST", "/token") => token(state, req),
+        _ => MockResponse::new(404, "Not Found"),
+    }
+}
+
+/// 認可エンドポイント（ログイン画面を飛ばしてすぐ redirect_uri に戻す）
+fn authorize(state: &mut State, req: &MockRequest) -> MockResponse {
+    let (Some(redirect_uri), Some(challenge), Some(client_state)) = (
+        req.query("redirect_uri"),
+        req.query("code_challenge"),
+        req.query("state"),
+    ) else {
+        return MockResponse::new(400, "missing parameter");
+    };
+    if req.query("client_id").as_deref() != Some(state.client_id.as_str())
+        || req.query("code_challenge_method").as_deref() != Some("S256")
+    {
+        return MockResponse::new(400, "invalid request");
+    }
+    state.challenge = Some((challenge, redirect_uri.clone()));
+
+    let client_state = if state.tamper_state {
+        "forged".to_string()
+    } else {
+        client_state
+    };
+    let result = if state.deny {
+        ("error", "access_denied")
+    } else {
+        ("code", AUTH_CODE)
+    };
+    let location =
+        url::Url::parse_with_params(&redirect_uri, &[result, ("state", client_state.as_str())])
+            .expect("redirect_uri");
+    MockResponse::new(302, "").header("Location", location.as_str())
+}
+
+fn token(state: &mut State, req: &MockRequest) -> MockResponse {
+    if req.form("client_id").as_deref() != Some(state.client_id.as_str())
+        || req.form("client_secret").as_deref() != Some("secret")
+    {
+        return error(401, "invalid_client");
+    }
+    let grant = req.form("grant_type").unwrap_or_default();
+    state.grants.push(grant.clone());
+
+    match grant.as_str() {
+        "urn:ietf:params:oauth:grant-type:device_code" => {
+            if req.form("device_code").as_deref() != Some(DEVICE_CODE) {
+                return error(400, "invalid_grant");
+            }
+            if state.deny {
+                return error(400, "access_denied");
+            }
+            if state.slow_down {
+                state.slow_down = false;
+                return error(400, "slow_down");
+            }
+            if state.pending > 0 {
+                state.pending -= 1;
+                return error(400, "authorization_pending");
+            }
+            issue(state, true)
+        }
+        "authorization_code" => {
+            let Some((challenge, redirect_uri)) = state.challenge.take() else {
+                return error(400, "invalid_grant");
+            };
+            let verified = req
+                .form("code_verifier")
+                .is_some_and(|verifier| pkce_challenge(&verifier) == challenge);
+            if req.form("code").as_deref() != Some(AUTH_CODE)
+                || req.form("redirect_uri") != Some(redirect_uri)
+                || !verified
+            {
+                return error(400, "invalid_grant");
+            }
+            issue(state, true)
+        }
+        // Google はリフレッシュ時に新しいリフレッシュトークンを返さない
+        "refresh_token" if req.form("refresh_token").as_deref() == Some(REFRESH_TOKEN) => {
+            issue(state, false)
+        }
+        "refresh_token" => error(400, "invalid_grant"),
+        _ => error(400, "unsupported_grant_type"),
+    }
+}
+
+fn issue(state: &mut State, with_refresh: bool) -> MockResponse {
+    state.issued += 1;
+    let mut body = json!({
+        "access_token": format!("access-{}", state.issued),
+        "expires_in": 3599,
+        "scope": "https://www.googleapis.com/auth/calendar",
+        "token_type": "Bearer",
+    });
+    if with_refresh {
+        body["refresh_token"] = json!(REFRESH_TOKEN);
+    }
+    ok(&body)
+}
+
+fn ok(body: &Value) -> MockResponse {
+    MockResponse::new(200, body.to_string()).header("Content-Type", "application/json")
+}
+
+fn error(status: u16, code: &str) -> MockResponse {
+    let body = json!({"error": code, "error_description": format!("mock: {code}")});
+    MockResponse::new(status, body.to_string()).header("Content-Type", "application/json")
+}
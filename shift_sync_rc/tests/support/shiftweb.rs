@@ -0,0 +1,144 @@
+//! ShiftWeb のモック
+//!
+//! `login.php` で発行したセッション Cookie を持ち、正しい ID・パスワードで
+//! `check_login.php` を通ったクライアントにだけ `shift.php` のシフト表を返す。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::{MockRequest, MockResponse, MockServer};
+
+const SESSION_COOKIE: &str = "PHPSESSID";
+
+const LOGIN_PAGE: &str = "<!DOCTYPE html>\n<html lang=\"ja\"><head><meta charset=\"UTF-8\">\
+<title>ログイン | ShiftWeb</title></head><body><form id=\"login\"></form></body></html>\n";
+
+#[derive(Default)]
+struct State {
+    id: String,
+    password: String,
+    /// "YYYY-MM" -> シフトページ HTML
+    pages: HashMap<String, String>,
+    /// セッションID -> ログイン済みか
+    sessions: HashMap<String, bool>,
+    /// 受け付けたリクエスト（"GET /shift.php?..." の形式）
+    log: Vec<String>,
+}
+
+pub struct MockShiftWeb {
+    server: MockServer,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockShiftWeb {
+    /// `id` / `password` でだけログインできる ShiftWeb を立てる
+    pub fn start(id: &str, password: &str) -> Self {
+        let state = Arc::new(Mutex::new(State {
+            id: id.to_string(),
+            password: password.to_string(),
+            ..State::default()
+        }));
+        let handler_state = Arc::clone(&state);
+        let server = MockServer::start(move |req| handle(&mut handler_state.lock().unwrap(), req));
+        MockShiftWeb { server, state }
+    }
+
+    /// `month`（"YYYY-MM"）のシフトページとして返す HTML を登録する
+    pub fn with_page(self, month: &str, html: &str) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .pages
+            .insert(month.to_string(), html.to_string());
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.server.base_url()
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().log.clone()
+    }
+}
+
+fn handle(state: &mut State, req: &MockRequest) -> MockResponse {
+    state.log.push(format!("{} {}", req.method, req.url));
+    match (req.method.as_str(), req.path()) {
+        ("GET", "/login.php") => {
+            let session = format!("sess{}", state.sessions.len() + 1);
+            state.sessions.insert(session.clone(), false);
+            html(LOGIN_PAGE).header(
+                "Set-Cookie",
+                format!("{SESSION_COOKIE}={session}; path=/; HttpOnly"),
+            )
+        }
+        ("POST", "/cont/login/check_login.php") => check_login(state, req),
+        ("GET", "/shift.php") => shift_page(state, req),
+        _ => MockResponse::new(404, "not found"),
+    }
+}
+
+fn check_login(state: &mut State, req: &MockRequest) -> MockResponse {
+    let Some(session) = req.cookie(SESSION_COOKIE).map(str::to_string) else {
+        return MockResponse::new(403, "no session");
+    };
+    if !state.sessions.contains_key(&session) {
+        return MockResponse::new(403, "unknown session");
+    }
+
+    let origin = format!("http://{}", req.header("Host").unwrap_or_default());
+    let referer = format!("{origin}/login.php?err=1");
+    if req.header("Origin") != Some(origin.as_str())
+        || req.header("Referer") != Some(referer.as_str())
+        || req.header("X-Requested-With") != Some("XMLHttpRequest")
+    {
+        return MockResponse::new(400, "bad headers");
+    }
+
+    let id = req.form("id").unwrap_or_default();
+    let password = req.form("password").unwrap_or_default();
+    if id != state.id || password != state.password || req.query_string() != state.id {
+        return MockResponse::new(401, "{\"result\":\"NG\"}");
+    }
+    state.sessions.insert(session, true);
+    MockResponse::new(200, "{\"result\":\"OK\"}").header("Content-Type", "application/json")
+}
+
+fn shift_page(state: &State, req: &MockRequest) -> MockResponse {
+    let logged_in = req
+        .cookie(SESSION_COOKIE)
+        .and_then(|session| state.sessions.get(session))
+        .copied()
+        .unwrap_or(false);
+    // 未ログインだと本物と同じくログイン画面が返ってくる
+    if !logged_in {
+        return html(LOGIN_PAGE);
+    }
+    if req.query("mod").as_deref() != Some("look") {
+        return MockResponse::new(400, "mod is required");
+    }
+    let Some(month) = req.query("date2") else {
+        return MockResponse::new(400, "date2 is required");
+    };
+    match state.pages.get(&month) {
+        Some(page) => html(page),
+        None => html(&empty_page(&month)),
+    }
+}
+
+/// シフトが1件もない月のページ
+fn empty_page(month: &str) -> String {
+    let (year, month) = month.split_once('-').unwrap_or(("", ""));
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"ja\"><head><meta charset=\"UTF-8\"></head><body>\
+<h3 class=\"btn-block\">{year}年{}月の確定シフト</h3>\
+<table id=\"shiftTable\"><tr><th>日付</th><th>店舗</th><th>時間</th></tr></table>\
+</body></html>\n",
+        month.trim_start_matches('0')
+    )
+}
+
+fn html(body: &str) -> MockResponse {
+    MockResponse::new(200, body).header("Content-Type", "text/html; charset=UTF-8")
+}
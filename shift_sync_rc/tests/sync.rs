@@ -0,0 +1,130 @@
+mod support;
+
+use chrono::NaiveDate;
+use shift_sync_rc::backend::memory::MemoryBackend;
+use shift_sync_rc::backend::{CalendarBackend, Capabilities};
+use shift_sync_rc::shift::{DEFAULT_TITLE, Shift};
+use shift_sync_rc::sync::{SyncAction, SyncWindow, sync_shifts};
+use support::{january, shift};
+
+#[test]
+fn adds_updates_and_deletes_inside_window() {
+    let mut backend = MemoryBackend::new();
+    let kept = shift(2026, 1, 5, "10:00", "19:00");
+    let changed = shift(2026, 1, 6, "10:00", "19:00");
+    let removed = shift(2026, 1, 20, "10:00", "19:00");
+    let history = shift(2025, 12, 20, "10:00", "19:00");
+    let everything = SyncWindow {
+        start: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
+        end: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+    };
+    let all = [
+        kept.clone(),
+        changed.clone(),
+        removed.clone(),
+        history.clone(),
+    ];
+    let summary = sync_shifts(&mut backend, &all, everything).unwrap();
+    assert_eq!((summary.added, summary.updated, summary.deleted), (4, 0, 0));
+
+    backend.clear_writes();
+    let changed = changed.with_memo("早番");
+    let added = shift(2026, 1, 31, "22:00", "06:00");
+    let shifts = [kept.clone(), changed.clone(), added.clone()];
+    let summary = sync_shifts(&mut backend, &shifts, january()).unwrap();
+    assert_eq!(summary.short_description(), "+1 ↻1 -1");
+    assert_eq!(
+        backend.writes(),
+        [
+            (SyncAction::Delete, removed.uid()),
+            (SyncAction::Update, changed.uid()),
+            (SyncAction::Create, added.uid()),
+        ]
+    );
+    // 同期範囲の外にある先月のシフトは残る
+    assert!(backend.get(&history.uid()).is_some());
+    assert_eq!(backend.get(&changed.uid()).unwrap().shift.memo, "早番");
+
+    backend.clear_writes();
+    let summary = sync_shifts(&mut backend, &shifts, january()).unwrap();
+    assert_eq!(summary.short_description(), "変更なし");
+    assert!(backend.writes().is_empty());
+}
+
+#[test]
+fn duplicate_shifts_are_written_once() {
+    let mut backend = MemoryBackend::new();
+    let s = shift(2026, 1, 5, "10:00", "19:00");
+    let summary = sync_shifts(&mut backend, &[s.clone(), s], january()).unwrap();
+    assert_eq!(summary.added, 1);
+    assert_eq!(backend.writes().len(), 1);
+}
+
+#[test]
+fn keeps_edits_made_elsewhere() {
+    let mut backend = MemoryBackend::new();
+    let s = shift(2026, 1, 5, "10:00", "19:00");
+    sync_shifts(&mut backend, std::slice::from_ref(&s), january()).unwrap();
+    backend.edit(&s.uid(), |shift| {
+        shift.title = "バイト（代打あり）".to_string()
+    });
+
+    let summary = sync_shifts(&mut backend, &[s.clone().with_memo("レジ")], january()).unwrap();
+    assert_eq!(summary.updated, 0);
+    assert_eq!(summary.conflicts.len(), 1);
+    assert_eq!(summary.conflicts[0].action, SyncAction::Update);
+    assert_eq!(
+        backend.get(&s.uid()).unwrap().shift.title,
+        "バイト（代打あり）"
+    );
+}
+
+#[test]
+fn overwrites_when_backend_cannot_detect_edits() {
+    let mut backend = MemoryBackend::with_capabilities(Capabilities::default());
+    let s = shift(2026, 1, 5, "10:00", "19:00");
+    sync_shifts(&mut backend, std::slice::from_ref(&s), january()).unwrap();
+    backend.edit(&s.uid(), |shift| shift.title = "シフト".to_string());
+    assert!(!backend.capabilities().detects_edits);
+
+    let summary = sync_shifts(&mut backend, std::slice::from_ref(&s), january()).unwrap();
+    assert_eq!(summary.updated, 1);
+    assert!(summary.conflicts.is_empty());
+    assert_eq!(backend.get(&s.uid()).unwrap().shift.title, DEFAULT_TITLE);
+}
+
+#[test]
+fn adopts_events_written_by_other_clients() {
+    let mut backend = MemoryBackend::new();
+    let s = shift(2026, 1, 5, "10:00", "19:00");
+    // 記録を持たない別の実装（Go版など）が書いたイベント
+    backend.insert(s.clone());
+
+    let summary = sync_shifts(&mut backend, std::slice::from_ref(&s), january()).unwrap();
+    assert_eq!(summary.short_description(), "変更なし");
+
+    let summary = sync_shifts(&mut backend, &[s.with_memo("レジ")], january()).unwrap();
+    assert_eq!(summary.updated, 1);
+    assert!(summary.conflicts.is_empty());
+}
+
+#[test]
+fn races_become_conflicts_and_failures_continue() {
+    let mut backend = MemoryBackend::new();
+    let raced = shift(2026, 1, 5, "10:00", "19:00");
+    let rejected = shift(2026, 1, 6, "10:00", "19:00");
+    let fine = shift(2026, 1, 7, "10:00", "19:00");
+    let shifts = [raced.clone(), rejected.clone(), fine.clone()];
+    sync_shifts(&mut backend, &shifts, january()).unwrap();
+
+    backend.race_on(&raced.uid());
+    backend.fail_on(&rejected.uid());
+    let shifts: Vec<Shift> = shifts.into_iter().map(|s| s.with_memo("早番")).collect();
+    let summary = sync_shifts(&mut backend, &shifts, january()).unwrap();
+    assert_eq!(summary.updated, 1);
+    assert_eq!(summary.conflicts.len(), 1);
+    assert_eq!(summary.conflicts[0].uid, raced.uid());
+    assert_eq!(summary.failures.len(), 1);
+    assert_eq!(summary.failures[0].uid, rejected.uid());
+    assert_eq!(backend.get(&fine.uid()).unwrap().shift.memo, "早番");
+}